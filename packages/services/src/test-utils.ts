@@ -106,6 +106,7 @@ export const mockIndexedDb: Mocked<IndexedDbInterface> = {
   updatePrice: vi.fn(mockDisabled),
   upsertAuction: vi.fn(mockDisabled),
   upsertValidatorInfo: vi.fn(mockDisabled),
+  walletDataConstants: vi.fn(mockDisabled),
   getPositionsByStrategyStream: vi.fn(mockDisabled),
   getPositionMetadataById: vi.fn(mockDisabled),
 };
@@ -126,6 +127,7 @@ export interface ShieldedPoolMock {
 
 export interface ViewServerMock {
   fullViewingKey?: FullViewingKey;
+  enrichTransaction?: Mock;
 }
 
 export interface MockQuerier {
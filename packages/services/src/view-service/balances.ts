@@ -13,7 +13,11 @@ import {
   ValueView,
   ValueView_KnownAssetId,
 } from '@penumbra-zone/protobuf/penumbra/core/asset/v1/asset_pb';
-import { AddressIndex, AddressView } from '@penumbra-zone/protobuf/penumbra/core/keys/v1/keys_pb';
+import {
+  Address,
+  AddressIndex,
+  AddressView,
+} from '@penumbra-zone/protobuf/penumbra/core/keys/v1/keys_pb';
 import {
   AddressByIndexRequest,
   AssetMetadataByIdRequest,
@@ -26,6 +30,7 @@ import { assetMetadataById } from './asset-metadata-by-id.js';
 import { addressByIndex } from './address-by-index.js';
 import { Amount } from '@penumbra-zone/protobuf/penumbra/core/num/v1/num_pb';
 import { Base64Str, uint8ArrayToBase64 } from '@penumbra-zone/types/base64';
+import { isTransparentAddress } from '@penumbra-zone/types/address';
 import { addLoHi } from '@penumbra-zone/types/lo-hi';
 import { IndexedDbInterface } from '@penumbra-zone/types/indexed-db';
 import { isZero, multiplyAmountByNumber } from '@penumbra-zone/types/amount';
@@ -75,10 +80,27 @@ export const balances: Impl['balances'] = async function* (req, ctx) {
  *   "nDjzm+ldIrNMJha1anGMDVxpA5cLCPnUYQ1clmHF1gw=": BalancesResponse
  *   }
  * }
+ *
+ * Notes received on the transparent address (t-addr) belong to account 0, but are kept
+ * apart from its other notes under a "t:" prefixed asset id, so the t-addr's balance is
+ * reported with the t-addr as its address.
  */
-type BalancesMap = Record<Base64Str, BalancesResponse>;
+type BalancesMap = Record<string, BalancesResponse>;
 type AccountMap = Record<AddressIndex['account'], BalancesMap>;
 
+const TRANSPARENT_KEY_PREFIX = 't:';
+
+const balanceKey = (assetId: Base64Str, transparent: boolean): string =>
+  transparent ? `${TRANSPARENT_KEY_PREFIX}${assetId}` : assetId;
+
+const assetIdOfBalanceKey = (key: string): string =>
+  key.startsWith(TRANSPARENT_KEY_PREFIX) ? key.slice(TRANSPARENT_KEY_PREFIX.length) : key;
+
+const getTransparentAddress = (n: SpendableNoteRecord): Address | undefined => {
+  const address = n.note?.address;
+  return address && isTransparentAddress(address) ? address : undefined;
+};
+
 interface BalancesAggregatorProps {
   ctx: HandlerContext;
   indexedDb: IndexedDbInterface;
@@ -105,18 +127,19 @@ class BalancesAggregator {
   }
 
   async add(n: SpendableNoteRecord) {
-    const accountNumber = n.addressIndex?.account ?? 0;
+    const transparent = !!getTransparentAddress(n);
+    const accountNumber = transparent ? 0 : (n.addressIndex?.account ?? 0);
 
     // Initialize account obj if not present
     this.accounts[accountNumber] ??= {};
 
     const assetId = getAssetIdFromRecord(n);
-    const assetIdBase64 = uint8ArrayToBase64(assetId.inner);
+    const key = balanceKey(uint8ArrayToBase64(assetId.inner), transparent);
 
-    this.accounts[accountNumber][assetIdBase64] ??= await this.initializeBalResponse(n);
+    this.accounts[accountNumber][key] ??= await this.initializeBalResponse(n);
 
     // eslint-disable-next-line @typescript-eslint/no-non-null-assertion -- TODO: justify non-null assertion
-    const valueView = this.accounts[accountNumber][assetIdBase64].balanceView!;
+    const valueView = this.accounts[accountNumber][key].balanceView!;
     this.aggregateAmount(valueView, n);
     await this.aggregateEquivalentValues(valueView, n);
   }
@@ -131,9 +154,9 @@ class BalancesAggregator {
       .flatMap(([, balances]) =>
         Object.entries(balances)
           .filter(
-            ([assetId]) =>
+            ([key]) =>
               !assetIdFilter || // No asset id filter requested
-              assetId === uint8ArrayToBase64(assetIdFilter.inner), // Asset id's match
+              assetIdOfBalanceKey(key) === uint8ArrayToBase64(assetIdFilter.inner), // Asset id's match
           )
           .map(([, balances]) => balances),
       );
@@ -194,8 +217,11 @@ class BalancesAggregator {
   }
 
   private async initializeBalResponse(n: SpendableNoteRecord) {
+    const transparentAddress = getTransparentAddress(n);
     const [accountAddress, balanceView] = await Promise.all([
-      this.initializeAddressView(this.ctx, n.addressIndex),
+      transparentAddress
+        ? this.transparentAddressView(transparentAddress)
+        : this.initializeAddressView(this.ctx, n.addressIndex),
       this.initializeValueView(this.ctx, getAssetIdFromRecord(n)),
     ]);
     return new BalancesResponse({ accountAddress, balanceView });
@@ -251,4 +277,17 @@ class BalancesAggregator {
       },
     });
   }
+
+  // The t-addr is not derived from an address index; it belongs to account 0.
+  private transparentAddressView(address: Address): AddressView {
+    return new AddressView({
+      addressView: {
+        case: 'decoded',
+        value: {
+          address,
+          index: new AddressIndex({ account: 0 }),
+        },
+      },
+    });
+  }
 }
@@ -7,7 +7,13 @@ import {
 import { createContextValues, createHandlerContext, HandlerContext } from '@connectrpc/connect';
 import { ViewService } from '@penumbra-zone/protobuf';
 import { servicesCtx } from '../ctx/prax.js';
-import { mockIndexedDb, MockServices, TendermintMock, testFullViewingKey } from '../test-utils.js';
+import {
+  mockIndexedDb,
+  MockServices,
+  TendermintMock,
+  testFullViewingKey,
+  ViewServerMock,
+} from '../test-utils.js';
 import { transactionInfoByHash } from './transaction-info-by-hash.js';
 import { TransactionId } from '@penumbra-zone/protobuf/penumbra/core/txhash/v1/txhash_pb';
 import type { ServicesInterface } from '@penumbra-zone/types/services';
@@ -27,6 +33,7 @@ describe('TransactionInfoByHash request handler', () => {
   let mockServices: MockServices;
   let mockCtx: HandlerContext;
   let mockTendermint: TendermintMock;
+  let mockViewServer: ViewServerMock;
 
   beforeEach(() => {
     vi.resetAllMocks();
@@ -34,10 +41,14 @@ describe('TransactionInfoByHash request handler', () => {
     mockTendermint = {
       getTransaction: vi.fn(),
     };
+    mockViewServer = {
+      enrichTransaction: vi.fn().mockResolvedValue([]),
+    };
     mockServices = {
       getWalletServices: vi.fn(() =>
         Promise.resolve({
           indexedDb: mockIndexedDb,
+          viewServer: mockViewServer,
           querier: {
             tendermint: mockTendermint,
           },
@@ -87,6 +98,7 @@ describe('TransactionInfoByHash request handler', () => {
       await transactionInfoByHash(new TransactionInfoByHashRequest({ id: transactionId }), mockCtx),
     );
     expect(txInfoByHashResponse.txInfo?.transaction!.equals(transaction)).toBeTruthy();
+    expect(mockViewServer.enrichTransaction).toHaveBeenCalledWith(transaction);
   });
 
   test('should get an error if TransactionId is not passed', async () => {
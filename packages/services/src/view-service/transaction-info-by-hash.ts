@@ -15,7 +15,7 @@ export const transactionInfoByHash: Impl['transactionInfoByHash'] = async (req,
   }
 
   const services = await ctx.values.get(servicesCtx)();
-  const { indexedDb, querier } = await services.getWalletServices();
+  const { indexedDb, querier, viewServer } = await services.getWalletServices();
   const fvk = ctx.values.get(fvkCtx);
 
   // First, check the database for the transaction.
@@ -27,6 +27,9 @@ export const transactionInfoByHash: Impl['transactionInfoByHash'] = async (req,
     throw new ConnectError('Transaction not available', Code.NotFound);
   }
 
+  // Fill in the return address of any notes the transaction created for the wallet.
+  await viewServer.enrichTransaction(transaction);
+
   // TODO: avoid regenerating the transaction info (TxV, TxP, summary)
   // and query from database if it already exists.
   const { txp: perspective, txv } = await generateTransactionInfo(
@@ -2,4 +2,11 @@
  * The version number for the IndexedDB schema. This version number is used to manage
  * database upgrades and ensure that the correct schema version is applied.
  */
-export const IDB_VERSION = 49;
+export const IDB_VERSION = 50;
+
+/**
+ * The version number for the wallet data IndexedDB schema. Unlike the view data database, which
+ * is recreated on every upgrade, upgrades of this database only add object stores, so a new
+ * version never deletes data the user entered.
+ */
+export const WALLET_DATA_IDB_VERSION = 2;
@@ -52,8 +52,10 @@ import {
   IDB_TABLES,
   IdbConstants,
   IdbUpdate,
+  InvoiceUpdate,
   IndexedDbInterface,
   PenumbraDb,
+  WalletDataDb,
 } from '@penumbra-zone/types/indexed-db';
 import type { Jsonified } from '@penumbra-zone/types/jsonified';
 import type {
@@ -69,7 +71,7 @@ import { ChainRegistryClient } from '@penumbra-labs/registry';
 import { PartialMessage, PlainMessage } from '@bufbuild/protobuf';
 import { getAmountFromRecord } from '@penumbra-zone/getters/spendable-note-record';
 import { isZero } from '@penumbra-zone/types/amount';
-import { IDB_VERSION } from './config.js';
+import { IDB_VERSION, WALLET_DATA_IDB_VERSION } from './config.js';
 import { addLoHi } from '@penumbra-zone/types/lo-hi';
 import { Amount } from '@penumbra-zone/protobuf/penumbra/core/num/v1/num_pb';
 import { typeRegistry } from '@penumbra-zone/protobuf';
@@ -112,8 +114,10 @@ interface IndexedDbProps {
 export class IndexedDb implements IndexedDbInterface {
   private constructor(
     private readonly db: IDBPDatabase<PenumbraDb>,
+    private readonly walletDataDb: IDBPDatabase<WalletDataDb>,
     private readonly u: IbdUpdater,
     private readonly c: IdbConstants,
+    private readonly walletDataC: IdbConstants,
     private readonly chainId: string,
     readonly stakingTokenAssetId: AssetId,
   ) {}
@@ -182,11 +186,31 @@ export class IndexedDb implements IndexedDbInterface {
       tables: IDB_TABLES,
     } satisfies IdbConstants;
 
+    const walletDataIdbName = `walletdata/${chainId}/${bech32Id}`;
+    const walletDataDb = await openDB<WalletDataDb>(walletDataIdbName, WALLET_DATA_IDB_VERSION, {
+      upgrade(db: IDBPDatabase<WalletDataDb>) {
+        // only add missing ObjectStores; existing ones hold data the user entered
+        if (!db.objectStoreNames.contains('ADDRESS_LABELS')) {
+          db.createObjectStore('ADDRESS_LABELS');
+        }
+        if (!db.objectStoreNames.contains('INVOICES')) {
+          db.createObjectStore('INVOICES');
+        }
+      },
+    });
+    const walletDataConstants = {
+      name: walletDataIdbName,
+      version: WALLET_DATA_IDB_VERSION,
+      tables: IDB_TABLES,
+    } satisfies IdbConstants;
+
     const { stakingAssetId } = registryClient.bundled.globals();
     const instance = new this(
       db,
+      walletDataDb,
       new IbdUpdater(db),
       constants,
+      walletDataConstants,
       chainId,
       new AssetId(stakingAssetId),
     );
@@ -197,12 +221,18 @@ export class IndexedDb implements IndexedDbInterface {
 
   close(): void {
     this.db.close();
+    this.walletDataDb.close();
   }
 
   constants(): IdbConstants {
     return this.c;
   }
 
+  // Constants of the database holding address labels and invoices, which resyncing leaves alone.
+  walletDataConstants(): IdbConstants {
+    return this.walletDataC;
+  }
+
   subscribe<DBTypes extends PenumbraDb, StoreName extends StoreNames<DBTypes>>(
     table: StoreName,
   ): AsyncGenerator<IdbUpdate<DBTypes, StoreName>, void> {
@@ -232,6 +262,7 @@ export class IndexedDb implements IndexedDbInterface {
     await this.addNewSwaps(txs, updates.newSwaps, updates.height);
     txs.add({ table: 'FULL_SYNC_HEIGHT', value: updates.height, key: 'height' });
 
+    await this.saveInvoiceUpdates(updates.invoiceUpdates);
     await this.u.updateAll(txs);
   }
 
@@ -1116,6 +1147,20 @@ export class IndexedDb implements IndexedDbInterface {
     }
   }
 
+  // Invoices live in the wallet data database, so they cannot share the notes' transaction. They
+  // are saved first: if saving the notes then fails, the block is scanned again, and notes an
+  // invoice already counts are not credited twice.
+  private async saveInvoiceUpdates(updates: InvoiceUpdate[]): Promise<void> {
+    if (!updates.length) {
+      return;
+    }
+    const tx = this.walletDataDb.transaction('INVOICES', 'readwrite');
+    for (const { key, invoice } of updates) {
+      void tx.store.put(invoice, key);
+    }
+    await tx.done;
+  }
+
   private async addNewSwaps(
     txs: IbdUpdates,
     swaps: SwapRecord[],
@@ -10,6 +10,7 @@ import { Epoch } from '@penumbra-zone/protobuf/penumbra/core/component/sct/v1/sc
 import { TransactionId } from '@penumbra-zone/protobuf/penumbra/core/txhash/v1/txhash_pb';
 import { Transaction } from '@penumbra-zone/protobuf/penumbra/core/transaction/v1/transaction_pb';
 import type { ScanBlockResult } from '@penumbra-zone/types/state-commitment-tree';
+import type { Invoice } from '@penumbra-zone/types/indexed-db';
 import { base64ToUint8Array } from '@penumbra-zone/types/base64';
 import { StateCommitment } from '@penumbra-zone/protobuf/penumbra/crypto/tct/v1/tct_pb';
 import { AddressIndex } from '@penumbra-zone/protobuf/penumbra/core/keys/v1/keys_pb';
@@ -20,6 +21,7 @@ export const emptyScanResult: ScanBlockResult = {
   height: 1092n,
   newNotes: [],
   newSwaps: [],
+  invoiceUpdates: [],
   sctUpdates: {
     delete_ranges: [],
     store_commitments: [],
@@ -27,10 +29,30 @@ export const emptyScanResult: ScanBlockResult = {
   },
 };
 
+export const paidInvoice: Invoice = {
+  id: 'invoice-1',
+  walletId: { inner: 'JbOzRkf0VKm4eIM0DS27N5igX8jxvPhAMpBWSr2bj/Q=' },
+  addressIndex: { account: 0, randomizer: 'AAECAwQFBgcICQoL' },
+  requested: {
+    amount: { lo: '1000' },
+    assetId: { inner: 'KeqcLzNx9qSH5+lcJHBB9KNW+YPrBk5dKzvPMiypahA=' },
+  },
+  received: { lo: '1000' },
+  paidBy: [{ inner: 'XQ5CaaCm1etf2jbB5F5hQbH75Gy8MSsE8UT3g3hslOc=' }],
+};
+
+export const paidInvoiceKey = 'wallet/0/000102030405060708090a0b';
+
+export const scanResultWithInvoiceUpdates: ScanBlockResult = {
+  ...emptyScanResult,
+  invoiceUpdates: [{ key: paidInvoiceKey, invoice: paidInvoice, state: 'paid' }],
+};
+
 export const scanResultWithSctUpdates: ScanBlockResult = {
   height: 1092n,
   newNotes: [],
   newSwaps: [],
+  invoiceUpdates: [],
   sctUpdates: {
     delete_ranges: [],
     store_commitments: [
@@ -99,6 +121,7 @@ export const scanResultWithNewSwaps: ScanBlockResult = {
       },
     }),
   ],
+  invoiceUpdates: [],
   sctUpdates: {
     delete_ranges: [],
     store_commitments: [
@@ -17,12 +17,15 @@ import {
   noteWithDelegationAssetA,
   noteWithDelegationAssetB,
   noteWithGmAsset,
+  paidInvoice,
+  paidInvoiceKey,
   positionGmGnSell,
   positionGmPenumbraBuy,
   positionGnPenumbraSell,
   positionIdGmGnSell,
   positionIdGmPenumbraBuy,
   positionIdGnPenumbraSell,
+  scanResultWithInvoiceUpdates,
   scanResultWithNewSwaps,
   scanResultWithSctUpdates,
   tradingPairGmGn,
@@ -44,7 +47,12 @@ import {
   Metadata,
   Value,
 } from '@penumbra-zone/protobuf/penumbra/core/asset/v1/asset_pb';
-import type { IdbUpdate, PenumbraDb } from '@penumbra-zone/types/indexed-db';
+import type {
+  AddressLabel,
+  IdbUpdate,
+  PenumbraDb,
+  WalletDataDb,
+} from '@penumbra-zone/types/indexed-db';
 import {
   AuctionId,
   DutchAuctionDescription,
@@ -54,6 +62,7 @@ import { ChainRegistryClient, Registry } from '@penumbra-labs/registry';
 import fetchMock from 'fetch-mock';
 import { uint8ArrayToBase64 } from '@penumbra-zone/types/base64';
 import { JsonValue } from '@bufbuild/protobuf';
+import { openDB } from 'idb';
 
 const inner0123 = Uint8Array.from({ length: 32 }, () => Math.floor(Math.random() * 256));
 const inner5678 = Uint8Array.from({ length: 32 }, () => Math.floor(Math.random() * 256));
@@ -214,6 +223,7 @@ describe('IndexedDb', () => {
         },
         newNotes: [],
         newSwaps: [],
+        invoiceUpdates: [],
       };
 
       await db.saveScanResult(scanResult);
@@ -240,6 +250,27 @@ describe('IndexedDb', () => {
       expect(txsAfterClean.length).toBe(0);
       expect(await db.getFullSyncHeight()).toBeUndefined();
     });
+
+    it('leaves address labels alone', async () => {
+      const db = await IndexedDb.initialize({ ...generateInitialProps() });
+      const walletData = await openDB<WalletDataDb>(db.walletDataConstants().name);
+      const label: AddressLabel = { target: { account: 1 }, label: 'Savings' };
+      await walletData.put('ADDRESS_LABELS', label, 'account/1');
+
+      await db.clear();
+
+      expect(await walletData.get('ADDRESS_LABELS', 'account/1')).toEqual(label);
+    });
+
+    it('leaves invoices alone', async () => {
+      const db = await IndexedDb.initialize({ ...generateInitialProps() });
+      await db.saveScanResult(scanResultWithInvoiceUpdates);
+
+      await db.clear();
+
+      const walletData = await openDB<WalletDataDb>(db.walletDataConstants().name);
+      expect(await walletData.get('INVOICES', paidInvoiceKey)).toEqual(paidInvoice);
+    });
   });
 
   describe('fmd params', () => {
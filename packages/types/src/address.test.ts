@@ -1,5 +1,6 @@
 import { describe, expect, test } from 'vitest';
-import { parseIntoAddr } from './address.js';
+import { Address } from '@penumbra-zone/protobuf/penumbra/core/keys/v1/keys_pb';
+import { isTransparentAddress, parseIntoAddr } from './address.js';
 
 describe('parseIntoAddr', () => {
   test('works with compat', () => {
@@ -22,3 +23,21 @@ describe('parseIntoAddr', () => {
     expect(() => parseIntoAddr('not_valid_format')).toThrow();
   });
 });
+
+describe('isTransparentAddress', () => {
+  test('detects a zero diversifier', () => {
+    const inner = new Uint8Array(80).fill(7);
+    inner.fill(0, 0, 16);
+    expect(isTransparentAddress(new Address({ inner }))).toBe(true);
+  });
+
+  test('rejects normal addresses', () => {
+    expect(
+      isTransparentAddress(
+        parseIntoAddr(
+          'penumbra1e8k5cyds484dxvapeamwveh5khqv4jsvyvaf5wwxaaccgfghm229qw03pcar3ryy8smptevstycch0qk3uu0rgkvtjpxy3cu3rjd0agawqtlz6erev28a6sg69u7cxy0t02nd4',
+        ),
+      ),
+    ).toBe(false);
+  });
+});
@@ -30,3 +30,11 @@ export const isLqtAddressIndex = (index: AddressIndex): boolean => {
   }
   return true;
 };
+
+const ADDRESS_LEN = 80;
+const DIVERSIFIER_LEN = 16;
+
+/** Check if an address is a transparent address (t-addr), whose diversifier is all zeros. */
+export const isTransparentAddress = (address: Address): boolean =>
+  address.inner.length === ADDRESS_LEN &&
+  address.inner.subarray(0, DIVERSIFIER_LEN).every(byte => byte === 0);
@@ -33,7 +33,11 @@ import {
   Note,
 } from '@penumbra-zone/protobuf/penumbra/core/component/shielded_pool/v1/shielded_pool_pb';
 import { ValidatorInfo } from '@penumbra-zone/protobuf/penumbra/core/component/stake/v1/stake_pb';
-import { AddressIndex, IdentityKey } from '@penumbra-zone/protobuf/penumbra/core/keys/v1/keys_pb';
+import {
+  AddressIndex,
+  IdentityKey,
+  WalletId,
+} from '@penumbra-zone/protobuf/penumbra/core/keys/v1/keys_pb';
 import {
   Transaction,
   TransactionPerspective,
@@ -65,6 +69,7 @@ export interface IndexedDbInterface {
     table: StoreName,
   ): AsyncGenerator<IdbUpdate<DBTypes, StoreName>, void>;
   constants(): IdbConstants;
+  walletDataConstants(): IdbConstants;
   clear(): Promise<void>;
   getFullSyncHeight(): Promise<bigint | undefined>;
   saveFullSyncHeight(height: bigint): Promise<void>;
@@ -420,6 +425,19 @@ export interface PenumbraDb extends DBSchema {
   };
 }
 
+// Data the user enters, as opposed to data recovered by syncing, is kept in its own database so
+// that it survives the upgrades and clears that reset `PenumbraDb`.
+export interface WalletDataDb extends DBSchema {
+  ADDRESS_LABELS: {
+    key: string; // `account/${number}` or `address/${bech32m}`
+    value: AddressLabel;
+  };
+  INVOICES: {
+    key: string; // `${bech32m WalletId}/${account}/${hex randomizer}`
+    value: Invoice;
+  };
+}
+
 // need to store PositionId and Position in the same table
 export interface PositionRecord {
   id: Jsonified<PositionId>; // PositionId (must be JsonValue because ['id']['inner'] is a key )
@@ -428,7 +446,30 @@ export interface PositionRecord {
   positionMetadata?: Jsonified<PositionMetadata>;
 }
 
-export type Tables = Record<string, StoreNames<PenumbraDb>>;
+// Mirrors `AddressLabel` in the wasm crate's storage.rs
+export interface AddressLabel {
+  target: { account: number } | { address: string }; // bech32m address
+  label: string;
+}
+
+// Mirrors `Invoice` in the wasm crate's invoice.rs
+export interface Invoice {
+  id: string;
+  walletId: Jsonified<WalletId>;
+  addressIndex: Jsonified<AddressIndex>;
+  requested: Jsonified<Value>;
+  received: Jsonified<Amount>;
+  paidBy: Jsonified<StateCommitment>[];
+}
+
+// Mirrors `InvoiceUpdate` in the wasm crate's invoice.rs
+export interface InvoiceUpdate {
+  key: WalletDataDb['INVOICES']['key'];
+  invoice: Invoice;
+  state: 'unpaid' | 'partial' | 'paid';
+}
+
+export type Tables = Record<string, StoreNames<PenumbraDb> | StoreNames<WalletDataDb>>;
 export type PenumbraStoreNames = StoreNames<PenumbraDb>;
 
 // Must be kept in sync with: https://github.com/penumbra-zone/penumbra/blob/02462635d6c825019822cbeeb44d422cf900f25d/crates/wasm/src/storage.rs#L15C1-L30
@@ -458,4 +499,6 @@ export const IDB_TABLES: Tables = {
   tree_last_position: 'TREE_LAST_POSITION',
   tree_last_forgotten: 'TREE_LAST_FORGOTTEN',
   lqt_historical_votes: 'LQT_HISTORICAL_VOTES',
+  address_labels: 'ADDRESS_LABELS',
+  invoices: 'INVOICES',
 };
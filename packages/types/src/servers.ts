@@ -2,6 +2,8 @@ import { ScanBlockResult } from './state-commitment-tree.js';
 import { CompactBlock } from '@penumbra-zone/protobuf/penumbra/core/component/compact_block/v1/compact_block_pb';
 import { MerkleRoot } from '@penumbra-zone/protobuf/penumbra/crypto/tct/v1/tct_pb';
 import { Address } from '@penumbra-zone/protobuf/penumbra/core/keys/v1/keys_pb';
+import { Transaction } from '@penumbra-zone/protobuf/penumbra/core/transaction/v1/transaction_pb';
+import { SpendableNoteRecord } from '@penumbra-zone/protobuf/penumbra/view/v1/view_pb';
 
 export interface ViewServerInterface {
   scanBlock(compactBlock: CompactBlock, skipTrialDecrypt: boolean): Promise<boolean>;
@@ -16,6 +18,8 @@ export interface ViewServerInterface {
 
   flushUpdates(): ScanBlockResult;
 
+  enrichTransaction(transaction: Transaction): Promise<SpendableNoteRecord[]>;
+
   resetTreeToStored(): Promise<void>;
 
   getSctRoot(): MerkleRoot;
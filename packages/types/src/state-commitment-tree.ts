@@ -1,6 +1,7 @@
 import { z } from 'zod';
 import { InnerBase64Schema } from './base64.js';
 import { SpendableNoteRecord, SwapRecord } from '@penumbra-zone/protobuf/penumbra/view/v1/view_pb';
+import type { InvoiceUpdate } from './indexed-db.js';
 
 export const Position = z.object({
   epoch: z.number(),
@@ -56,6 +57,7 @@ export interface ScanBlockResult {
   sctUpdates: SctUpdates;
   newNotes: SpendableNoteRecord[];
   newSwaps: SwapRecord[];
+  invoiceUpdates: InvoiceUpdate[];
 }
 
 export const StateCommitmentTreeSchema = z.object({
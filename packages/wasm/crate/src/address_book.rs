@@ -0,0 +1,90 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use penumbra_keys::Address;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use crate::database::interface::Database;
+use crate::error::WasmResult;
+use crate::storage::{init_idb_storage, AddressLabel, LabelTarget, Storage};
+use crate::utils;
+
+/// Names an account or a recipient address
+/// Arguments:
+///     label: `AddressLabel`
+///     idb_constants: `IndexedDbConstants` of the wallet data database
+/// Returns: nothing
+#[wasm_bindgen]
+pub async fn set_address_label(label: JsValue, idb_constants: JsValue) -> WasmResult<()> {
+    utils::set_panic_hook();
+
+    let label: AddressLabel = serde_wasm_bindgen::from_value(label)?;
+    let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let storage = init_idb_storage(constants).await?;
+
+    set_address_label_inner(&storage, label).await
+}
+
+/// Looks up the label of an account or address
+/// Arguments:
+///     target: `LabelTarget`
+///     idb_constants: `IndexedDbConstants` of the wallet data database
+/// Returns: `Option<AddressLabel>`
+#[wasm_bindgen]
+pub async fn get_address_label(target: JsValue, idb_constants: JsValue) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let target: LabelTarget = serde_wasm_bindgen::from_value(target)?;
+    let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let storage = init_idb_storage(constants).await?;
+
+    let label = storage
+        .get_address_label(&normalize_target(target)?)
+        .await?;
+    let result = serde_wasm_bindgen::to_value(&label)?;
+    Ok(result)
+}
+
+/// Lists every labelled account and address
+/// Arguments:
+///     idb_constants: `IndexedDbConstants` of the wallet data database
+/// Returns: `AddressLabel[]`
+#[wasm_bindgen]
+pub async fn get_address_labels(idb_constants: JsValue) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let storage = init_idb_storage(constants).await?;
+
+    let labels = storage.get_address_labels().await?;
+    let result = serde_wasm_bindgen::to_value(&labels)?;
+    Ok(result)
+}
+
+pub async fn set_address_label_inner<Db: Database>(
+    storage: &Storage<Db>,
+    label: AddressLabel,
+) -> WasmResult<()> {
+    let name = label.label.trim();
+    if name.is_empty() {
+        return Err(anyhow!("address label must not be empty").into());
+    }
+
+    let label = AddressLabel {
+        target: normalize_target(label.target)?,
+        label: name.to_string(),
+    };
+    storage.set_address_label(&label).await
+}
+
+/// Re-encodes address targets, so that the same address always maps to the same entry.
+fn normalize_target(target: LabelTarget) -> WasmResult<LabelTarget> {
+    match target {
+        LabelTarget::Address(address) => {
+            let address = Address::from_str(address.trim())?;
+            Ok(LabelTarget::Address(address.to_string()))
+        }
+        account => Ok(account),
+    }
+}
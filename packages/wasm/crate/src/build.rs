@@ -1,11 +1,18 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
 use crate::error::WasmResult;
+use crate::keys::ensure_proving_key;
 use crate::utils;
+use anyhow::anyhow;
+use js_sys::Function;
 use penumbra_keys::FullViewingKey;
 use penumbra_proto::DomainType;
 use penumbra_transaction::{
     plan::{ActionPlan, TransactionPlan},
     Action, AuthorizationData, Transaction, WitnessData,
 };
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
 /// Builds a planned [`Action`] specified by
@@ -40,6 +47,7 @@ pub fn build_action_inner(
     full_viewing_key: FullViewingKey,
     witness: WitnessData,
 ) -> WasmResult<Action> {
+    ensure_proving_key(&action_plan)?;
     let memo_key = transaction_plan.memo.map(|memo_plan| memo_plan.key);
 
     let action = ActionPlan::build_unauth(action_plan, &full_viewing_key, &witness, memo_key)?;
@@ -82,6 +90,7 @@ pub fn build_serial_inner(
     witness: WitnessData,
     auth: AuthorizationData,
 ) -> WasmResult<Transaction> {
+    plan.actions.iter().try_for_each(ensure_proving_key)?;
     let tx: Transaction = plan.build(&fvk, &witness, &auth)?;
 
     Ok(tx)
@@ -127,3 +136,151 @@ pub fn build_parallel_inner(
 
     Ok(tx)
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BuildStage {
+    /// The action is queued and has not been started.
+    Planned,
+    /// The action is being built, which includes generating its proof.
+    Proving,
+    /// The action is built.
+    Proved,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildProgress {
+    /// Index of the action in the transaction plan.
+    pub index: usize,
+    /// Number of actions in the transaction plan.
+    pub total: usize,
+    pub stage: BuildStage,
+}
+
+/// The actions built so far. Once `complete`, they can be passed to `build_parallel`;
+/// otherwise they can be passed back to `build_actions_with_progress` to resume.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialBuild {
+    pub actions: Vec<Action>,
+    pub complete: bool,
+}
+
+/// Stops `build_actions_with_progress` before the next action is started.
+/// Since the build runs synchronously, the token is typically cancelled from
+/// within the progress callback.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Rc<Cell<bool>>,
+}
+
+#[wasm_bindgen]
+impl CancellationToken {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+    }
+}
+
+/// Builds the actions of a [`TransactionPlan`] one by one, reporting progress
+/// and stopping early if cancelled.
+/// Arguments:
+///     full_viewing_key: `FullViewingKey`
+///     transaction_plan: `TransactionPlan`
+///     witness_data: `WitnessData`
+///     built_actions: `Vec<Action>` returned by a previous, cancelled call, or `undefined`
+///     on_progress: `(progress: BuildProgress) => void`
+///     cancellation: `CancellationToken`
+/// Returns: `PartialBuild`
+#[wasm_bindgen]
+pub fn build_actions_with_progress(
+    full_viewing_key: &[u8],
+    transaction_plan: &[u8],
+    witness_data: &[u8],
+    built_actions: JsValue,
+    on_progress: &Function,
+    cancellation: &CancellationToken,
+) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let fvk = FullViewingKey::decode(full_viewing_key)?;
+    let plan = TransactionPlan::decode(transaction_plan)?;
+    let witness = WitnessData::decode(witness_data)?;
+    let built_actions: Option<Vec<Action>> = serde_wasm_bindgen::from_value(built_actions)?;
+
+    let partial = build_actions_with_progress_inner(
+        &fvk,
+        &plan,
+        &witness,
+        built_actions.unwrap_or_default(),
+        |progress| {
+            let progress = serde_wasm_bindgen::to_value(&progress)?;
+            on_progress
+                .call1(&JsValue::NULL, &progress)
+                .map_err(|error| anyhow!("progress callback failed: {error:?}"))?;
+            Ok(())
+        },
+        || cancellation.is_cancelled(),
+    )?;
+
+    Ok(serde_wasm_bindgen::to_value(&partial)?)
+}
+
+pub fn build_actions_with_progress_inner(
+    fvk: &FullViewingKey,
+    plan: &TransactionPlan,
+    witness: &WitnessData,
+    mut actions: Vec<Action>,
+    mut on_progress: impl FnMut(BuildProgress) -> WasmResult<()>,
+    is_cancelled: impl Fn() -> bool,
+) -> WasmResult<PartialBuild> {
+    let total = plan.actions.len();
+    if actions.len() > total {
+        return Err(anyhow!(
+            "{} actions were already built, but the plan has only {total}",
+            actions.len()
+        )
+        .into());
+    }
+
+    let memo_key = plan.memo.as_ref().map(|memo_plan| memo_plan.key);
+    let progress = |index, stage| BuildProgress {
+        index,
+        total,
+        stage,
+    };
+
+    for index in actions.len()..total {
+        on_progress(progress(index, BuildStage::Planned))?;
+    }
+
+    for (index, action_plan) in plan.actions.iter().enumerate().skip(actions.len()) {
+        if is_cancelled() {
+            return Ok(PartialBuild {
+                actions,
+                complete: false,
+            });
+        }
+
+        ensure_proving_key(action_plan)?;
+        on_progress(progress(index, BuildStage::Proving))?;
+        let action = ActionPlan::build_unauth(action_plan.clone(), fvk, witness, memo_key)?;
+        actions.push(action);
+        on_progress(progress(index, BuildStage::Proved))?;
+    }
+
+    Ok(PartialBuild {
+        actions,
+        complete: true,
+    })
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+
+use penumbra_asset::asset::Id;
+use penumbra_dex::swap_claim::SwapClaimView;
+use penumbra_keys::{Address, FullViewingKey};
+use penumbra_num::Amount;
+use penumbra_proto::core::component::fee::v1::Fee;
+use penumbra_proto::DomainType;
+use penumbra_shielded_pool::NoteView;
+use penumbra_transaction::view::action_view::{ActionView, OutputView, SpendView};
+use penumbra_transaction::{MemoView, TransactionView};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+
+use crate::error::{WasmError, WasmResult};
+use crate::transparent;
+use crate::utils;
+
+/// What a transaction did, from the point of view of one wallet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionKind {
+    /// Value left the wallet.
+    Send,
+    /// Value arrived from someone else.
+    Receive,
+    /// Value moved between accounts of the same wallet, or back to the same account.
+    InternalTransfer,
+    Swap,
+    SwapClaim,
+    Delegate,
+    Undelegate,
+    UndelegateClaim,
+    /// A governance vote, proposal or deposit claim.
+    Vote,
+    LiquidityTournamentVote,
+    /// Scheduling, ending or withdrawing from a Dutch auction.
+    Auction,
+    /// Opening, closing or withdrawing a liquidity position.
+    LiquidityPosition,
+    IbcWithdrawal,
+    /// Nothing in the transaction is visible to the wallet.
+    Unknown,
+}
+
+/// The change in balance of one asset in one account.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetEffect {
+    pub account: u32,
+    pub asset_id: Id,
+    /// Signed decimal amount: negative when the account's balance went down.
+    pub amount: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionClassification {
+    pub kind: TransactionKind,
+    /// Addresses outside the wallet that sent to or received from it.
+    pub counterparties: Vec<Address>,
+    pub net_effects: Vec<NetEffect>,
+    /// The fee, if the wallet paid for the transaction.
+    pub fee: Option<Fee>,
+}
+
+/// Classify a transaction for display
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     txv: `byte representation inner TransactionView`
+/// Returns: `TransactionClassification`
+#[wasm_bindgen]
+pub fn classify_transaction(full_viewing_key: &[u8], txv: &[u8]) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let fvk = FullViewingKey::decode(full_viewing_key)?;
+    let txv = TransactionView::decode(txv)?;
+    let classification = classify_transaction_inner(&fvk, &txv)?;
+
+    let result = serde_wasm_bindgen::to_value(&classification)?;
+    Ok(result)
+}
+
+pub fn classify_transaction_inner(
+    fvk: &FullViewingKey,
+    txv: &TransactionView,
+) -> WasmResult<TransactionClassification> {
+    let mut balances: BTreeMap<(u32, Id), i128> = BTreeMap::new();
+    let mut counterparties: Vec<Address> = Vec::new();
+    let mut spent_by_us = false;
+    let mut received_by_us = false;
+    let mut sent_to_others = false;
+    let mut kind = None;
+
+    let mut add_note = |note: &NoteView, sign: i128| -> WasmResult<bool> {
+        let Some(index) = transparent::address_index(fvk, &note.address()) else {
+            return Ok(false);
+        };
+        let balance = balances
+            .entry((index.account, note.asset_id()))
+            .or_default();
+        *balance = signed(note.value.value().amount, sign)
+            .and_then(|amount| balance.checked_add(amount))
+            .ok_or(WasmError::NetEffectOverflow {
+                account: index.account,
+                asset_id: note.asset_id(),
+            })?;
+        Ok(true)
+    };
+
+    for action_view in txv.action_views() {
+        match action_view {
+            ActionView::Spend(SpendView::Visible { note, .. }) => {
+                spent_by_us |= add_note(note, -1)?;
+            }
+            ActionView::Output(OutputView::Visible { note, .. }) => {
+                if add_note(note, 1)? {
+                    received_by_us = true;
+                } else {
+                    sent_to_others = true;
+                    push_unique(&mut counterparties, note.address());
+                }
+            }
+            ActionView::SwapClaim(SwapClaimView::Visible {
+                output_1, output_2, ..
+            }) => {
+                add_note(output_1, 1)?;
+                add_note(output_2, 1)?;
+            }
+            _ => {}
+        }
+
+        // Actions other than plain transfers decide the kind; the first one wins.
+        kind = kind.or(action_kind(action_view));
+    }
+
+    // Notes received from someone else carry their return address in the memo.
+    if !spent_by_us {
+        if let Some(MemoView::Visible { plaintext, .. }) = &txv.body_view.memo_view {
+            let return_address = plaintext.return_address.address();
+            if transparent::address_index(fvk, &return_address).is_none() {
+                push_unique(&mut counterparties, return_address);
+            }
+        }
+    }
+
+    let kind = kind.unwrap_or(match (spent_by_us, received_by_us, sent_to_others) {
+        (true, _, true) => TransactionKind::Send,
+        (true, _, false) => TransactionKind::InternalTransfer,
+        (false, true, _) => TransactionKind::Receive,
+        (false, false, _) => TransactionKind::Unknown,
+    });
+
+    let net_effects = balances
+        .into_iter()
+        .filter(|(_, amount)| *amount != 0)
+        .map(|((account, asset_id), amount)| NetEffect {
+            account,
+            asset_id,
+            amount: amount.to_string(),
+        })
+        .collect();
+
+    Ok(TransactionClassification {
+        kind,
+        counterparties,
+        net_effects,
+        fee: spent_by_us.then(|| txv.body_view.transaction_parameters.fee.into()),
+    })
+}
+
+fn action_kind(action_view: &ActionView) -> Option<TransactionKind> {
+    let kind = match action_view {
+        ActionView::Swap(_) => TransactionKind::Swap,
+        ActionView::SwapClaim(_) => TransactionKind::SwapClaim,
+        ActionView::Delegate(_) => TransactionKind::Delegate,
+        ActionView::Undelegate(_) => TransactionKind::Undelegate,
+        ActionView::UndelegateClaim(_) => TransactionKind::UndelegateClaim,
+        ActionView::DelegatorVote(_)
+        | ActionView::ValidatorVote(_)
+        | ActionView::ProposalSubmit(_)
+        | ActionView::ProposalWithdraw(_)
+        | ActionView::ProposalDepositClaim(_) => TransactionKind::Vote,
+        ActionView::ActionLiquidityTournamentVote(_) => TransactionKind::LiquidityTournamentVote,
+        ActionView::ActionDutchAuctionSchedule(_)
+        | ActionView::ActionDutchAuctionEnd(_)
+        | ActionView::ActionDutchAuctionWithdraw(_) => TransactionKind::Auction,
+        ActionView::PositionOpen(_)
+        | ActionView::PositionClose(_)
+        | ActionView::PositionWithdraw(_) => TransactionKind::LiquidityPosition,
+        ActionView::Ics20Withdrawal(_) => TransactionKind::IbcWithdrawal,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// The amount with `sign` applied, or `None` if it does not fit in an `i128`.
+fn signed(amount: Amount, sign: i128) -> Option<i128> {
+    i128::try_from(amount.value()).ok()?.checked_mul(sign)
+}
+
+fn push_unique(addresses: &mut Vec<Address>, address: Address) {
+    if !addresses.contains(&address) {
+        addresses.push(address);
+    }
+}
@@ -2,8 +2,8 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsValue;
 
 use crate::database::interface::Database;
@@ -28,11 +28,22 @@ pub fn get_mock_tables() -> Tables {
         tree_hashes: "tree_hashes".to_string(),
         tree_last_position: "tree_last_position".to_string(),
         tree_last_forgotten: "tree_last_forgotten".to_string(),
+        address_labels: "address_labels".to_string(),
+        invoices: "invoices".to_string(),
     }
 }
 
 type DbTable = Rc<RefCell<HashMap<String, JsValue>>>;
 
+/// A field of a stored record. Only indexes over `{ inner }` byte wrappers (nullifiers,
+/// commitments, asset ids) are supported by the mock.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IndexField {
+    Bytes { inner: String },
+    Other(IgnoredAny),
+}
+
 #[derive(Clone, Debug)]
 pub struct MockDb {
     tables: RefCell<HashMap<String, DbTable>>,
@@ -77,13 +88,28 @@ impl Database for MockDb {
         Ok(result)
     }
 
-    async fn get_with_index<T, K>(&self, _: &str, _: K, _: &str) -> WasmResult<Option<T>>
+    async fn get_with_index<T, K>(&self, table: &str, key: K, index: &str) -> WasmResult<Option<T>>
     where
         T: DeserializeOwned,
         K: Into<JsValue>,
     {
-        // TODO: Implement mock method when test suite requires it
-        unimplemented!()
+        let table = self.get_table(table);
+        let key = key.into().as_string().unwrap_or_default();
+
+        let result = table
+            .borrow()
+            .values()
+            .find(|js_value| {
+                serde_wasm_bindgen::from_value::<HashMap<String, IndexField>>((*js_value).clone())
+                    .ok()
+                    .and_then(|mut fields| fields.remove(index))
+                    .is_some_and(
+                        |field| matches!(field, IndexField::Bytes { inner } if inner == key),
+                    )
+            })
+            .and_then(|js_value| serde_wasm_bindgen::from_value(js_value.clone()).ok());
+
+        Ok(result)
     }
 
     async fn get_latest<T>(&self, _table: &str) -> WasmResult<Option<T>>
@@ -47,11 +47,15 @@ pub fn decrypt_position_metadata(
     utils::set_panic_hook();
 
     let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
-    let ovk = fvk.outgoing();
-    let pmk = PositionMetadataKey::derive(ovk);
+    let pmk = position_metadata_key(&fvk);
     let plaintext = pmk
         .decrypt(position_metadata)
         .expect("Failed to decrypt position metadata with derived PMK");
 
     Ok(plaintext)
 }
+
+/// The key position metadata is encrypted with, derived from the outgoing viewing key.
+pub fn position_metadata_key(fvk: &FullViewingKey) -> PositionMetadataKey {
+    PositionMetadataKey::derive(fvk.outgoing())
+}
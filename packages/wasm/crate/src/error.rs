@@ -1,6 +1,8 @@
 use std::num::TryFromIntError;
 
+use penumbra_asset::asset::Id;
 use penumbra_tct::error::InsertError;
+use penumbra_tct::StateCommitment;
 use prost::DecodeError as ProstDecodeError;
 use serde_wasm_bindgen::Error;
 use thiserror::Error;
@@ -20,6 +22,71 @@ pub enum WasmError {
     #[error("{0}")]
     InsertError(#[from] InsertError),
 
+    #[error("Invalid SCT frontier snapshot: {0}")]
+    InvalidFrontier(String),
+
+    #[error("Genesis chunk out of order: expected chunk starting at {expected}, got {actual}")]
+    GenesisChunkOutOfOrder { expected: u64, actual: u64 },
+
+    #[error("State commitment tree is full: {0}")]
+    SctFull(String),
+
+    #[error("Block out of sequence: expected height {expected}, got {actual}")]
+    BlockOutOfSequence { expected: u64, actual: u64 },
+
+    #[error(
+        "SCT divergence at height {height}: computed {root} root does not match the compact block"
+    )]
+    SctDivergence { height: u64, root: &'static str },
+
+    #[error(
+        "Compact block at height {height} has both a note and a swap with commitment {commitment}"
+    )]
+    AmbiguousCommitment {
+        height: u64,
+        commitment: StateCommitment,
+    },
+
+    #[error("Expected {expected} {kind} signatures, got {actual}")]
+    AuthorizationCount {
+        kind: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("The {kind} signature at index {index} does not verify against the full viewing key")]
+    InvalidAuthorization { kind: &'static str, index: usize },
+
+    #[error("State commitment {0} is not in the SCT")]
+    CommitmentNotInSct(StateCommitment),
+
+    #[error("Net effect of asset {asset_id} on account {account} does not fit in 128 bits")]
+    NetEffectOverflow { account: u32, asset_id: Id },
+
+    #[error("Memo text is {len} bytes, but at most {max} bytes are allowed")]
+    MemoTooLong { len: usize, max: usize },
+
+    #[error("The {0} proving key is not loaded")]
+    MissingProvingKey(&'static str),
+
+    #[error("The {key_type} proving key failed verification: {reason}")]
+    InvalidProvingKey {
+        key_type: &'static str,
+        reason: String,
+    },
+
+    #[error("The {0} proving key is already loaded, so the given bytes were not checked")]
+    ProvingKeyAlreadyLoaded(&'static str),
+
+    #[error("Seed phrases have 12 or 24 words, got {0}")]
+    SeedPhraseWordCount(usize),
+
+    #[error("Seed phrase word {} ({word:?}) is not in the BIP-39 word list", .index + 1)]
+    UnknownSeedPhraseWord { index: usize, word: String },
+
+    #[error("Seed phrase checksum does not match; check the words for typos or a wrong order")]
+    InvalidSeedPhraseChecksum,
+
     #[error("Decode error: {0}")]
     ProstDecodeError(#[from] ProstDecodeError),
 
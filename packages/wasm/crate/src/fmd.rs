@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use decaf377_fmd::{Clue, DetectionKey};
+use penumbra_keys::keys::AddressIndex;
+use penumbra_keys::FullViewingKey;
+use penumbra_proto::crypto::decaf377_fmd::v1 as pb;
+use penumbra_shielded_pool::{fmd, note};
+use serde::{Deserialize, Serialize};
+
+use crate::error::WasmResult;
+
+/// The FMD clue published for an output note, keyed by the note commitment it was created for.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoteClue {
+    pub note_commitment: note::StateCommitment,
+    pub clue: pb::Clue,
+}
+
+/// Converts clues supplied alongside a block into a lookup table keyed by note commitment.
+pub fn clues_by_commitment(
+    note_clues: Vec<NoteClue>,
+) -> WasmResult<BTreeMap<note::StateCommitment, Clue>> {
+    note_clues
+        .into_iter()
+        .map(
+            |NoteClue {
+                 note_commitment,
+                 clue,
+             }| {
+                let clue = Clue::try_from(clue.inner.as_slice())
+                    .map_err(|_| anyhow!("invalid FMD clue length: {}", clue.inner.len()))?;
+                Ok((note_commitment, clue))
+            },
+        )
+        .collect()
+}
+
+/// Running counters describing how well FMD pre-filtering performs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FmdStats {
+    /// Clues examined against the detection keys.
+    pub examined: u64,
+    /// Clues that matched at least one detection key.
+    pub detected: u64,
+    /// Detected clues whose payload then failed trial decryption.
+    pub false_positives: u64,
+    /// Undetected clues whose payload trial decrypted as ours anyway, e.g. because it was sent to
+    /// a one-time address. Always 0 when undetected payloads are skipped.
+    pub missed: u64,
+    /// `false_positives / (examined - true positives - missed)`, or 0 before any clue has been
+    /// examined.
+    pub observed_false_positive_rate: f64,
+    /// `2^-precision` for the FMD parameters in storage.
+    pub expected_false_positive_rate: f64,
+}
+
+/// Examines note clues with fuzzy message detection.
+///
+/// Detection keys are derived for the default address of each account of every wallet. Notes sent
+/// to any other address of the wallet (one-time, invoice, forwarding or transparent addresses)
+/// carry clues for other detection keys and are never detected. By default the filter only
+/// records how the clues compare with trial decryption, and every payload is still trial
+/// decrypted. With `skip_undetected`, undetected payloads are not trial decrypted at all, so
+/// notes sent to those addresses are not found.
+pub struct FmdFilter {
+    detection_keys: Vec<DetectionKey>,
+    account_count: u32,
+    skip_undetected: bool,
+    params: fmd::Parameters,
+    stats: FmdStats,
+}
+
+impl FmdFilter {
+    pub fn new(params: fmd::Parameters, account_count: u32, skip_undetected: bool) -> Self {
+        Self {
+            detection_keys: Vec::new(),
+            account_count,
+            skip_undetected,
+            params,
+            stats: FmdStats::default(),
+        }
+    }
+
+    /// Whether payloads whose clue matches none of our detection keys are skipped.
+    pub fn skips_undetected(&self) -> bool {
+        self.skip_undetected
+    }
+
+    /// Derives detection keys for the first `account_count` accounts of the wallet.
+    pub fn add_wallet(&mut self, fvk: &FullViewingKey) {
+        let detection_keys = (0..self.account_count).map(|account| {
+            fvk.incoming()
+                .payment_address(AddressIndex::from(account))
+                .1
+        });
+        self.detection_keys.extend(detection_keys);
+    }
+
+    /// Returns true if the clue matches any of our detection keys.
+    pub fn detects(&mut self, clue: &Clue) -> bool {
+        self.stats.examined += 1;
+        let detected = self.detection_keys.iter().any(|dtk| dtk.examine(clue));
+        if detected {
+            self.stats.detected += 1;
+        }
+        detected
+    }
+
+    /// Records a detected clue whose payload turned out not to be ours.
+    pub fn record_false_positive(&mut self) {
+        self.stats.false_positives += 1;
+    }
+
+    /// Records an undetected clue whose payload turned out to be ours.
+    pub fn record_missed(&mut self) {
+        self.stats.missed += 1;
+    }
+
+    pub fn stats(&self) -> FmdStats {
+        let true_positives = self.stats.detected - self.stats.false_positives;
+        let negatives = self.stats.examined - true_positives - self.stats.missed;
+        let observed_false_positive_rate = if negatives == 0 {
+            0.0
+        } else {
+            self.stats.false_positives as f64 / negatives as f64
+        };
+
+        FmdStats {
+            observed_false_positive_rate,
+            expected_false_positive_rate: 0.5f64.powi(self.params.precision.bits().into()),
+            ..self.stats.clone()
+        }
+    }
+}
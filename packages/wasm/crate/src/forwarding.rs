@@ -0,0 +1,225 @@
+//! Forwarding addresses for deposits from other chains.
+//!
+//! A forwarding address is a Penumbra address set aside for deposits through one counterparty
+//! chain. Its randomizer is `[0xff; 9]`, a byte naming the chain, then `LE16(sequence)`; for
+//! Noble the chain byte is `0xff`, which keeps the original `[0xff; 10] + LE16(sequence)` scheme.
+//! Because the chain and sequence are part of the address index, a note received on a
+//! forwarding address can be traced back to the deposit route it came through.
+
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use penumbra_keys::keys::AddressIndex;
+use penumbra_keys::{Address, FullViewingKey};
+use penumbra_proto::core::keys::v1 as pb;
+use penumbra_proto::DomainType;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use crate::error::WasmResult;
+use crate::utils;
+
+const FORWARDING_PREFIX: [u8; 9] = [0xff; 9];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ForwardingChain {
+    /// Noble's forwarding accounts, which relay anything they receive to the Penumbra address.
+    Noble,
+    /// Chains running the packet-forward middleware, such as Osmosis. Deposits carry a
+    /// `forward` memo naming the Penumbra address.
+    PacketForward,
+    /// Any other chain sending plain ICS-20 transfers. The address is given in its compat
+    /// encoding, which fits the bech32 length limit of most Cosmos chains.
+    Ibc,
+}
+
+impl ForwardingChain {
+    pub const ALL: [ForwardingChain; 3] = [
+        ForwardingChain::Noble,
+        ForwardingChain::PacketForward,
+        ForwardingChain::Ibc,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ForwardingChain::Noble => "noble",
+            ForwardingChain::PacketForward => "packetForward",
+            ForwardingChain::Ibc => "ibc",
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            ForwardingChain::Noble => 0xff,
+            ForwardingChain::PacketForward => 0xfe,
+            ForwardingChain::Ibc => 0xfd,
+        }
+    }
+
+    /// The address index of a forwarding address.
+    pub fn address_index(self, account: u32, sequence: u16) -> AddressIndex {
+        let mut randomizer = [0u8; 12];
+        randomizer[..9].copy_from_slice(&FORWARDING_PREFIX);
+        randomizer[9] = self.tag();
+        randomizer[10..].copy_from_slice(&sequence.to_le_bytes());
+
+        AddressIndex {
+            account,
+            randomizer,
+        }
+    }
+}
+
+impl FromStr for ForwardingChain {
+    type Err = anyhow::Error;
+
+    fn from_str(chain: &str) -> anyhow::Result<Self> {
+        ForwardingChain::ALL
+            .into_iter()
+            .find(|candidate| candidate.as_str() == chain)
+            .ok_or_else(|| anyhow!("unsupported forwarding chain {chain}"))
+    }
+}
+
+/// Where a forwarding address sends its deposits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForwardingIndex {
+    pub chain: ForwardingChain,
+    pub account: u32,
+    pub sequence: u16,
+}
+
+impl ForwardingIndex {
+    /// Recognizes the address index of a forwarding address.
+    pub fn from_address_index(index: &AddressIndex) -> Option<ForwardingIndex> {
+        let (prefix, rest) = index.randomizer.split_at(FORWARDING_PREFIX.len());
+        if prefix != FORWARDING_PREFIX {
+            return None;
+        }
+
+        let chain = ForwardingChain::ALL
+            .into_iter()
+            .find(|chain| chain.tag() == rest[0])?;
+        Some(ForwardingIndex {
+            chain,
+            account: index.account,
+            sequence: u16::from_le_bytes([rest[1], rest[2]]),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForwardingAddress {
+    pub index: ForwardingIndex,
+    /// The Penumbra address deposits are forwarded to.
+    pub address: pb::Address,
+    /// What to hand the counterparty chain: a Noble forwarding address, a packet-forward
+    /// memo, or a compat-encoded Penumbra address.
+    pub encoding: String,
+}
+
+/// Generates a forwarding address for deposits through another chain
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     chain: `ForwardingChain`, e.g. `noble`
+///     sequence: `u16`
+///     channel: `string`, the Penumbra channel on the counterparty chain, e.g. `channel-4`
+///     account: `Option<u32>`
+/// Returns: `ForwardingAddress`
+#[wasm_bindgen]
+pub fn get_forwarding_address(
+    full_viewing_key: &[u8],
+    chain: &str,
+    sequence: u16,
+    channel: &str,
+    account: Option<u32>,
+) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
+    let index = ForwardingIndex {
+        chain: ForwardingChain::from_str(chain)?,
+        account: account.unwrap_or_default(),
+        sequence,
+    };
+    let forwarding_address = forwarding_address_inner(&fvk, index, channel)?;
+    let result = serde_wasm_bindgen::to_value(&forwarding_address)?;
+    Ok(result)
+}
+
+/// Recognizes a forwarding address of the wallet
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     address: `byte representation inner Address`
+/// Returns: `Option<ForwardingIndex>`, `None` for addresses that are not forwarding addresses
+#[wasm_bindgen]
+pub fn get_forwarding_index_by_address(
+    full_viewing_key: &[u8],
+    address: &[u8],
+) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
+    let address: Address = Address::decode(address)?;
+    let index = fvk
+        .address_index(&address)
+        .and_then(|index| ForwardingIndex::from_address_index(&index));
+    let result = serde_wasm_bindgen::to_value(&index)?;
+    Ok(result)
+}
+
+/// Recognizes the address index of a received note as a forwarding address
+/// Arguments:
+///     address_index: `byte representation inner AddressIndex`, as in a `SpendableNoteRecord`
+/// Returns: `Option<ForwardingIndex>`
+#[wasm_bindgen]
+pub fn get_forwarding_index(address_index: &[u8]) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let address_index = AddressIndex::decode(address_index)?;
+    let index = ForwardingIndex::from_address_index(&address_index);
+    let result = serde_wasm_bindgen::to_value(&index)?;
+    Ok(result)
+}
+
+pub fn forwarding_address_inner(
+    fvk: &FullViewingKey,
+    index: ForwardingIndex,
+    channel: &str,
+) -> WasmResult<ForwardingAddress> {
+    let address = penumbra_forwarding_address(fvk, index);
+    let encoding = match index.chain {
+        ForwardingChain::Noble => address.noble_forwarding_address(channel).to_string(),
+        ForwardingChain::PacketForward => packet_forward_memo(&address, channel)?,
+        ForwardingChain::Ibc => address.compat_encoding(),
+    };
+
+    Ok(ForwardingAddress {
+        index,
+        address: address.into(),
+        encoding,
+    })
+}
+
+pub fn penumbra_forwarding_address(fvk: &FullViewingKey, index: ForwardingIndex) -> Address {
+    let address_index = index.chain.address_index(index.account, index.sequence);
+    let (address, _dtk) = fvk.incoming().payment_address(address_index);
+    address
+}
+
+/// The packet-forward middleware memo relaying a transfer to `address` over `channel`.
+fn packet_forward_memo(address: &Address, channel: &str) -> WasmResult<String> {
+    let channel_id = Regex::new(r"^channel-\d+$")?;
+    if !channel_id.is_match(channel) {
+        return Err(anyhow!("invalid channel id {channel}").into());
+    }
+
+    Ok(format!(
+        r#"{{"forward":{{"receiver":"{address}","port":"transfer","channel":"{channel}"}}}}"#
+    ))
+}
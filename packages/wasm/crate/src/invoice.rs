@@ -0,0 +1,295 @@
+//! Invoice addresses.
+//!
+//! Each invoice gets a one-time address whose randomizer is derived from the invoice id, so the
+//! address can be re-derived at any time and every note received on it belongs to the invoice.
+//! Outstanding invoices are recorded in the wallet data database, which resyncing leaves alone,
+//! and the view server credits them as their payments are scanned. A note is credited at most
+//! once, so rescanning it after a resync does not count it again.
+
+use anyhow::anyhow;
+use penumbra_asset::Value;
+use penumbra_keys::keys::{AddressIndex, WalletId};
+use penumbra_keys::{Address, FullViewingKey};
+use penumbra_num::Amount;
+use penumbra_proto::DomainType;
+use penumbra_shielded_pool::note;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use crate::database::interface::Database;
+use crate::error::WasmResult;
+use crate::note_record::SpendableNoteRecord;
+use crate::storage::{init_idb_storage, invoice_key, Storage};
+use crate::utils;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InvoiceState {
+    Unpaid,
+    Partial,
+    Paid,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Invoice {
+    pub id: String,
+    pub wallet_id: WalletId,
+    pub address_index: AddressIndex,
+    pub requested: Value,
+    /// The total of the payments received in the requested asset.
+    pub received: Amount,
+    /// The notes credited to the invoice, so that rescanning a block does not count them twice.
+    pub paid_by: Vec<note::StateCommitment>,
+}
+
+impl Invoice {
+    pub fn state(&self) -> InvoiceState {
+        if self.received >= self.requested.amount {
+            InvoiceState::Paid
+        } else if self.received > Amount::zero() {
+            InvoiceState::Partial
+        } else {
+            InvoiceState::Unpaid
+        }
+    }
+
+    pub fn status(self) -> InvoiceStatus {
+        InvoiceStatus {
+            state: self.state(),
+            invoice: self,
+        }
+    }
+
+    pub fn update(self) -> InvoiceUpdate {
+        InvoiceUpdate {
+            key: invoice_key(&self.wallet_id, &self.address_index),
+            state: self.state(),
+            invoice: self,
+        }
+    }
+
+    /// Credits a note received on the invoice's address. Notes in another asset than the one
+    /// requested, and notes already credited, are not counted.
+    /// Returns: whether the note was credited
+    pub fn credit(&mut self, record: &SpendableNoteRecord) -> bool {
+        if record.address_index != self.address_index
+            || record.note.asset_id() != self.requested.asset_id
+            || self.paid_by.contains(&record.note_commitment)
+        {
+            return false;
+        }
+
+        self.received += record.note.amount();
+        self.paid_by.push(record.note_commitment);
+        true
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceStatus {
+    pub invoice: Invoice,
+    pub state: InvoiceState,
+}
+
+/// An invoice credited by a scan, with the key it is stored under, so that it can be saved in
+/// the same transaction as the notes that paid it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceUpdate {
+    pub key: String,
+    pub invoice: Invoice,
+    pub state: InvoiceState,
+}
+
+/// Get the one-time address of an invoice
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     account: `u32`
+///     invoice_id: `string`
+/// Returns: `Uint8Array representing inner Address`
+#[wasm_bindgen]
+pub fn get_invoice_address(
+    full_viewing_key: &[u8],
+    account: u32,
+    invoice_id: &str,
+) -> WasmResult<Vec<u8>> {
+    utils::set_panic_hook();
+
+    let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
+    let (address, _index) = invoice_address(&fvk, account, invoice_id)?;
+    Ok(address.encode_to_vec())
+}
+
+/// Record an outstanding invoice and get the address it should be paid to
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     account: `u32`
+///     invoice_id: `string`
+///     requested: `byte representation inner Value`
+///     idb_constants: `IndexedDbConstants` of the wallet data database
+/// Returns: `Uint8Array representing inner Address`
+#[wasm_bindgen]
+pub async fn create_invoice(
+    full_viewing_key: &[u8],
+    account: u32,
+    invoice_id: &str,
+    requested: &[u8],
+    idb_constants: JsValue,
+) -> WasmResult<Vec<u8>> {
+    utils::set_panic_hook();
+
+    let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
+    let requested = Value::decode(requested)?;
+    let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let storage = init_idb_storage(constants).await?;
+
+    let address = create_invoice_inner(&storage, &fvk, account, invoice_id, requested).await?;
+    Ok(address.encode_to_vec())
+}
+
+/// Get the payment state of an invoice
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     account: `u32`
+///     invoice_id: `string`
+///     idb_constants: `IndexedDbConstants` of the wallet data database
+/// Returns: `Option<InvoiceStatus>`
+#[wasm_bindgen]
+pub async fn get_invoice_status(
+    full_viewing_key: &[u8],
+    account: u32,
+    invoice_id: &str,
+    idb_constants: JsValue,
+) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
+    let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let storage = init_idb_storage(constants).await?;
+
+    let status = get_invoice_status_inner(&storage, &fvk, account, invoice_id).await?;
+    let result = serde_wasm_bindgen::to_value(&status)?;
+    Ok(result)
+}
+
+/// Get the payment state of every recorded invoice
+/// Arguments:
+///     idb_constants: `IndexedDbConstants` of the wallet data database
+/// Returns: `InvoiceStatus[]`
+#[wasm_bindgen]
+pub async fn get_invoices(idb_constants: JsValue) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let storage = init_idb_storage(constants).await?;
+
+    let statuses: Vec<InvoiceStatus> = storage
+        .get_invoices()
+        .await?
+        .into_iter()
+        .map(Invoice::status)
+        .collect();
+    let result = serde_wasm_bindgen::to_value(&statuses)?;
+    Ok(result)
+}
+
+/// The randomizer of an invoice's address, a personalized BLAKE2b hash of its id.
+pub fn invoice_randomizer(invoice_id: &str) -> [u8; 12] {
+    let hash = blake2b_simd::Params::new()
+        .personal(b"Penumbra_Invoice")
+        .hash_length(12)
+        .hash(invoice_id.as_bytes());
+    let mut randomizer = [0u8; 12];
+    randomizer.copy_from_slice(hash.as_bytes());
+    randomizer
+}
+
+pub fn invoice_address(
+    fvk: &FullViewingKey,
+    account: u32,
+    invoice_id: &str,
+) -> WasmResult<(Address, AddressIndex)> {
+    if invoice_id.is_empty() {
+        return Err(anyhow!("invoice id must not be empty").into());
+    }
+
+    let index = AddressIndex {
+        account,
+        randomizer: invoice_randomizer(invoice_id),
+    };
+    let (address, _dtk) = fvk.incoming().payment_address(index);
+    Ok((address, index))
+}
+
+pub async fn create_invoice_inner<Db: Database>(
+    storage: &Storage<Db>,
+    fvk: &FullViewingKey,
+    account: u32,
+    invoice_id: &str,
+    requested: Value,
+) -> WasmResult<Address> {
+    let (address, address_index) = invoice_address(fvk, account, invoice_id)?;
+    let wallet_id = fvk.wallet_id();
+
+    if storage
+        .get_invoice(&wallet_id, &address_index)
+        .await?
+        .is_some()
+    {
+        return Err(anyhow!("invoice {invoice_id} already exists for account {account}").into());
+    }
+
+    let invoice = Invoice {
+        id: invoice_id.to_string(),
+        wallet_id,
+        address_index,
+        requested,
+        received: Amount::zero(),
+        paid_by: Vec::new(),
+    };
+    storage.save_invoice(&invoice).await?;
+
+    Ok(address)
+}
+
+pub async fn get_invoice_status_inner<Db: Database>(
+    storage: &Storage<Db>,
+    fvk: &FullViewingKey,
+    account: u32,
+    invoice_id: &str,
+) -> WasmResult<Option<InvoiceStatus>> {
+    let (_address, address_index) = invoice_address(fvk, account, invoice_id)?;
+    let invoice = storage
+        .get_invoice(&fvk.wallet_id(), &address_index)
+        .await?;
+    Ok(invoice.map(Invoice::status))
+}
+
+/// Credits a scanned note to the invoice whose address received it, if there is one.
+/// Nothing is saved here: the scan saves credited invoices along with the rest of its updates,
+/// so `pending` is the invoice as already credited by the scan, and storage is only read when
+/// the scan has not credited it yet.
+/// Returns: the updated invoice, or `None` if the note did not pay an invoice
+pub async fn record_invoice_payment<Db: Database>(
+    storage: &Storage<Db>,
+    wallet_id: &WalletId,
+    pending: Option<&Invoice>,
+    record: &SpendableNoteRecord,
+) -> WasmResult<Option<Invoice>> {
+    let invoice = match pending {
+        Some(invoice) => Some(invoice.clone()),
+        None => {
+            storage
+                .get_invoice(wallet_id, &record.address_index)
+                .await?
+        }
+    };
+    let Some(mut invoice) = invoice else {
+        return Ok(None);
+    };
+
+    Ok(invoice.credit(record).then_some(invoice))
+}
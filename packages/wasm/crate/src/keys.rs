@@ -1,57 +1,405 @@
 use anyhow;
+use bip39::{Language, Mnemonic};
+use js_sys::{Function, Uint8Array};
 use penumbra_keys::keys::{AddressIndex, Bip44Path, SeedPhrase, SpendKey};
-use penumbra_keys::{Address, FullViewingKey};
+use penumbra_keys::{Address, AddressView, FullViewingKey};
 use penumbra_proof_params::{
     CONVERT_PROOF_PROVING_KEY, DELEGATOR_VOTE_PROOF_PROVING_KEY, OUTPUT_PROOF_PROVING_KEY,
     SPEND_PROOF_PROVING_KEY, SWAPCLAIM_PROOF_PROVING_KEY, SWAP_PROOF_PROVING_KEY,
 };
 use penumbra_proto::core::keys::v1 as pb;
 use penumbra_proto::DomainType;
+use penumbra_transaction::{ActionPlan, TransactionPlan};
 use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::ops::Range;
 use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
 use wasm_bindgen::prelude::*;
 
-use crate::error::WasmResult;
+use crate::error::{WasmError, WasmResult};
+use crate::forwarding::{penumbra_forwarding_address, ForwardingChain, ForwardingIndex};
+use crate::rng::WalletRng;
+use crate::transparent::{self, transparent_address};
 use crate::utils;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProvingKeyType {
+    Spend,
+    Output,
+    DelegatorVote,
+    Swap,
+    SwapClaim,
+    UndelegateClaim,
+    ActionLiquidityTournamentVote,
+}
+
+impl ProvingKeyType {
+    pub const ALL: [ProvingKeyType; 7] = [
+        ProvingKeyType::Spend,
+        ProvingKeyType::Output,
+        ProvingKeyType::DelegatorVote,
+        ProvingKeyType::Swap,
+        ProvingKeyType::SwapClaim,
+        ProvingKeyType::UndelegateClaim,
+        ProvingKeyType::ActionLiquidityTournamentVote,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProvingKeyType::Spend => "spend",
+            ProvingKeyType::Output => "output",
+            ProvingKeyType::DelegatorVote => "delegatorVote",
+            ProvingKeyType::Swap => "swap",
+            ProvingKeyType::SwapClaim => "swapClaim",
+            ProvingKeyType::UndelegateClaim => "undelegateClaim",
+            ProvingKeyType::ActionLiquidityTournamentVote => "actionLiquidityTournamentVote",
+        }
+    }
+
+    /// The key needed to prove an action, or `None` if the action carries no proof.
+    pub fn for_action(action: &ActionPlan) -> Option<ProvingKeyType> {
+        match action {
+            ActionPlan::Spend(_) => Some(ProvingKeyType::Spend),
+            ActionPlan::Output(_) => Some(ProvingKeyType::Output),
+            ActionPlan::DelegatorVote(_) => Some(ProvingKeyType::DelegatorVote),
+            ActionPlan::Swap(_) => Some(ProvingKeyType::Swap),
+            ActionPlan::SwapClaim(_) => Some(ProvingKeyType::SwapClaim),
+            ActionPlan::UndelegateClaim(_) => Some(ProvingKeyType::UndelegateClaim),
+            ActionPlan::ActionLiquidityTournamentVote(_) => {
+                Some(ProvingKeyType::ActionLiquidityTournamentVote)
+            }
+            _ => None,
+        }
+    }
+
+    /// The proving key slot the action's proofs are made with. Liquidity tournament votes
+    /// are proven by `penumbra-funding` with the delegator vote key, so the two share a
+    /// slot; every other key type has a slot of its own.
+    pub fn slot(self) -> ProvingKeyType {
+        match self {
+            ProvingKeyType::ActionLiquidityTournamentVote => ProvingKeyType::DelegatorVote,
+            other => other,
+        }
+    }
+
+    fn is_loaded(self) -> bool {
+        loaded_proving_keys().contains(&self.slot())
+    }
+
+    fn try_load(self, key: &[u8], checked: bool) -> anyhow::Result<()> {
+        // Bytes loaded under another key type's slot are always checked, so that a wrong key
+        // loaded for liquidity tournament votes cannot break delegator votes. Checking hashes
+        // the whole key, so the slot's own key type keeps the unchecked path.
+        let checked = checked || self.slot() != self;
+
+        let proving_key = match self {
+            ProvingKeyType::Spend => &SPEND_PROOF_PROVING_KEY,
+            ProvingKeyType::Output => &OUTPUT_PROOF_PROVING_KEY,
+            ProvingKeyType::DelegatorVote => &DELEGATOR_VOTE_PROOF_PROVING_KEY,
+            ProvingKeyType::Swap => &SWAP_PROOF_PROVING_KEY,
+            ProvingKeyType::SwapClaim => &SWAPCLAIM_PROOF_PROVING_KEY,
+            ProvingKeyType::UndelegateClaim => &CONVERT_PROOF_PROVING_KEY,
+            ProvingKeyType::ActionLiquidityTournamentVote => &DELEGATOR_VOTE_PROOF_PROVING_KEY,
+        };
+
+        if checked {
+            // Compares the key's id against the one shipped with `penumbra-proof-params`.
+            proving_key.try_load(key)?;
+        } else {
+            proving_key.try_load_unchecked(key)?;
+        }
+        loaded_proving_keys().insert(self.slot());
+        Ok(())
+    }
+}
+
+impl FromStr for ProvingKeyType {
+    type Err = anyhow::Error;
+
+    fn from_str(key_type: &str) -> anyhow::Result<Self> {
+        ProvingKeyType::ALL
+            .into_iter()
+            .find(|candidate| candidate.as_str() == key_type)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported key type"))
+    }
+}
+
+/// The proving keys are process-wide statics, so the record of which slots are loaded is too.
+static LOADED_PROVING_KEYS: Mutex<BTreeSet<ProvingKeyType>> = Mutex::new(BTreeSet::new());
+
+fn loaded_proving_keys() -> MutexGuard<'static, BTreeSet<ProvingKeyType>> {
+    LOADED_PROVING_KEYS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 /// Loads the proving key as a collection of bytes, and to sets the keys in memory
 /// dynamicaly at runtime. Failure to bundle the proving keys in the wasm binary
 /// or call the load function will fail to generate a proof. Consumers of this
 /// function will additionally require downloading the proving key parameter `.bin`
-/// file for each key type.
+/// file for each key type. `actionLiquidityTournamentVote` shares the delegator vote
+/// key, so bytes loaded under that name are checked as in `load_proving_key_checked`.
 #[wasm_bindgen]
 pub fn load_proving_key(key: &[u8], key_type: &str) -> WasmResult<()> {
-    // Map key type with proving keys.
-    let proving_key_map = match key_type {
-        "spend" => &SPEND_PROOF_PROVING_KEY,
-        "output" => &OUTPUT_PROOF_PROVING_KEY,
-        "delegatorVote" => &DELEGATOR_VOTE_PROOF_PROVING_KEY,
-        "swap" => &SWAP_PROOF_PROVING_KEY,
-        "swapClaim" => &SWAPCLAIM_PROOF_PROVING_KEY,
-        "undelegateClaim" => &CONVERT_PROOF_PROVING_KEY,
-        "actionLiquidityTournamentVote" => &DELEGATOR_VOTE_PROOF_PROVING_KEY,
-        _ => return Err(anyhow::anyhow!("Unsupported key type").into()),
-    };
+    let key_type = ProvingKeyType::from_str(key_type)?;
 
     // Load proving key.
-    proving_key_map.try_load_unchecked(key)?;
+    key_type.try_load(key, false)?;
     Ok(())
 }
 
+/// Loads a proving key like `load_proving_key`, but first checks that the bytes are
+/// the key expected for the key type, so that a corrupted or tampered download is
+/// rejected instead of producing invalid proofs. A loaded key cannot be replaced, so
+/// loading into a slot that is already populated is an error rather than a silent no-op.
+/// Arguments:
+///     key: `Uint8Array` with the contents of the proving key `.bin` file
+///     key_type: `string`, e.g. `spend`
+/// Returns: nothing, or an error if the key does not match or is already loaded
+#[wasm_bindgen]
+pub fn load_proving_key_checked(key: &[u8], key_type: &str) -> WasmResult<()> {
+    utils::set_panic_hook();
+
+    let key_type = ProvingKeyType::from_str(key_type)?;
+    if key_type.is_loaded() {
+        return Err(WasmError::ProvingKeyAlreadyLoaded(key_type.as_str()));
+    }
+    key_type
+        .try_load(key, true)
+        .map_err(|error| WasmError::InvalidProvingKey {
+            key_type: key_type.as_str(),
+            reason: error.to_string(),
+        })?;
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvingKeyStatus {
+    pub loaded: Vec<ProvingKeyType>,
+    pub missing: Vec<ProvingKeyType>,
+}
+
+/// Reports which proving keys are loaded
+/// Returns: `ProvingKeyStatus`
+#[wasm_bindgen]
+pub fn proving_key_status() -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let result = serde_wasm_bindgen::to_value(&proving_key_status_inner())?;
+    Ok(result)
+}
+
+pub fn proving_key_status_inner() -> ProvingKeyStatus {
+    let (loaded, missing) = ProvingKeyType::ALL
+        .into_iter()
+        .partition(|key_type| key_type.is_loaded());
+    ProvingKeyStatus { loaded, missing }
+}
+
+/// Lists the proving keys needed to build a transaction plan
+/// Arguments:
+///     transaction_plan: `TransactionPlan`
+/// Returns: `ProvingKeyType[]`
+#[wasm_bindgen]
+pub fn required_proving_keys(transaction_plan: &[u8]) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let plan = TransactionPlan::decode(transaction_plan)?;
+    let result = serde_wasm_bindgen::to_value(&required_proving_keys_inner(&plan))?;
+    Ok(result)
+}
+
+pub fn required_proving_keys_inner(plan: &TransactionPlan) -> Vec<ProvingKeyType> {
+    plan.actions
+        .iter()
+        .filter_map(ProvingKeyType::for_action)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+type ProvingKeyLoader = Box<dyn Fn(ProvingKeyType) -> WasmResult<Vec<u8>>>;
+
+thread_local! {
+    static PROVING_KEY_LOADER: RefCell<Option<ProvingKeyLoader>> = const { RefCell::new(None) };
+}
+
+/// Registers a callback that fetches proving keys the first time an action needs them.
+/// Building is synchronous, so the callback must return the key bytes directly, e.g.
+/// from a cache the host has already filled.
+/// Arguments:
+///     loader: `(keyType: string) => Uint8Array`
+#[wasm_bindgen]
+pub fn register_proving_key_loader(loader: Function) {
+    utils::set_panic_hook();
+
+    register_proving_key_loader_inner(move |key_type| {
+        let key = loader
+            .call1(&JsValue::NULL, &JsValue::from_str(key_type.as_str()))
+            .map_err(|error| anyhow::anyhow!("proving key loader failed: {error:?}"))?;
+        Ok(Uint8Array::new(&key).to_vec())
+    });
+}
+
+pub fn register_proving_key_loader_inner(
+    loader: impl Fn(ProvingKeyType) -> WasmResult<Vec<u8>> + 'static,
+) {
+    PROVING_KEY_LOADER.with(|cell| *cell.borrow_mut() = Some(Box::new(loader)));
+}
+
+/// Removes the callback registered with `register_proving_key_loader`.
+#[wasm_bindgen]
+pub fn clear_proving_key_loader() {
+    PROVING_KEY_LOADER.with(|cell| *cell.borrow_mut() = None);
+}
+
+/// Makes sure the proving key an action needs is loaded, fetching it through the
+/// registered loader if there is one. Fails with `WasmError::MissingProvingKey`
+/// if the key is neither loaded nor loadable.
+pub fn ensure_proving_key(action: &ActionPlan) -> WasmResult<()> {
+    let Some(key_type) = ProvingKeyType::for_action(action) else {
+        return Ok(());
+    };
+    if key_type.is_loaded() {
+        return Ok(());
+    }
+
+    let key = PROVING_KEY_LOADER.with(|cell| match cell.borrow().as_ref() {
+        Some(loader) => loader(key_type),
+        None => Err(WasmError::MissingProvingKey(key_type.as_str())),
+    })?;
+    load_proving_key_checked(&key, key_type.as_str())
+}
+
+/// The seed phrase lengths Penumbra accepts.
+pub const SEED_PHRASE_WORD_COUNTS: [usize; 2] = [12, 24];
+
+/// generate a new BIP-39 seed phrase
+/// Arguments:
+///     word_count: `12 | 24`
+/// Returns: `string`
+#[wasm_bindgen]
+pub fn generate_seed_phrase(word_count: u32) -> WasmResult<String> {
+    utils::set_panic_hook();
+
+    let word_count = word_count as usize;
+    if !SEED_PHRASE_WORD_COUNTS.contains(&word_count) {
+        return Err(WasmError::SeedPhraseWordCount(word_count));
+    }
+    let mnemonic = Mnemonic::generate_in_with(&mut WalletRng, Language::English, word_count)
+        .map_err(|error| anyhow::anyhow!("{error}"))?;
+    Ok(mnemonic.to_string())
+}
+
+/// check a seed phrase, naming the first word that is not in the BIP-39 word list
+/// Arguments:
+///     seed_phrase: `string`
+/// Returns: nothing, or an error describing why the phrase is invalid
+#[wasm_bindgen]
+pub fn validate_seed_phrase(seed_phrase: &str) -> WasmResult<()> {
+    utils::set_panic_hook();
+
+    parse_seed_phrase(seed_phrase)?;
+    Ok(())
+}
+
+pub fn parse_seed_phrase(seed_phrase: &str) -> WasmResult<SeedPhrase> {
+    let words: Vec<&str> = seed_phrase.split_whitespace().collect();
+    if !SEED_PHRASE_WORD_COUNTS.contains(&words.len()) {
+        return Err(WasmError::SeedPhraseWordCount(words.len()));
+    }
+
+    let normalized = words.join(" ");
+    Mnemonic::parse_in_normalized(Language::English, &normalized).map_err(|error| match error {
+        bip39::Error::UnknownWord(index) => WasmError::UnknownSeedPhraseWord {
+            index,
+            word: words[index].to_string(),
+        },
+        bip39::Error::InvalidChecksum => WasmError::InvalidSeedPhraseChecksum,
+        other => anyhow::anyhow!("{other}").into(),
+    })?;
+
+    Ok(SeedPhrase::from_str(&normalized)?)
+}
+
 /// generate a spend key from a seed phrase
 /// Arguments:
 ///     seed_phrase: `string`
 /// Returns: `Uint8Array representing inner SpendKey`
 #[wasm_bindgen]
 pub fn generate_spend_key(seed_phrase: &str) -> WasmResult<Vec<u8>> {
+    generate_spend_key_for_account(seed_phrase, 0)
+}
+
+/// generate the spend key of a BIP-44 account from a seed phrase
+/// Other Penumbra clients derive account `n` at path `m/44'/6532'/n'`.
+/// Arguments:
+///     seed_phrase: `string`
+///     account: `u32`
+/// Returns: `Uint8Array representing inner SpendKey`
+#[wasm_bindgen]
+pub fn generate_spend_key_for_account(seed_phrase: &str, account: u32) -> WasmResult<Vec<u8>> {
     utils::set_panic_hook();
 
-    let seed = SeedPhrase::from_str(seed_phrase)?;
-    let path = Bip44Path::new(0);
+    let seed = parse_seed_phrase(seed_phrase)?;
+    let path = Bip44Path::new(account);
     let spend_key = SpendKey::from_seed_phrase_bip44(seed, &path);
     Ok(spend_key.encode_to_vec())
 }
 
+/// import a bech32m-encoded spend key (`penumbraspendkey1...`)
+/// Arguments:
+///     spend_key: `string`
+/// Returns: `Uint8Array representing inner SpendKey`
+#[wasm_bindgen]
+pub fn import_spend_key(spend_key: &str) -> WasmResult<Vec<u8>> {
+    utils::set_panic_hook();
+
+    let spend_key = SpendKey::from_str(spend_key.trim())?;
+    Ok(spend_key.encode_to_vec())
+}
+
+/// export a spend key as bech32m (`penumbraspendkey1...`)
+/// Arguments:
+///     spend_key: `byte representation inner SpendKey`
+/// Returns: `string`
+#[wasm_bindgen]
+pub fn export_spend_key(spend_key: &[u8]) -> WasmResult<String> {
+    utils::set_panic_hook();
+
+    let spend_key = SpendKey::decode(spend_key)?;
+    Ok(spend_key.to_string())
+}
+
+/// import a bech32m-encoded full viewing key (`penumbrafullviewingkey1...`)
+/// Arguments:
+///     full_viewing_key: `string`
+/// Returns: `Uint8Array representing inner FullViewingKey`
+#[wasm_bindgen]
+pub fn import_full_viewing_key(full_viewing_key: &str) -> WasmResult<Vec<u8>> {
+    utils::set_panic_hook();
+
+    let fvk = FullViewingKey::from_str(full_viewing_key.trim())?;
+    Ok(fvk.encode_to_vec())
+}
+
+/// export a full viewing key as bech32m (`penumbrafullviewingkey1...`)
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+/// Returns: `string`
+#[wasm_bindgen]
+pub fn export_full_viewing_key(full_viewing_key: &[u8]) -> WasmResult<String> {
+    utils::set_panic_hook();
+
+    let fvk = FullViewingKey::decode(full_viewing_key)?;
+    Ok(fvk.to_string())
+}
+
 /// get full viewing key from spend key
 /// Arguments:
 ///     spend_key: `byte representation inner SpendKey`
@@ -127,7 +475,8 @@ pub fn get_index_by_address(full_viewing_key: &[u8], address: &[u8]) -> WasmResu
 
     let address: Address = Address::decode(address)?;
     let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
-    let index: Option<pb::AddressIndex> = fvk.address_index(&address).map(Into::into);
+    let index: Option<pb::AddressIndex> =
+        transparent::address_index(&fvk, &address).map(Into::into);
     let result = serde_wasm_bindgen::to_value(&index)?;
     Ok(result)
 }
@@ -142,8 +491,57 @@ pub fn is_controlled_address(full_viewing_key: &[u8], address: &[u8]) -> WasmRes
     Ok(is_controlled_inner(&fvk, &address))
 }
 
+/// Whether the FVK controls `address`, counting its transparent address.
 pub fn is_controlled_inner(fvk: &FullViewingKey, address: &Address) -> bool {
-    fvk.address_index(address).is_some()
+    transparent::address_index(fvk, address).is_some()
+}
+
+/// Describes an address from the point of view of a wallet.
+/// Addresses the FVK controls are decoded with their index and the wallet id, with the
+/// transparent address as account 0; any other address is opaque.
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     address: `byte representation inner Address`
+/// Returns: `Uint8Array representing inner AddressView`
+#[wasm_bindgen]
+pub fn address_view(full_viewing_key: &[u8], address: &[u8]) -> WasmResult<Vec<u8>> {
+    utils::set_panic_hook();
+
+    let address: Address = Address::decode(address)?;
+    let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
+    Ok(transparent::view_address(&fvk, address).encode_to_vec())
+}
+
+/// Derives the default address of a range of accounts
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     start_account: `u32`
+///     count: `u32`
+/// Returns: `Vec<AddressView>`, one decoded view per account
+#[wasm_bindgen]
+pub fn derive_addresses(
+    full_viewing_key: &[u8],
+    start_account: u32,
+    count: u32,
+) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
+    let end_account = start_account
+        .checked_add(count)
+        .ok_or_else(|| anyhow::anyhow!("account range {start_account} + {count} overflows"))?;
+    let views: Vec<pb::AddressView> = derive_addresses_inner(&fvk, start_account..end_account)
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let result = serde_wasm_bindgen::to_value(&views)?;
+    Ok(result)
+}
+
+pub fn derive_addresses_inner(fvk: &FullViewingKey, accounts: Range<u32>) -> Vec<AddressView> {
+    accounts
+        .map(|account| fvk.view_address(fvk.payment_address(AddressIndex::new(account)).0))
+        .collect()
 }
 
 #[wasm_bindgen(getter_with_clone)]
@@ -178,18 +576,12 @@ pub fn get_noble_forwarding_addr(
 
 /// Noble Randomizer: [0xff; 10] followed by LE16(sequence)
 pub fn forwarding_addr_inner(sequence: u16, account: Option<u32>, fvk: &FullViewingKey) -> Address {
-    let mut randomizer: [u8; 12] = [0xff; 12]; // Initialize all 12 bytes to 0xff
-    let seq_bytes = sequence.to_le_bytes();
-    randomizer[10..].copy_from_slice(&seq_bytes); // Replace the last 2 bytes with seq_bytes
-
-    let index = AddressIndex {
+    let index = ForwardingIndex {
+        chain: ForwardingChain::Noble,
         account: account.unwrap_or_default(),
-        randomizer,
+        sequence,
     };
-
-    let (address, _dtk) = fvk.incoming().payment_address(index);
-
-    address
+    penumbra_forwarding_address(fvk, index)
 }
 
 #[wasm_bindgen(getter_with_clone)]
@@ -203,13 +595,14 @@ pub struct TransparentAddrResponse {
 /// Returns the "truncated" address (t-addr) associated with the account.
 #[wasm_bindgen]
 pub fn get_transparent_address(full_viewing_key: &[u8]) -> WasmResult<TransparentAddrResponse> {
+    utils::set_panic_hook();
+
     let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
-    let encoding = fvk.incoming().transparent_address();
-    let address: Address = encoding.parse().expect("encoding transparent address");
+    let address = transparent_address(&fvk)?;
 
     Ok(TransparentAddrResponse {
         address: address.encode_to_vec(),
-        encoding: encoding.to_string(),
+        encoding: fvk.incoming().transparent_address().to_string(),
     })
 }
 
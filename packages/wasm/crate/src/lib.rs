@@ -1,18 +1,28 @@
+pub mod address_book;
 pub mod asset;
 pub mod auction;
 pub mod build;
+pub mod classify;
 pub mod database;
 pub mod dex;
 pub mod error;
+pub mod fmd;
+pub mod forwarding;
+pub mod invoice;
 pub mod keys;
+pub mod memo;
 pub mod metadata;
 pub mod note_record;
+pub mod plan_view;
 pub mod planner;
+pub mod rng;
 pub mod stake;
 pub mod storage;
 pub mod swap_record;
+pub mod transparent;
 pub mod tree;
 pub mod tx;
 pub mod utils;
+pub mod verify;
 pub mod view_server;
 pub mod voting;
@@ -0,0 +1,73 @@
+use penumbra_keys::address::ADDRESS_LEN_BYTES;
+use penumbra_keys::FullViewingKey;
+use penumbra_proto::core::transaction::v1 as pb;
+use penumbra_proto::DomainType;
+use penumbra_transaction::memo::{MemoPlaintext, MEMO_LEN_BYTES};
+use penumbra_transaction::{MemoPlaintextView, MemoView, Transaction};
+use prost::Message;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::error::{WasmError, WasmResult};
+use crate::transparent;
+use crate::utils;
+
+/// The longest memo text the protocol accepts, in bytes: the memo plaintext also carries the
+/// return address.
+pub const MAX_MEMO_TEXT_LEN: usize = MEMO_LEN_BYTES - ADDRESS_LEN_BYTES;
+
+/// Decrypt the memo of a transaction
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     transaction: `byte representation inner Transaction`
+/// Returns: `pb::MemoView`, visible if the memo could be decrypted and opaque otherwise, or
+/// `undefined` if the transaction has no memo
+#[wasm_bindgen]
+pub fn decrypt_memo(full_viewing_key: &[u8], transaction: &[u8]) -> WasmResult<Option<Vec<u8>>> {
+    utils::set_panic_hook();
+
+    let fvk = FullViewingKey::decode(full_viewing_key)?;
+    let tx = Transaction::decode(transaction)?;
+
+    Ok(memo_view(&fvk, &tx).map(|memo_view| pb::MemoView::from(memo_view).encode_to_vec()))
+}
+
+pub fn memo_view(fvk: &FullViewingKey, tx: &Transaction) -> Option<MemoView> {
+    let ciphertext = tx.transaction_body.memo.clone()?;
+
+    let memo_view = match tx.decrypt_memo(fvk) {
+        Ok(plaintext) => MemoView::Visible {
+            plaintext: MemoPlaintextView {
+                return_address: transparent::view_address(fvk, plaintext.return_address()),
+                text: plaintext.text().to_string(),
+            },
+            ciphertext,
+        },
+        Err(_) => MemoView::Opaque { ciphertext },
+    };
+    Some(memo_view)
+}
+
+/// Check a memo against the protocol limits before planning
+/// Arguments:
+///     memo: `pb::MemoPlaintext`
+/// Returns: nothing, or an error describing why the memo is invalid
+#[wasm_bindgen]
+pub fn validate_memo(memo: &[u8]) -> WasmResult<()> {
+    utils::set_panic_hook();
+
+    let memo = pb::MemoPlaintext::decode(memo)?;
+    validate_memo_inner(memo)?;
+    Ok(())
+}
+
+pub fn validate_memo_inner(memo: pb::MemoPlaintext) -> WasmResult<MemoPlaintext> {
+    if memo.text.len() > MAX_MEMO_TEXT_LEN {
+        return Err(WasmError::MemoTooLong {
+            len: memo.text.len(),
+            max: MAX_MEMO_TEXT_LEN,
+        });
+    }
+
+    let memo = memo.try_into()?;
+    Ok(memo)
+}
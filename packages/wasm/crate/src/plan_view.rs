@@ -0,0 +1,523 @@
+use std::collections::BTreeSet;
+
+use penumbra_asset::asset::{Cache, Id, Metadata};
+use penumbra_asset::{Value, ValueView, STAKING_TOKEN_ASSET_ID};
+use penumbra_auction::auction::AuctionNft;
+use penumbra_dex::lp::position;
+use penumbra_keys::{AddressView, FullViewingKey};
+use penumbra_proto::DomainType;
+use penumbra_stake::{DelegationToken, IdentityKey};
+use penumbra_tct::StateCommitment;
+use penumbra_transaction::plan::TransactionPlan;
+use penumbra_transaction::ActionPlan;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+
+use crate::database::interface::Database;
+use crate::error::WasmResult;
+use crate::metadata::customize_symbol_inner;
+use crate::storage::{init_idb_storage, Storage};
+use crate::transparent;
+use crate::utils;
+
+/// A planned action, as it will appear once the plan is built.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PlannedActionView {
+    Spend {
+        value: ValueView,
+        address: AddressView,
+    },
+    #[serde(rename_all = "camelCase")]
+    Output {
+        value: ValueView,
+        recipient: AddressView,
+        /// The output was added by the planner to return change to the wallet.
+        change: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Swap {
+        input_1: ValueView,
+        input_2: ValueView,
+        claim_fee: ValueView,
+        claim_address: AddressView,
+    },
+    #[serde(rename_all = "camelCase")]
+    SwapClaim {
+        output_1: ValueView,
+        output_2: ValueView,
+        claim_address: AddressView,
+    },
+    DelegatorVote {
+        proposal: u64,
+        value: ValueView,
+    },
+    #[serde(rename_all = "camelCase")]
+    Delegate {
+        validator: IdentityKey,
+        unbonded: ValueView,
+        delegation: ValueView,
+    },
+    #[serde(rename_all = "camelCase")]
+    Undelegate {
+        validator: IdentityKey,
+        delegation: ValueView,
+        unbonding: ValueView,
+    },
+    #[serde(rename_all = "camelCase")]
+    UndelegateClaim {
+        validator: IdentityKey,
+        unbonding: ValueView,
+        /// The staking tokens released once the penalty is applied.
+        claimed: ValueView,
+    },
+    #[serde(rename_all = "camelCase")]
+    Ics20Withdrawal {
+        value: ValueView,
+        /// The address on the counterparty chain.
+        destination: String,
+        channel: String,
+        return_address: AddressView,
+    },
+    #[serde(rename_all = "camelCase")]
+    PositionOpen {
+        position_id: position::Id,
+        reserves_1: ValueView,
+        reserves_2: ValueView,
+        fee_bps: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    PositionClose {
+        position_id: position::Id,
+    },
+    #[serde(rename_all = "camelCase")]
+    PositionWithdraw {
+        position_id: position::Id,
+        reserves_1: ValueView,
+        reserves_2: ValueView,
+    },
+    #[serde(rename_all = "camelCase")]
+    LiquidityTournamentVote {
+        /// The base denom of the asset voted for.
+        incentivized: String,
+        value: ValueView,
+        rewards_recipient: AddressView,
+    },
+    #[serde(rename_all = "camelCase")]
+    AuctionSchedule {
+        input: ValueView,
+        /// The asset the auction sells for, if its metadata is known.
+        output_denom: Option<Metadata>,
+        auction_nft: ValueView,
+    },
+    #[serde(rename_all = "camelCase")]
+    AuctionEnd {
+        auction_nft: ValueView,
+    },
+    #[serde(rename_all = "camelCase")]
+    AuctionWithdraw {
+        auction_nft: ValueView,
+        reserves_input: ValueView,
+        reserves_output: ValueView,
+    },
+    /// Actions without a dedicated rendering.
+    Other {
+        action: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoPlanView {
+    pub text: String,
+    pub return_address: AddressView,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanView {
+    pub actions: Vec<PlannedActionView>,
+    pub fee: ValueView,
+    pub memo: Option<MemoPlanView>,
+    pub expiry_height: u64,
+}
+
+/// The difference between two plans for the same request, such as a plan and its re-plan.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanDiff {
+    /// Actions in the new plan that the old plan does not have.
+    pub added: Vec<PlannedActionView>,
+    /// Actions in the old plan that the new plan no longer has.
+    pub removed: Vec<PlannedActionView>,
+    /// The old and new fee, if it changed.
+    pub fee: Option<(ValueView, ValueView)>,
+    pub memo_changed: bool,
+    pub expiry_height_changed: bool,
+}
+
+/// Render a transaction plan for review before it is built
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     transaction_plan: `pb::TransactionPlan`
+///     change_commitments: `Vec<StateCommitment>`, optional; the planner's `changeCommitments`
+///     idb_constants: `IndexedDbConstants`
+/// Returns: `PlanView`
+#[wasm_bindgen]
+pub async fn view_transaction_plan(
+    full_viewing_key: &[u8],
+    transaction_plan: &[u8],
+    change_commitments: JsValue,
+    idb_constants: JsValue,
+) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let fvk = FullViewingKey::decode(full_viewing_key)?;
+    let plan = TransactionPlan::decode(transaction_plan)?;
+    let change: Option<Vec<StateCommitment>> = serde_wasm_bindgen::from_value(change_commitments)?;
+    let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let storage = init_idb_storage(constants).await?;
+
+    let plan_view =
+        view_transaction_plan_inner(&storage, &fvk, &plan, &change.unwrap_or_default()).await?;
+
+    let result = serde_wasm_bindgen::to_value(&plan_view)?;
+    Ok(result)
+}
+
+/// Explain how a re-planned transaction differs from the original plan
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     old_plan: `pb::TransactionPlan`
+///     new_plan: `pb::TransactionPlan`
+///     old_change_commitments: `Vec<StateCommitment>`, optional; the old plan's `changeCommitments`
+///     new_change_commitments: `Vec<StateCommitment>`, optional; the new plan's `changeCommitments`
+///     idb_constants: `IndexedDbConstants`
+/// Returns: `PlanDiff`
+#[wasm_bindgen]
+pub async fn diff_transaction_plans(
+    full_viewing_key: &[u8],
+    old_plan: &[u8],
+    new_plan: &[u8],
+    old_change_commitments: JsValue,
+    new_change_commitments: JsValue,
+    idb_constants: JsValue,
+) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let fvk = FullViewingKey::decode(full_viewing_key)?;
+    let old_plan = TransactionPlan::decode(old_plan)?;
+    let new_plan = TransactionPlan::decode(new_plan)?;
+    let old_change: Option<Vec<StateCommitment>> =
+        serde_wasm_bindgen::from_value(old_change_commitments)?;
+    let new_change: Option<Vec<StateCommitment>> =
+        serde_wasm_bindgen::from_value(new_change_commitments)?;
+    let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let storage = init_idb_storage(constants).await?;
+
+    let old_view =
+        view_transaction_plan_inner(&storage, &fvk, &old_plan, &old_change.unwrap_or_default())
+            .await?;
+    let new_view =
+        view_transaction_plan_inner(&storage, &fvk, &new_plan, &new_change.unwrap_or_default())
+            .await?;
+    let diff = diff_plan_views(&old_view, &new_view);
+
+    let result = serde_wasm_bindgen::to_value(&diff)?;
+    Ok(result)
+}
+
+pub async fn view_transaction_plan_inner<Db: Database>(
+    storage: &Storage<Db>,
+    fvk: &FullViewingKey,
+    plan: &TransactionPlan,
+    change_commitments: &[StateCommitment],
+) -> WasmResult<PlanView> {
+    let fee = plan.transaction_parameters.fee;
+
+    let mut asset_ids = BTreeSet::from([fee.asset_id()]);
+    let mut derived_denoms = Vec::new();
+    for action in &plan.actions {
+        match action {
+            ActionPlan::Spend(spend) => {
+                asset_ids.insert(spend.note.asset_id());
+            }
+            ActionPlan::Output(output) => {
+                asset_ids.insert(output.value.asset_id);
+            }
+            ActionPlan::Swap(swap) => {
+                asset_ids.insert(swap.swap_plaintext.trading_pair.asset_1());
+                asset_ids.insert(swap.swap_plaintext.trading_pair.asset_2());
+                asset_ids.insert(swap.swap_plaintext.claim_fee.asset_id());
+            }
+            ActionPlan::SwapClaim(claim) => {
+                asset_ids.insert(claim.swap_plaintext.trading_pair.asset_1());
+                asset_ids.insert(claim.swap_plaintext.trading_pair.asset_2());
+            }
+            ActionPlan::DelegatorVote(vote) => {
+                asset_ids.insert(vote.staked_note.asset_id());
+            }
+            ActionPlan::Delegate(delegate) => {
+                asset_ids.insert(*STAKING_TOKEN_ASSET_ID);
+                derived_denoms.push(DelegationToken::new(delegate.validator_identity).denom());
+            }
+            ActionPlan::Undelegate(undelegate) => {
+                derived_denoms.push(DelegationToken::new(undelegate.validator_identity).denom());
+                derived_denoms.push(undelegate.unbonding_token().denom());
+            }
+            ActionPlan::UndelegateClaim(claim) => {
+                asset_ids.insert(*STAKING_TOKEN_ASSET_ID);
+                derived_denoms.push(claim.unbonding_token().denom());
+            }
+            ActionPlan::Ics20Withdrawal(withdrawal) => {
+                asset_ids.insert(withdrawal.denom.id());
+            }
+            ActionPlan::PositionOpen(open) => {
+                asset_ids.insert(open.position.phi.pair.asset_1());
+                asset_ids.insert(open.position.phi.pair.asset_2());
+            }
+            ActionPlan::PositionWithdraw(withdraw) => {
+                asset_ids.insert(withdraw.pair.asset_1());
+                asset_ids.insert(withdraw.pair.asset_2());
+            }
+            ActionPlan::ActionLiquidityTournamentVote(vote) => {
+                asset_ids.insert(vote.staked_note.asset_id());
+            }
+            ActionPlan::ActionDutchAuctionSchedule(schedule) => {
+                asset_ids.insert(schedule.description.input.asset_id);
+                asset_ids.insert(schedule.description.output_id);
+                derived_denoms.push(AuctionNft::new(schedule.description.id(), 0).metadata);
+            }
+            ActionPlan::ActionDutchAuctionEnd(end) => {
+                derived_denoms.push(AuctionNft::new(end.auction_id, 0).metadata);
+            }
+            ActionPlan::ActionDutchAuctionWithdraw(withdraw) => {
+                asset_ids.insert(withdraw.reserves_input.asset_id);
+                asset_ids.insert(withdraw.reserves_output.asset_id);
+                derived_denoms.push(
+                    AuctionNft::new(withdraw.auction_id, withdraw.seq.saturating_sub(1)).metadata,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let mut denoms = Cache::default();
+    for id in asset_ids {
+        if let Some(denom) = storage.get_asset(&id).await? {
+            denoms.extend(Some(denom));
+        }
+    }
+    for metadata in derived_denoms {
+        // Prefer stored metadata, which already has a customized symbol.
+        let denom = match storage.get_asset(&metadata.id()).await? {
+            Some(denom) => denom,
+            None => Metadata::try_from(customize_symbol_inner(metadata.to_proto())?)?,
+        };
+        denoms.extend(Some(denom));
+    }
+
+    let view_value = |value: Value| value.view_with_cache(&denoms);
+    // Auction NFTs are unique, so a plan always refers to exactly one of them.
+    let view_nft = |asset_id: Id| {
+        view_value(Value {
+            amount: 1u64.into(),
+            asset_id,
+        })
+    };
+
+    let actions = plan
+        .actions
+        .iter()
+        .map(|action| match action {
+            ActionPlan::Spend(spend) => PlannedActionView::Spend {
+                value: view_value(spend.note.value()),
+                address: transparent::view_address(fvk, spend.note.address()),
+            },
+            ActionPlan::Output(output) => PlannedActionView::Output {
+                value: view_value(output.value),
+                recipient: transparent::view_address(fvk, output.dest_address.clone()),
+                change: change_commitments.contains(&output.output_note().commit()),
+            },
+            ActionPlan::Swap(swap) => {
+                let plaintext = &swap.swap_plaintext;
+                PlannedActionView::Swap {
+                    input_1: view_value(Value {
+                        amount: plaintext.delta_1_i,
+                        asset_id: plaintext.trading_pair.asset_1(),
+                    }),
+                    input_2: view_value(Value {
+                        amount: plaintext.delta_2_i,
+                        asset_id: plaintext.trading_pair.asset_2(),
+                    }),
+                    claim_fee: view_value(plaintext.claim_fee.0),
+                    claim_address: transparent::view_address(fvk, plaintext.claim_address.clone()),
+                }
+            }
+            ActionPlan::SwapClaim(claim) => {
+                let plaintext = &claim.swap_plaintext;
+                let (lambda_1, lambda_2) = claim
+                    .output_data
+                    .pro_rata_outputs((plaintext.delta_1_i, plaintext.delta_2_i));
+                PlannedActionView::SwapClaim {
+                    output_1: view_value(Value {
+                        amount: lambda_1,
+                        asset_id: plaintext.trading_pair.asset_1(),
+                    }),
+                    output_2: view_value(Value {
+                        amount: lambda_2,
+                        asset_id: plaintext.trading_pair.asset_2(),
+                    }),
+                    claim_address: transparent::view_address(fvk, plaintext.claim_address.clone()),
+                }
+            }
+            ActionPlan::DelegatorVote(vote) => PlannedActionView::DelegatorVote {
+                proposal: vote.proposal,
+                value: view_value(vote.staked_note.value()),
+            },
+            ActionPlan::ActionDutchAuctionSchedule(schedule) => {
+                PlannedActionView::AuctionSchedule {
+                    input: view_value(schedule.description.input),
+                    output_denom: denoms.get(&schedule.description.output_id).cloned(),
+                    auction_nft: view_nft(
+                        AuctionNft::new(schedule.description.id(), 0).metadata.id(),
+                    ),
+                }
+            }
+            ActionPlan::ActionDutchAuctionEnd(end) => PlannedActionView::AuctionEnd {
+                auction_nft: view_nft(AuctionNft::new(end.auction_id, 0).metadata.id()),
+            },
+            ActionPlan::ActionDutchAuctionWithdraw(withdraw) => {
+                PlannedActionView::AuctionWithdraw {
+                    auction_nft: view_nft(
+                        AuctionNft::new(withdraw.auction_id, withdraw.seq.saturating_sub(1))
+                            .metadata
+                            .id(),
+                    ),
+                    reserves_input: view_value(withdraw.reserves_input),
+                    reserves_output: view_value(withdraw.reserves_output),
+                }
+            }
+            ActionPlan::Delegate(delegate) => PlannedActionView::Delegate {
+                validator: delegate.validator_identity,
+                unbonded: view_value(Value {
+                    amount: delegate.unbonded_amount,
+                    asset_id: *STAKING_TOKEN_ASSET_ID,
+                }),
+                delegation: view_value(Value {
+                    amount: delegate.delegation_amount,
+                    asset_id: DelegationToken::new(delegate.validator_identity).id(),
+                }),
+            },
+            ActionPlan::Undelegate(undelegate) => PlannedActionView::Undelegate {
+                validator: undelegate.validator_identity,
+                delegation: view_value(Value {
+                    amount: undelegate.delegation_amount,
+                    asset_id: DelegationToken::new(undelegate.validator_identity).id(),
+                }),
+                unbonding: view_value(Value {
+                    amount: undelegate.unbonded_amount,
+                    asset_id: undelegate.unbonding_token().id(),
+                }),
+            },
+            ActionPlan::UndelegateClaim(claim) => PlannedActionView::UndelegateClaim {
+                validator: claim.validator_identity,
+                unbonding: view_value(Value {
+                    amount: claim.unbonding_amount,
+                    asset_id: claim.unbonding_token().id(),
+                }),
+                claimed: view_value(Value {
+                    amount: claim.penalty.apply_to_amount(claim.unbonding_amount),
+                    asset_id: *STAKING_TOKEN_ASSET_ID,
+                }),
+            },
+            ActionPlan::Ics20Withdrawal(withdrawal) => PlannedActionView::Ics20Withdrawal {
+                value: view_value(Value {
+                    amount: withdrawal.amount,
+                    asset_id: withdrawal.denom.id(),
+                }),
+                destination: withdrawal.destination_chain_address.clone(),
+                channel: withdrawal.source_channel.to_string(),
+                return_address: transparent::view_address(fvk, withdrawal.return_address.clone()),
+            },
+            ActionPlan::PositionOpen(open) => {
+                let position = &open.position;
+                PlannedActionView::PositionOpen {
+                    position_id: position.id(),
+                    reserves_1: view_value(Value {
+                        amount: position.reserves.r1,
+                        asset_id: position.phi.pair.asset_1(),
+                    }),
+                    reserves_2: view_value(Value {
+                        amount: position.reserves.r2,
+                        asset_id: position.phi.pair.asset_2(),
+                    }),
+                    fee_bps: position.phi.component.fee,
+                }
+            }
+            ActionPlan::PositionClose(close) => PlannedActionView::PositionClose {
+                position_id: close.position_id,
+            },
+            ActionPlan::PositionWithdraw(withdraw) => PlannedActionView::PositionWithdraw {
+                position_id: withdraw.position_id,
+                reserves_1: view_value(Value {
+                    amount: withdraw.reserves.r1,
+                    asset_id: withdraw.pair.asset_1(),
+                }),
+                reserves_2: view_value(Value {
+                    amount: withdraw.reserves.r2,
+                    asset_id: withdraw.pair.asset_2(),
+                }),
+            },
+            ActionPlan::ActionLiquidityTournamentVote(vote) => {
+                PlannedActionView::LiquidityTournamentVote {
+                    incentivized: vote.incentivized.denom.clone(),
+                    value: view_value(vote.staked_note.value()),
+                    rewards_recipient: transparent::view_address(
+                        fvk,
+                        vote.rewards_recipient.clone(),
+                    ),
+                }
+            }
+            other => PlannedActionView::Other {
+                action: utils::action_name(other.variant_index()).to_string(),
+            },
+        })
+        .collect();
+
+    let memo = plan.memo.as_ref().map(|memo| MemoPlanView {
+        text: memo.plaintext.text().to_string(),
+        return_address: transparent::view_address(fvk, memo.plaintext.return_address()),
+    });
+
+    Ok(PlanView {
+        actions,
+        fee: view_value(fee.0),
+        memo,
+        expiry_height: plan.transaction_parameters.expiry_height,
+    })
+}
+
+pub fn diff_plan_views(old: &PlanView, new: &PlanView) -> PlanDiff {
+    // Actions are compared by what they do, so the same action planned twice matches even though
+    // its randomness differs.
+    let mut removed = old.actions.clone();
+    let mut added = Vec::new();
+    for action in &new.actions {
+        match removed.iter().position(|old_action| old_action == action) {
+            Some(index) => {
+                removed.remove(index);
+            }
+            None => added.push(action.clone()),
+        }
+    }
+
+    PlanDiff {
+        added,
+        removed,
+        fee: (old.fee != new.fee).then(|| (old.fee.clone(), new.fee.clone())),
+        memo_changed: old.memo != new.memo,
+        expiry_height_changed: old.expiry_height != new.expiry_height,
+    }
+}
@@ -30,12 +30,13 @@ use penumbra_sct::params::SctParameters;
 use penumbra_shielded_pool::{fmd, Note, OutputPlan, SpendPlan};
 use penumbra_stake::rate::RateData;
 use penumbra_stake::{IdentityKey, Penalty, Undelegate, UndelegateClaimPlan};
-use penumbra_tct::Position;
+use penumbra_tct::{Position, StateCommitment};
 use penumbra_transaction::gas::swap_claim_gas_cost;
 use penumbra_transaction::memo::MemoPlaintext;
 use penumbra_transaction::{plan::MemoPlan, ActionPlan, TransactionParameters};
 use penumbra_transaction::{ActionList, TransactionPlan};
-use rand_core::{OsRng, RngCore};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::mem;
 use std::num::{NonZero, NonZeroU32};
@@ -44,9 +45,12 @@ use wasm_bindgen::JsValue;
 
 use crate::database::interface::Database;
 use crate::error::WasmError;
+use crate::memo::validate_memo_inner;
 use crate::metadata::customize_symbol_inner;
 use crate::note_record::SpendableNoteRecord;
+use crate::rng::WalletRng;
 use crate::storage::{init_idb_storage, DbConstants, OutstandingReserves, Storage};
+use crate::transparent::{self, transparent_address};
 use crate::utils;
 use crate::{error::WasmResult, swap_record::SwapRecord};
 
@@ -161,6 +165,24 @@ pub async fn insufficient_funds_err<Db: Database>(
     anyhow!(error_message).into()
 }
 
+/// Choices the planner makes that are not part of a `TransactionPlannerRequest`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlannerOptions {
+    /// Return change, and the automatic memo's return address, to the wallet's transparent
+    /// address instead of the default address of the source account. Only account 0 has a
+    /// transparent address.
+    pub transparent_change: bool,
+}
+
+/// A transaction plan, with the note commitments of the outputs the planner added as change.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedTransaction {
+    pub plan: TransactionPlan,
+    pub change_commitments: Vec<StateCommitment>,
+}
+
 /// Process a `TransactionPlannerRequest`, returning a `TransactionPlan`
 #[wasm_bindgen]
 pub async fn plan_transaction(
@@ -189,8 +211,82 @@ pub async fn plan_transaction_inner<Db: Database>(
     fvk: FullViewingKey,
     fee_asset_id: Id,
 ) -> WasmResult<TransactionPlan> {
+    plan_transaction_with_rng(storage, request, fvk, fee_asset_id, &mut WalletRng).await
+}
+
+/// Like `plan_transaction_inner`, with every random choice (blinding factors, note
+/// randomness, nonces) drawn from `rng`.
+pub async fn plan_transaction_with_rng<Db: Database, R: RngCore + CryptoRng>(
+    storage: Storage<Db>,
+    request: TransactionPlannerRequest,
+    fvk: FullViewingKey,
+    fee_asset_id: Id,
+    rng: &mut R,
+) -> WasmResult<TransactionPlan> {
+    plan_transaction_with_options_inner(
+        storage,
+        request,
+        fvk,
+        fee_asset_id,
+        PlannerOptions::default(),
+        rng,
+    )
+    .await
+    .map(|planned| planned.plan)
+}
+
+/// Process a `TransactionPlannerRequest` with `PlannerOptions`, returning a `TransactionPlan`
+/// Arguments:
+///     idb_constants: `IndexedDbConstants`
+///     request: `byte representation inner TransactionPlannerRequest`
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     gas_fee_token: `byte representation inner AssetId`
+///     options: `PlannerOptions`
+/// Returns: `PlannedTransaction`
+#[wasm_bindgen]
+pub async fn plan_transaction_with_options(
+    idb_constants: JsValue,
+    request: &[u8],
+    full_viewing_key: &[u8],
+    gas_fee_token: &[u8],
+    options: JsValue,
+) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let tx_planner_req = TransactionPlannerRequest::decode(request)?;
+    let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
+    let fee_asset_id = Id::decode(gas_fee_token)?;
+    let options: PlannerOptions = serde_wasm_bindgen::from_value(options)?;
+    let constants: DbConstants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let storage = init_idb_storage(constants).await?;
+
+    let planned = plan_transaction_with_options_inner(
+        storage,
+        tx_planner_req,
+        fvk,
+        fee_asset_id,
+        options,
+        &mut WalletRng,
+    )
+    .await?;
+
+    Ok(serde_wasm_bindgen::to_value(&planned)?)
+}
+
+/// Like `plan_transaction_with_rng`, with the choices in `options` applied.
+pub async fn plan_transaction_with_options_inner<Db: Database, R: RngCore + CryptoRng>(
+    storage: Storage<Db>,
+    request: TransactionPlannerRequest,
+    fvk: FullViewingKey,
+    fee_asset_id: Id,
+    options: PlannerOptions,
+    rng: &mut R,
+) -> WasmResult<PlannedTransaction> {
     let expiry_height: u64 = request.expiry_height;
 
+    // Reject memos the chain would not accept before doing any work.
+    let memo_plaintext = request.memo.map(validate_memo_inner).transpose()?;
+
     let mut source_address_index: AddressIndex = request
         .source
         .clone()
@@ -207,6 +303,16 @@ pub async fn plan_transaction_inner<Db: Database>(
     let (mut change_address, _) = fvk
         .incoming()
         .payment_address(source_address_index.account.into());
+    if options.transparent_change {
+        if source_address_index.account != 0 {
+            return Err(anyhow!(
+                "account {} has no transparent address for change",
+                source_address_index.account
+            )
+            .into());
+        }
+        change_address = transparent_address(&fvk)?;
+    }
 
     let fmd_params: fmd::Parameters = storage
         .get_fmd_params()
@@ -259,7 +365,7 @@ pub async fn plan_transaction_inner<Db: Database>(
         let address = address
             .ok_or_else(|| anyhow!("missing address in output"))?
             .try_into()?;
-        let output = OutputPlan::new(&mut OsRng, value, address);
+        let output = OutputPlan::new(rng, value, address);
 
         actions_list.push(output);
     }
@@ -311,14 +417,14 @@ pub async fn plan_transaction_inner<Db: Database>(
 
         // Create the `SwapPlaintext` representing the swap to be performed:
         let swap_plaintext = SwapPlaintext::new(
-            &mut OsRng,
+            rng,
             trading_pair,
             delta_1,
             delta_2,
             estimated_claim_fee,
             claim_address,
         );
-        let swap = SwapPlan::new(&mut OsRng, swap_plaintext);
+        let swap = SwapPlan::new(rng, swap_plaintext);
 
         actions_list.push(swap);
     }
@@ -338,8 +444,8 @@ pub async fn plan_transaction_inner<Db: Database>(
             position: swap_record.position,
             output_data: swap_record.output_data,
             epoch_duration: sct_params.epoch_duration,
-            proof_blinding_r: Fq::rand(&mut OsRng),
-            proof_blinding_s: Fq::rand(&mut OsRng),
+            proof_blinding_r: Fq::rand(rng),
+            proof_blinding_s: Fq::rand(rng),
         };
 
         actions_list.push(swap_claim);
@@ -395,9 +501,9 @@ pub async fn plan_transaction_inner<Db: Database>(
             unbonding_start_height,
             penalty,
             unbonding_amount,
-            balance_blinding: Fr::rand(&mut OsRng),
-            proof_blinding_r: Fq::rand(&mut OsRng),
-            proof_blinding_s: Fq::rand(&mut OsRng),
+            balance_blinding: Fr::rand(rng),
+            proof_blinding_r: Fq::rand(rng),
+            proof_blinding_s: Fq::rand(rng),
         };
 
         actions_list.push(ActionPlan::UndelegateClaim(undelegate_claim_plan));
@@ -408,13 +514,31 @@ pub async fn plan_transaction_inner<Db: Database>(
         return Err(anyhow!("IbcRelay not yet implemented").into());
     }
 
-    for ics20_withdrawal in request.ics20_withdrawals {
+    for mut ics20_withdrawal in request.ics20_withdrawals {
+        // Withdrawals that show the counterparty chain a transparent address must also
+        // return to it if they time out, so the wallet's own t-addr is filled in. A caller's
+        // return address is never replaced silently.
+        if ics20_withdrawal.use_transparent_address {
+            let transparent = transparent_address(&fvk)?;
+            let return_address: Option<Address> = ics20_withdrawal
+                .return_address
+                .clone()
+                .map(TryInto::try_into)
+                .transpose()?;
+            if return_address.is_some_and(|address| address != transparent) {
+                return Err(anyhow!(
+                    "ICS-20 withdrawal uses the transparent address but returns to another address"
+                )
+                .into());
+            }
+            ics20_withdrawal.return_address = Some(transparent.into());
+        }
         actions_list.push(ActionPlan::Ics20Withdrawal(ics20_withdrawal.try_into()?));
     }
 
     // Generate a shared bundle_identifier for positions actions. The Relationship here is
     // that all positions with the same `bundle_id` must share the same `strategy_tag`.
-    let bundle_identifier = NonZeroU32::new(OsRng.next_u32()).expect("randomizer for identifier");
+    let bundle_identifier = NonZeroU32::new(rng.next_u32()).expect("randomizer for identifier");
 
     // Note: during action creation, a convenience method is invoked that generates the `PositionMetadatakey`
     // derived from the outgoing viewing key (OVK), and encrypt the plaintext `PositionMetadata`
@@ -489,7 +613,7 @@ pub async fn plan_transaction_inner<Db: Database>(
             .ok_or_else(|| anyhow!("missing max output in Dutch auction schedule action"))?
             .try_into()?;
         let mut nonce = [0u8; 32];
-        OsRng.fill_bytes(&mut nonce);
+        rng.fill_bytes(&mut nonce);
 
         let description = DutchAuctionDescription {
             start_height: description.start_height,
@@ -590,7 +714,7 @@ pub async fn plan_transaction_inner<Db: Database>(
                 .ok_or_else(|| anyhow::anyhow!("missing vote param"))?;
 
             let vote_plan = DelegatorVotePlan::new(
-                &mut OsRng,
+                rng,
                 proposal,
                 start_position.into(),
                 domain_vote,
@@ -653,7 +777,7 @@ pub async fn plan_transaction_inner<Db: Database>(
             // Filter out zero-valued notes from spendable note record (SNR) set.
             if record.clone().note.amount() != 0u64.into() {
                 let spend: SpendPlan =
-                    SpendPlan::new(&mut OsRng, record.clone().note, record.clone().position);
+                    SpendPlan::new(rng, record.clone().note, record.clone().position);
                 actions_list.push(spend);
             }
         }
@@ -709,7 +833,7 @@ pub async fn plan_transaction_inner<Db: Database>(
             let start_position: Position = Position::from((epoch_index as u16, 0, 0));
 
             let vote_plan = ActionLiquidityTournamentVotePlan::new(
-                &mut OsRng,
+                rng,
                 incentivized,
                 rewards_recipient,
                 note.clone(),
@@ -733,7 +857,27 @@ pub async fn plan_transaction_inner<Db: Database>(
             //     optionally spend the notes during voting, consolidating your voting power into a single, unlinkable
             //     note—allowing future votes with fewer actions.
             actions_list.push(vote_plan);
-            actions_list.push(SpendPlan::new(&mut OsRng, note, note_position));
+            actions_list.push(SpendPlan::new(rng, note, note_position));
+        }
+    }
+
+    // A memo can only be sent alongside an output, so a memo-only send carries it in an empty
+    // output to the memo's return address, which must be the wallet's own. Spend requests send
+    // their change to the recipient, which already carries the memo.
+    if let Some(memo_plaintext) = &memo_plaintext {
+        if !actions_list.requires_memo() && request.spends.is_empty() {
+            let return_address = memo_plaintext.return_address();
+            if transparent::address_index(&fvk, &return_address).is_none() {
+                return Err(anyhow!(
+                    "memo-only send has a return address that does not belong to the wallet"
+                )
+                .into());
+            }
+            let value = Value {
+                amount: Amount::zero(),
+                asset_id: fee_asset_id,
+            };
+            actions_list.push(OutputPlan::new(rng, value, return_address));
         }
     }
 
@@ -745,7 +889,7 @@ pub async fn plan_transaction_inner<Db: Database>(
     // don't accidentally try to use the same one twice.
 
     // Compute an initial fee estimate based on the actions we have so far.
-    actions_list.refresh_fee_and_change(OsRng, &gas_prices, &fee_tier, &change_address);
+    actions_list.refresh_fee_and_change(&mut *rng, &gas_prices, &fee_tier, &change_address);
 
     for required in actions_list.balance_with_fee().required() {
         // Find all the notes of this asset in the source account.
@@ -777,10 +921,10 @@ pub async fn plan_transaction_inner<Db: Database>(
         }?;
 
         // Add a spend for that note to the action list.
-        actions_list.push(SpendPlan::new(&mut OsRng, note.note, note.position));
+        actions_list.push(SpendPlan::new(rng, note.note, note.position));
 
         // Refresh the fee estimate and change outputs.
-        actions_list.refresh_fee_and_change(OsRng, &gas_prices, &fee_tier, &change_address);
+        actions_list.refresh_fee_and_change(&mut *rng, &gas_prices, &fee_tier, &change_address);
 
         iterations += 1;
         if iterations > 100 {
@@ -789,20 +933,34 @@ pub async fn plan_transaction_inner<Db: Database>(
     }
 
     // Add memo to the transaction plan.
-    let memo = if let Some(pb_memo_plaintext) = request.memo {
-        Some(MemoPlan::new(&mut OsRng, pb_memo_plaintext.try_into()?))
+    let memo = if let Some(memo_plaintext) = memo_plaintext {
+        Some(MemoPlan::new(rng, memo_plaintext))
     } else if actions_list.requires_memo() {
         // If a memo was not provided, but is required (because we have outputs),
         // auto-create one with the change address.
         let plaintext = MemoPlaintext::new(change_address, String::new())?;
-        Some(MemoPlan::new(&mut OsRng, plaintext))
+        Some(MemoPlan::new(rng, plaintext))
     } else {
         None
     };
 
-    // Reset the planner in case it were reused.
-    let plan =
-        mem::take(&mut actions_list).into_plan(OsRng, &fmd_params, transaction_parameters, memo)?;
+    // The change outputs are indistinguishable from the other outputs once the plan is sorted.
+    let change_commitments = actions_list
+        .change_outputs()
+        .values()
+        .map(|output| output.output_note().commit())
+        .collect();
 
-    Ok(plan)
+    // Reset the planner in case it were reused.
+    let plan = mem::take(&mut actions_list).into_plan(
+        &mut *rng,
+        &fmd_params,
+        transaction_parameters,
+        memo,
+    )?;
+
+    Ok(PlannedTransaction {
+        plan,
+        change_commitments,
+    })
 }
@@ -0,0 +1,79 @@
+//! Randomness for planning, authorization and witnessing.
+//!
+//! The `*_inner` functions take the RNG as an argument; the wasm exports pass [`WalletRng`],
+//! which draws from the OS. Builds with the `seeded-rng` feature can instead seed it with
+//! `set_rng_seed`, so that plans and authorizations are reproducible byte for byte.
+//!
+//! Building takes no RNG: proofs are blinded with factors drawn into the plan, spend and vote
+//! signatures come from the authorization, and the binding signature is deterministic.
+
+use rand_core::{CryptoRng, OsRng, RngCore};
+
+#[cfg(feature = "seeded-rng")]
+use {
+    crate::error::WasmResult, anyhow::anyhow, rand_chacha::ChaCha20Rng, rand_core::SeedableRng,
+    std::cell::RefCell, wasm_bindgen::prelude::wasm_bindgen,
+};
+
+/// The RNG used by the wasm exports.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WalletRng;
+
+impl RngCore for WalletRng {
+    fn next_u32(&mut self) -> u32 {
+        with_rng(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        with_rng(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        with_rng(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        with_rng(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+impl CryptoRng for WalletRng {}
+
+#[cfg(feature = "seeded-rng")]
+thread_local! {
+    static SEEDED_RNG: RefCell<Option<ChaCha20Rng>> = const { RefCell::new(None) };
+}
+
+#[cfg(feature = "seeded-rng")]
+fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    SEEDED_RNG.with(|cell| match cell.borrow_mut().as_mut() {
+        Some(rng) => f(rng),
+        None => f(&mut OsRng),
+    })
+}
+
+#[cfg(not(feature = "seeded-rng"))]
+fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    f(&mut OsRng)
+}
+
+/// Makes the wasm exports draw their randomness from a ChaCha20 stream with the given seed.
+/// Only for tests and debugging: seeded plans and signatures are predictable.
+/// Arguments:
+///     seed: `Uint8Array` of 32 bytes
+#[cfg(feature = "seeded-rng")]
+#[wasm_bindgen]
+pub fn set_rng_seed(seed: &[u8]) -> WasmResult<()> {
+    let seed: [u8; 32] = seed
+        .try_into()
+        .map_err(|_| anyhow!("RNG seed must be 32 bytes, got {}", seed.len()))?;
+    SEEDED_RNG.with(|cell| *cell.borrow_mut() = Some(ChaCha20Rng::from_seed(seed)));
+    Ok(())
+}
+
+/// Returns the wasm exports to drawing randomness from the OS.
+#[cfg(feature = "seeded-rng")]
+#[wasm_bindgen]
+pub fn clear_rng_seed() {
+    SEEDED_RNG.with(|cell| *cell.borrow_mut() = None);
+}
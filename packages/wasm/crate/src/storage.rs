@@ -7,7 +7,7 @@ use indexed_db_futures::IdbDatabase;
 use penumbra_asset::asset::{Id, Metadata};
 use penumbra_auction::auction::AuctionId;
 use penumbra_fee::GasPrices;
-use penumbra_keys::keys::AddressIndex;
+use penumbra_keys::keys::{AddressIndex, WalletId};
 use penumbra_num::Amount;
 use penumbra_proto::core::keys;
 use penumbra_proto::{
@@ -19,12 +19,16 @@ use penumbra_proto::{
 use penumbra_sct::Nullifier;
 use penumbra_shielded_pool::{fmd, note, Note};
 use penumbra_stake::{DelegationToken, IdentityKey};
+use penumbra_tct::storage::StoredPosition;
+use penumbra_tct::Forgotten;
 use serde::{Deserialize, Serialize};
 
 use crate::database::indexed_db::open_idb_database;
 use crate::database::interface::Database;
 use crate::error::{WasmError, WasmResult};
+use crate::invoice::Invoice;
 use crate::note_record::SpendableNoteRecord;
+use crate::view_server::StoredTree;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DbConstants {
@@ -51,6 +55,8 @@ pub struct Tables {
     pub tree_hashes: String,
     pub tree_last_position: String,
     pub tree_last_forgotten: String,
+    pub address_labels: String,
+    pub invoices: String,
 }
 
 pub async fn init_idb_storage(constants: DbConstants) -> WasmResult<Storage<IdbDatabase>> {
@@ -158,6 +164,11 @@ impl<Db: Database> Storage<Db> {
         Ok(result)
     }
 
+    pub async fn save_note(&self, note: &SpendableNoteRecord) -> WasmResult<()> {
+        self.db.put(&self.tables.spendable_notes, note).await?;
+        Ok(())
+    }
+
     pub async fn get_note_by_nullifier(
         &self,
         nullifier: &Nullifier,
@@ -226,6 +237,32 @@ impl<Db: Database> Storage<Db> {
         Ok(result)
     }
 
+    pub async fn get_tree_last_position(&self) -> WasmResult<Option<StoredPosition>> {
+        let result = self
+            .db
+            .get(&self.tables.tree_last_position, "last_position")
+            .await?;
+        Ok(result)
+    }
+
+    pub async fn get_tree_last_forgotten(&self) -> WasmResult<Option<Forgotten>> {
+        let result = self
+            .db
+            .get(&self.tables.tree_last_forgotten, "last_forgotten")
+            .await?;
+        Ok(result)
+    }
+
+    /// Reads every stored SCT row, as `load_tree` expects them.
+    pub async fn get_stored_tree(&self) -> WasmResult<StoredTree> {
+        Ok(StoredTree {
+            last_position: self.get_tree_last_position().await?,
+            last_forgotten: self.get_tree_last_forgotten().await?,
+            hashes: self.db.get_all(&self.tables.tree_hashes).await?,
+            commitments: self.db.get_all(&self.tables.tree_commitments).await?,
+        })
+    }
+
     pub async fn get_transaction_infos(&self) -> WasmResult<Vec<TransactionInfo>> {
         let all_txs = self
             .db
@@ -247,6 +284,53 @@ impl<Db: Database> Storage<Db> {
         result.ok_or_else(|| WasmError::Anyhow(anyhow!("could not find reserves")))
     }
 
+    pub async fn get_address_label(
+        &self,
+        target: &LabelTarget,
+    ) -> WasmResult<Option<AddressLabel>> {
+        let result = self
+            .db
+            .get(&self.tables.address_labels, target.key())
+            .await?;
+        Ok(result)
+    }
+
+    pub async fn get_address_labels(&self) -> WasmResult<Vec<AddressLabel>> {
+        let result = self.db.get_all(&self.tables.address_labels).await?;
+        Ok(result)
+    }
+
+    /// Names an account or a recipient address, replacing any earlier label for it.
+    pub async fn set_address_label(&self, label: &AddressLabel) -> WasmResult<()> {
+        self.db
+            .put_with_key(&self.tables.address_labels, label.target.key(), label)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_invoice(
+        &self,
+        wallet_id: &WalletId,
+        address_index: &AddressIndex,
+    ) -> WasmResult<Option<Invoice>> {
+        let key = invoice_key(wallet_id, address_index);
+        let result = self.db.get(&self.tables.invoices, key).await?;
+        Ok(result)
+    }
+
+    pub async fn get_invoices(&self) -> WasmResult<Vec<Invoice>> {
+        let result = self.db.get_all(&self.tables.invoices).await?;
+        Ok(result)
+    }
+
+    pub async fn save_invoice(&self, invoice: &Invoice) -> WasmResult<()> {
+        let key = invoice_key(&invoice.wallet_id, &invoice.address_index);
+        self.db
+            .put_with_key(&self.tables.invoices, key, invoice)
+            .await?;
+        Ok(())
+    }
+
     pub async fn get_delegation_assets(&self) -> WasmResult<BTreeMap<Id, DelegationToken>> {
         let all_metadata = self.db.get_all::<Metadata>(&self.tables.assets).await?;
 
@@ -304,6 +388,39 @@ pub fn byte_array_to_base64(byte_array: &Vec<u8>) -> String {
     base64::Engine::encode(&base64::engine::general_purpose::STANDARD, byte_array)
 }
 
+/// Invoices are looked up by the index of the address a note was received on.
+pub fn invoice_key(wallet_id: &WalletId, address_index: &AddressIndex) -> String {
+    format!(
+        "{wallet_id}/{}/{}",
+        address_index.account,
+        hex::encode(address_index.randomizer)
+    )
+}
+
+/// What an address book entry names: one of the wallet's accounts, or any address by its
+/// bech32m encoding.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LabelTarget {
+    Account(u32),
+    Address(String),
+}
+
+impl LabelTarget {
+    fn key(&self) -> String {
+        match self {
+            LabelTarget::Account(account) => format!("account/{account}"),
+            LabelTarget::Address(address) => format!("address/{address}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressLabel {
+    pub target: LabelTarget,
+    pub label: String,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct OutstandingReserves {
     pub input: Value,
@@ -0,0 +1,51 @@
+//! The wallet's transparent address (t-addr).
+//!
+//! The t-addr is the address of account 0 with a zero diversifier and a zero clue key, so that
+//! it fits in the 32-byte addresses of chains that cannot take a full Penumbra address. It is
+//! not derived from an `AddressIndex`: `index_for_diversifier` maps its zero diversifier to a
+//! meaningless index. The helpers here attribute it to account 0 instead.
+
+use penumbra_keys::keys::AddressIndex;
+use penumbra_keys::{Address, AddressView, FullViewingKey};
+
+use crate::error::WasmResult;
+
+/// The transparent address of the wallet.
+pub fn transparent_address(fvk: &FullViewingKey) -> WasmResult<Address> {
+    let encoding = fvk.incoming().transparent_address();
+    let address: Address = encoding.parse()?;
+    Ok(address)
+}
+
+/// Whether `address` is the transparent address of the wallet.
+pub fn is_transparent_address(fvk: &FullViewingKey, address: &Address) -> bool {
+    transparent_address(fvk).is_ok_and(|transparent| &transparent == address)
+}
+
+/// Like `FullViewingKey::address_index`, but attributes the transparent address to account 0.
+pub fn address_index(fvk: &FullViewingKey, address: &Address) -> Option<AddressIndex> {
+    if is_transparent_address(fvk, address) {
+        return Some(AddressIndex::new(0));
+    }
+    fvk.address_index(address)
+}
+
+/// The address index of a note received by the wallet, as recorded in its `SpendableNoteRecord`.
+pub fn note_address_index(fvk: &FullViewingKey, address: &Address) -> AddressIndex {
+    if is_transparent_address(fvk, address) {
+        return AddressIndex::new(0);
+    }
+    fvk.incoming().index_for_diversifier(address.diversifier())
+}
+
+/// Like `FullViewingKey::view_address`, but decodes the transparent address as account 0.
+pub fn view_address(fvk: &FullViewingKey, address: Address) -> AddressView {
+    if is_transparent_address(fvk, &address) {
+        return AddressView::Decoded {
+            address,
+            index: AddressIndex::new(0),
+            wallet_id: fvk.wallet_id(),
+        };
+    }
+    fvk.view_address(address)
+}
@@ -1,33 +1,49 @@
+use crate::database::interface::Database;
+use crate::dex::position_metadata_key;
 use crate::error::{WasmError, WasmResult};
+use crate::metadata::customize_symbol_inner;
+use crate::note_record::SpendableNoteRecord;
+use crate::rng::WalletRng;
 use crate::storage::Storage;
 use crate::storage::{init_idb_storage, DbConstants};
+use crate::transparent;
 use crate::utils;
 use crate::view_server::{load_tree, StoredTree};
-use anyhow::anyhow;
-use indexed_db_futures::IdbDatabase;
+use js_sys::Uint8Array;
+use penumbra_asset::asset::{Id, Metadata};
+use penumbra_asset::STAKING_TOKEN_ASSET_ID;
 use penumbra_auction::auction::dutch::actions::view::{
     ActionDutchAuctionScheduleView, ActionDutchAuctionWithdrawView,
 };
+use penumbra_auction::auction::AuctionNft;
+use penumbra_dex::lp::position::State;
+use penumbra_dex::lp::LpNft;
 use penumbra_dex::BatchSwapOutputData;
 use penumbra_dex::{swap::SwapView, swap_claim::SwapClaimView};
 use penumbra_keys::keys::SpendKey;
 use penumbra_keys::FullViewingKey;
-use penumbra_proto::core::transaction::v1::{TransactionPerspective, TransactionView};
+use penumbra_proto::core::transaction::v1 as pb;
+use penumbra_proto::view::v1::{SwapRecord, TransactionInfo};
 use penumbra_proto::DomainType;
 use penumbra_sct::{CommitmentSource, Nullifier};
-use penumbra_tct::{Position, Proof, StateCommitment};
+use penumbra_shielded_pool::note;
+use penumbra_stake::{DelegationToken, UnbondingToken};
+use penumbra_tct::storage::StoredPosition;
+use penumbra_tct::{Forgotten, Position, Proof, StateCommitment, Tree};
 use penumbra_transaction::plan::TransactionPlan;
 use penumbra_transaction::txhash::TransactionId;
 use penumbra_transaction::view::action_view::{
     ActionView, DelegatorVoteView, OutputView, SpendView,
 };
 use penumbra_transaction::Action;
-use penumbra_transaction::TransactionView as TransactionViewComponent;
-use penumbra_transaction::{AuthorizationData, Transaction, WitnessData};
+use penumbra_transaction::{
+    AuthorizationData, Transaction, TransactionPerspective, TransactionView, WitnessData,
+};
 use prost::Message;
-use rand_core::OsRng;
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::{BTreeMap, BTreeSet};
-use std::convert::TryInto;
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::JsValue;
 
@@ -43,10 +59,178 @@ pub fn authorize(spend_key: &[u8], transaction_plan: &[u8]) -> WasmResult<Vec<u8
     let spend_key: SpendKey = SpendKey::decode(spend_key)?;
     let plan = TransactionPlan::decode(transaction_plan)?;
 
-    let auth_data: AuthorizationData = plan.authorize(OsRng, &spend_key)?;
+    let auth_data = authorize_inner(&spend_key, &plan, WalletRng)?;
+    Ok(auth_data.encode_to_vec())
+}
+
+pub fn authorize_inner<R: RngCore + CryptoRng>(
+    spend_key: &SpendKey,
+    plan: &TransactionPlan,
+    rng: R,
+) -> WasmResult<AuthorizationData> {
+    let auth_data: AuthorizationData = plan.authorize(rng, spend_key)?;
+    Ok(auth_data)
+}
+
+/// The data an external signer needs to authorize a transaction plan. Spend, delegator vote and
+/// liquidity tournament vote signatures are made over the effect hash, each with the spend
+/// authorization key randomized by the corresponding randomizer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningPayload {
+    /// Hex-encoded effect hash.
+    pub effect_hash: String,
+    /// Hex-encoded randomizers, in the order of the plan's actions.
+    pub spend_randomizers: Vec<String>,
+    pub delegator_vote_randomizers: Vec<String>,
+    pub lqt_vote_randomizers: Vec<String>,
+}
+
+/// Hex-encoded signatures produced by an external signer for a [`SigningPayload`]. The binding
+/// signature is not included: it is made during the build from the plan's blinding factors and
+/// does not need the spend key.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalSignatures {
+    pub spend_auths: Vec<String>,
+    #[serde(default)]
+    pub delegator_vote_auths: Vec<String>,
+    #[serde(default)]
+    pub lqt_vote_auths: Vec<String>,
+}
+
+/// Get the payload an external signer must sign to authorize a transaction
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     transaction_plan: `pb::TransactionPlan`
+/// Returns: `SigningPayload`
+#[wasm_bindgen]
+pub fn authorization_payload(
+    full_viewing_key: &[u8],
+    transaction_plan: &[u8],
+) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let fvk = FullViewingKey::decode(full_viewing_key)?;
+    let plan = TransactionPlan::decode(transaction_plan)?;
+
+    let payload = authorization_payload_inner(&fvk, &plan)?;
+
+    let result = serde_wasm_bindgen::to_value(&payload)?;
+    Ok(result)
+}
+
+pub fn authorization_payload_inner(
+    fvk: &FullViewingKey,
+    plan: &TransactionPlan,
+) -> WasmResult<SigningPayload> {
+    let effect_hash = plan.effect_hash(fvk)?;
+
+    Ok(SigningPayload {
+        effect_hash: hex::encode(effect_hash.as_ref()),
+        spend_randomizers: plan
+            .spend_plans()
+            .map(|plan| hex::encode(plan.randomizer.to_bytes()))
+            .collect(),
+        delegator_vote_randomizers: plan
+            .delegator_vote_plans()
+            .map(|plan| hex::encode(plan.randomizer.to_bytes()))
+            .collect(),
+        lqt_vote_randomizers: plan
+            .lqt_vote_plans()
+            .map(|plan| hex::encode(plan.randomizer.to_bytes()))
+            .collect(),
+    })
+}
+
+/// Assemble authorization data from externally produced signatures. Every signature is checked
+/// against the full viewing key before it is accepted.
+/// Arguments:
+///     full_viewing_key: `byte representation inner FullViewingKey`
+///     transaction_plan: `pb::TransactionPlan`
+///     signatures: `ExternalSignatures`
+/// Returns: `pb::AuthorizationData`
+#[wasm_bindgen]
+pub fn assemble_authorization(
+    full_viewing_key: &[u8],
+    transaction_plan: &[u8],
+    signatures: JsValue,
+) -> WasmResult<Vec<u8>> {
+    utils::set_panic_hook();
+
+    let fvk = FullViewingKey::decode(full_viewing_key)?;
+    let plan = TransactionPlan::decode(transaction_plan)?;
+    let signatures: ExternalSignatures = serde_wasm_bindgen::from_value(signatures)?;
+
+    let auth_data = assemble_authorization_inner(&fvk, &plan, &signatures)?;
     Ok(auth_data.encode_to_vec())
 }
 
+pub fn assemble_authorization_inner(
+    fvk: &FullViewingKey,
+    plan: &TransactionPlan,
+    signatures: &ExternalSignatures,
+) -> WasmResult<AuthorizationData> {
+    let effect_hash = plan.effect_hash(fvk)?;
+    let ak = fvk.spend_verification_key();
+
+    let verify = |kind: &'static str,
+                  randomizers: Vec<&decaf377::Fr>,
+                  signatures: &[String]|
+     -> WasmResult<Vec<_>> {
+        if randomizers.len() != signatures.len() {
+            return Err(WasmError::AuthorizationCount {
+                kind,
+                expected: randomizers.len(),
+                actual: signatures.len(),
+            });
+        }
+
+        randomizers
+            .into_iter()
+            .zip(signatures)
+            .enumerate()
+            .map(|(index, (randomizer, signature))| -> WasmResult<_> {
+                let invalid = || WasmError::InvalidAuthorization { kind, index };
+                let bytes: [u8; 64] = hex::decode(signature)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(invalid)?;
+                let signature = bytes.into();
+                ak.randomize(randomizer)
+                    .verify(effect_hash.as_ref(), &signature)
+                    .map_err(|_| invalid())?;
+                Ok(signature)
+            })
+            .collect()
+    };
+
+    let spend_auths = verify(
+        "spend",
+        plan.spend_plans().map(|plan| &plan.randomizer).collect(),
+        &signatures.spend_auths,
+    )?;
+    let delegator_vote_auths = verify(
+        "delegator vote",
+        plan.delegator_vote_plans()
+            .map(|plan| &plan.randomizer)
+            .collect(),
+        &signatures.delegator_vote_auths,
+    )?;
+    let lqt_vote_auths = verify(
+        "liquidity tournament vote",
+        plan.lqt_vote_plans().map(|plan| &plan.randomizer).collect(),
+        &signatures.lqt_vote_auths,
+    )?;
+
+    Ok(AuthorizationData {
+        effect_hash: Some(effect_hash),
+        spend_auths,
+        delegator_vote_auths,
+        lqt_vote_auths,
+    })
+}
+
 /// Get witness data
 /// Obtaining witness data is directly related to SCT so we need to pass the tree data
 /// Arguments:
@@ -66,8 +250,110 @@ pub fn witness(transaction_plan: &[u8], stored_tree: JsValue) -> WasmResult<Vec<
 }
 
 pub fn witness_inner(plan: TransactionPlan, stored_tree: StoredTree) -> WasmResult<WitnessData> {
+    witness_with_rng(plan, stored_tree, &mut WalletRng)
+}
+
+/// Like `witness_inner`, with the dummy proofs for zero-value spends drawn from `rng`.
+pub fn witness_with_rng<R: RngCore + CryptoRng>(
+    plan: TransactionPlan,
+    stored_tree: StoredTree,
+    rng: &mut R,
+) -> WasmResult<WitnessData> {
     let sct = load_tree(stored_tree);
+    witness_from_tree(&plan, &sct, rng)
+}
+
+/// Get witness data, reading the SCT from storage
+/// The anchor is the root of the whole tree, and storage only keeps the hashes the tree needs to
+/// rebuild itself, so every row is loaded to compute the auth paths. The tree is then kept for
+/// as long as the database and its stored tree position are unchanged, so only the position rows
+/// are read when nothing was synced in between.
+/// Arguments:
+///     transaction_plan: `pb::TransactionPlan`
+///     idb_constants: `IndexedDbConstants`
+/// Returns: `pb::WitnessData`
+#[wasm_bindgen]
+pub async fn witness_from_storage(
+    transaction_plan: &[u8],
+    idb_constants: JsValue,
+) -> WasmResult<Vec<u8>> {
+    utils::set_panic_hook();
+
+    let plan = TransactionPlan::decode(transaction_plan)?;
+    let constants: DbConstants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let database = constants.name.clone();
+    let storage = init_idb_storage(constants).await?;
+
+    let witness_data = witness_from_storage_inner(&storage, &database, &plan).await?;
+
+    Ok(witness_data.encode_to_vec())
+}
+
+/// Frees the tree cached by `witness_from_storage`
+/// Should be called when the wallet is locked or removed, since the cache otherwise holds the
+/// wallet's whole SCT until a witness is requested from another database.
+#[wasm_bindgen]
+pub fn clear_witness_cache() {
+    utils::set_panic_hook();
 
+    TREE_CACHE.set(None);
+}
+
+/// A tree loaded from storage, with the database and position it was loaded at.
+struct CachedTree {
+    database: String,
+    last_position: Option<StoredPosition>,
+    last_forgotten: Option<Forgotten>,
+    sct: Tree,
+}
+
+thread_local! {
+    static TREE_CACHE: RefCell<Option<CachedTree>> = const { RefCell::new(None) };
+}
+
+/// Witnesses `plan` against the SCT in `storage`, reusing the cached tree if it was loaded from
+/// the same `database` at the same position. A tree loaded from another database replaces it.
+pub async fn witness_from_storage_inner<Db: Database>(
+    storage: &Storage<Db>,
+    database: &str,
+    plan: &TransactionPlan,
+) -> WasmResult<WitnessData> {
+    let last_position = storage.get_tree_last_position().await?;
+    let last_forgotten = storage.get_tree_last_forgotten().await?;
+
+    let cached = TREE_CACHE.with_borrow(|cache| match cache {
+        Some(cached)
+            if cached.database == database
+                && cached.last_position == last_position
+                && cached.last_forgotten == last_forgotten =>
+        {
+            Some(witness_from_tree(plan, &cached.sct, &mut WalletRng))
+        }
+        _ => None,
+    });
+    if let Some(witness_data) = cached {
+        return witness_data;
+    }
+
+    // Drop the stale tree before loading the new one, so both are never held at once.
+    TREE_CACHE.set(None);
+    let sct = load_tree(storage.get_stored_tree().await?);
+    let witness_data = witness_from_tree(plan, &sct, &mut WalletRng);
+    TREE_CACHE.set(Some(CachedTree {
+        database: database.to_string(),
+        last_position,
+        last_forgotten,
+        sct,
+    }));
+
+    witness_data
+}
+
+fn witness_from_tree<R: RngCore + CryptoRng>(
+    plan: &TransactionPlan,
+    sct: &Tree,
+    rng: &mut R,
+) -> WasmResult<WitnessData> {
     let note_commitments: Vec<StateCommitment> = plan
         .spend_plans()
         .filter(|plan| plan.note.amount() != 0u64.into())
@@ -91,14 +377,8 @@ pub fn witness_inner(plan: TransactionPlan, stored_tree: StoredTree) -> WasmResu
     // Obtain an auth path for each requested note commitment
     let auth_paths = note_commitments
         .iter()
-        .map(|nc| {
-            sct.witness(*nc)
-                .ok_or(anyhow!("note commitment is in the SCT"))
-        })
-        .collect::<Result<Vec<Proof>, anyhow::Error>>()?;
-
-    // Release the read lock on the SCT
-    drop(sct);
+        .map(|nc| sct.witness(*nc).ok_or(WasmError::CommitmentNotInSct(*nc)))
+        .collect::<WasmResult<Vec<Proof>>>()?;
 
     // Construct witness data
     let mut witness_data = WitnessData {
@@ -116,7 +396,7 @@ pub fn witness_inner(plan: TransactionPlan, stored_tree: StoredTree) -> WasmResu
         .filter(|plan| plan.note.amount() == 0u64.into())
         .map(|plan| plan.note.commit())
     {
-        witness_data.add_proof(nc, Proof::dummy(&mut OsRng, nc));
+        witness_data.add_proof(nc, Proof::dummy(rng, nc));
     }
 
     Ok(witness_data)
@@ -126,6 +406,8 @@ pub fn witness_inner(plan: TransactionPlan, stored_tree: StoredTree) -> WasmResu
 pub struct TxpAndTxvBytes {
     pub txp: Vec<u8>,
     pub txv: Vec<u8>,
+    /// `PerspectiveWarning[]` describing data that was left out of the perspective
+    pub warnings: JsValue,
 }
 
 /// Get transaction perspective, transaction view
@@ -133,7 +415,7 @@ pub struct TxpAndTxvBytes {
 ///     full_viewing_key: `FullViewingKey` inner bytes
 ///     tx: Binary-encoded `Transaction` message
 ///     idb_constants: IndexedDbConstants
-/// Returns: `{ txp: Uint8Array, txv: Uint8Array }` representing binary-encoded `TransactionPerspective` and `TransactionView`
+/// Returns: `{ txp: Uint8Array, txv: Uint8Array, warnings: PerspectiveWarning[] }` representing binary-encoded `TransactionPerspective` and `TransactionView`
 #[wasm_bindgen]
 pub async fn transaction_perspective_and_view(
     full_viewing_key: &[u8],
@@ -145,24 +427,203 @@ pub async fn transaction_perspective_and_view(
     let transaction = Transaction::decode(tx)?;
     let constants = serde_wasm_bindgen::from_value(idb_constants)?;
     let fvk = FullViewingKey::decode(full_viewing_key)?;
-    let (txp, txv) = transaction_info_inner(fvk, transaction, constants).await?;
+    let storage = init_idb_storage(constants).await?;
+    let info = transaction_info_inner(&storage, &fvk, &transaction).await?;
 
     Ok(TxpAndTxvBytes {
-        txp: txp.encode_to_vec(),
-        txv: txv.encode_to_vec(),
+        txp: pb::TransactionPerspective::from(info.txp).encode_to_vec(),
+        txv: info.txv.encode_to_vec(),
+        warnings: serde_wasm_bindgen::to_value(&info.warnings)?,
     })
 }
 
-async fn transaction_info_inner(
-    fvk: FullViewingKey,
-    tx: Transaction,
-    idb_constants: DbConstants,
-) -> Result<(TransactionPerspective, TransactionView), WasmError> {
-    let storage = init_idb_storage(idb_constants).await?;
+/// Get transaction perspectives and views for many transactions at once
+/// Storage is opened once, and asset metadata, notes and transaction history are
+/// looked up once for the whole batch, which makes this much faster than calling
+/// `transaction_perspective_and_view` per transaction when syncing history.
+/// Arguments:
+///     full_viewing_key: `FullViewingKey` inner bytes
+///     txs: `Uint8Array[]` of binary-encoded `Transaction` messages
+///     idb_constants: IndexedDbConstants
+/// Returns: `{ txp: Uint8Array, txv: Uint8Array, warnings: PerspectiveWarning[] }[]` in the order
+/// of `txs`, each as returned by `transaction_perspective_and_view`
+#[wasm_bindgen]
+pub async fn transaction_perspectives_and_views(
+    full_viewing_key: &[u8],
+    txs: Vec<Uint8Array>,
+    idb_constants: JsValue,
+) -> WasmResult<Vec<TxpAndTxvBytes>> {
+    utils::set_panic_hook();
+
+    let txs = txs
+        .iter()
+        .map(|tx| Transaction::decode(tx.to_vec().as_slice()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+    let fvk = FullViewingKey::decode(full_viewing_key)?;
+    let storage = init_idb_storage(constants).await?;
+
+    transaction_infos_inner(&storage, &fvk, &txs)
+        .await?
+        .into_iter()
+        .map(|info| {
+            Ok(TxpAndTxvBytes {
+                txp: pb::TransactionPerspective::from(info.txp).encode_to_vec(),
+                txv: info.txv.encode_to_vec(),
+                warnings: serde_wasm_bindgen::to_value(&info.warnings)?,
+            })
+        })
+        .collect()
+}
+
+/// Data the perspective builder left out because it is not known locally or could not be read.
+/// The perspective is still usable; the affected parts of the view are shown as opaque.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PerspectiveWarning {
+    /// The record of the swap being claimed has a commitment source that could not be parsed.
+    InvalidSwapSource {
+        nullifier: Nullifier,
+        reason: String,
+    },
+    /// An output of a swap claim is not in storage, although the claim is ours.
+    UnknownSwapClaimOutput { commitment: note::StateCommitment },
+    /// The memo could not be decrypted, so its return address is not shown.
+    UnreadableMemo { reason: String },
+}
+
+/// The perspective of a wallet on a transaction, the view it yields, and anything that was
+/// skipped while building it.
+#[derive(Clone, Debug)]
+pub struct PerspectiveAndView {
+    pub txp: TransactionPerspective,
+    pub txv: TransactionView,
+    pub warnings: Vec<PerspectiveWarning>,
+}
+
+/// Storage lookups shared by the transactions of a batch.
+#[derive(Default)]
+pub struct PerspectiveCache {
+    assets: BTreeMap<Id, Option<Metadata>>,
+    notes: BTreeMap<note::StateCommitment, Option<SpendableNoteRecord>>,
+    notes_by_nullifier: BTreeMap<Nullifier, Option<SpendableNoteRecord>>,
+    swaps: BTreeMap<StateCommitment, Option<SwapRecord>>,
+    swaps_by_nullifier: BTreeMap<Nullifier, Option<SwapRecord>>,
+    transaction_infos: Option<Vec<TransactionInfo>>,
+}
+
+impl PerspectiveCache {
+    async fn get_asset<Db: Database>(
+        &mut self,
+        storage: &Storage<Db>,
+        id: &Id,
+    ) -> WasmResult<Option<Metadata>> {
+        if let Some(asset) = self.assets.get(id) {
+            return Ok(asset.clone());
+        }
+        let asset = storage.get_asset(id).await?;
+        self.assets.insert(*id, asset.clone());
+        Ok(asset)
+    }
+
+    async fn get_note<Db: Database>(
+        &mut self,
+        storage: &Storage<Db>,
+        commitment: &note::StateCommitment,
+    ) -> WasmResult<Option<SpendableNoteRecord>> {
+        if let Some(note) = self.notes.get(commitment) {
+            return Ok(note.clone());
+        }
+        let note = storage.get_note(commitment).await?;
+        self.notes.insert(*commitment, note.clone());
+        Ok(note)
+    }
+
+    async fn get_note_by_nullifier<Db: Database>(
+        &mut self,
+        storage: &Storage<Db>,
+        nullifier: &Nullifier,
+    ) -> WasmResult<Option<SpendableNoteRecord>> {
+        if let Some(note) = self.notes_by_nullifier.get(nullifier) {
+            return Ok(note.clone());
+        }
+        let note = storage.get_note_by_nullifier(nullifier).await?;
+        self.notes_by_nullifier.insert(*nullifier, note.clone());
+        Ok(note)
+    }
+
+    async fn get_swap<Db: Database>(
+        &mut self,
+        storage: &Storage<Db>,
+        commitment: &StateCommitment,
+    ) -> WasmResult<Option<SwapRecord>> {
+        if let Some(swap) = self.swaps.get(commitment) {
+            return Ok(swap.clone());
+        }
+        let swap = storage.get_swap_by_commitment((*commitment).into()).await?;
+        self.swaps.insert(*commitment, swap.clone());
+        Ok(swap)
+    }
+
+    async fn get_swap_by_nullifier<Db: Database>(
+        &mut self,
+        storage: &Storage<Db>,
+        nullifier: &Nullifier,
+    ) -> WasmResult<Option<SwapRecord>> {
+        if let Some(swap) = self.swaps_by_nullifier.get(nullifier) {
+            return Ok(swap.clone());
+        }
+        let swap = storage.get_swap_by_nullifier(nullifier).await?;
+        self.swaps_by_nullifier.insert(*nullifier, swap.clone());
+        Ok(swap)
+    }
+
+    async fn get_transaction_infos<Db: Database>(
+        &mut self,
+        storage: &Storage<Db>,
+    ) -> WasmResult<&[TransactionInfo]> {
+        if self.transaction_infos.is_none() {
+            self.transaction_infos = Some(storage.get_transaction_infos().await?);
+        }
+        Ok(self.transaction_infos.as_deref().unwrap_or_default())
+    }
+}
+
+/// Builds the perspectives and views for a batch of transactions, in order, sharing storage
+/// lookups between them.
+pub async fn transaction_infos_inner<Db: Database>(
+    storage: &Storage<Db>,
+    fvk: &FullViewingKey,
+    txs: &[Transaction],
+) -> WasmResult<Vec<PerspectiveAndView>> {
+    let mut cache = PerspectiveCache::default();
+    let mut infos = Vec::with_capacity(txs.len());
+    for tx in txs {
+        infos.push(transaction_info_cached(storage, &mut cache, fvk, tx).await?);
+    }
+    Ok(infos)
+}
+
+/// Builds the perspective of `fvk` on `tx` from the records in `storage`, and the view it yields.
+pub async fn transaction_info_inner<Db: Database>(
+    storage: &Storage<Db>,
+    fvk: &FullViewingKey,
+    tx: &Transaction,
+) -> WasmResult<PerspectiveAndView> {
+    transaction_info_cached(storage, &mut PerspectiveCache::default(), fvk, tx).await
+}
+
+async fn transaction_info_cached<Db: Database>(
+    storage: &Storage<Db>,
+    cache: &mut PerspectiveCache,
+    fvk: &FullViewingKey,
+    tx: &Transaction,
+) -> WasmResult<PerspectiveAndView> {
+    let mut warnings = Vec::new();
 
     // First, create a TxP with the payload keys visible to our FVK and no other data.
-    let mut txp = penumbra_transaction::TransactionPerspective {
-        payload_keys: tx.payload_keys(&fvk)?,
+    let mut txp = TransactionPerspective {
+        payload_keys: tx.payload_keys(fvk)?,
         transaction_id: tx.id(),
         ..Default::default()
     };
@@ -175,7 +636,7 @@ async fn transaction_info_inner(
                 let nullifier = spend.body.nullifier;
                 // An error here indicates we don't know the nullifier, so we omit it from the Perspective.
                 if let Some(spendable_note_record) =
-                    storage.get_note_by_nullifier(&nullifier).await?
+                    cache.get_note_by_nullifier(storage, &nullifier).await?
                 {
                     txp.spend_nullifiers
                         .insert(nullifier, spendable_note_record.note.clone());
@@ -183,8 +644,7 @@ async fn transaction_info_inner(
             }
             Action::Swap(swap) => {
                 let commitment = swap.body.payload.commitment;
-                if let Some(swap_record) = storage.get_swap_by_commitment(commitment.into()).await?
-                {
+                if let Some(swap_record) = cache.get_swap(storage, &commitment).await? {
                     // Add swap output to perspective
                     if let Some(output_data) = swap_record.output_data {
                         let bsod = BatchSwapOutputData::try_from(output_data)?;
@@ -194,8 +654,9 @@ async fn transaction_info_inner(
                     // Add swap claim to perspective
                     let swap_position = Position::from(swap_record.position);
                     add_swap_claim_txn_to_perspective(
-                        &storage,
-                        &fvk,
+                        storage,
+                        cache,
+                        fvk,
                         &mut txp,
                         &commitment,
                         swap_position,
@@ -206,51 +667,72 @@ async fn transaction_info_inner(
             Action::SwapClaim(claim) => {
                 let nullifier = claim.body.nullifier;
 
-                storage
-                    .get_swap_by_nullifier(&nullifier)
-                    .await?
-                    .and_then(|swap_record| swap_record.source)
-                    .into_iter()
-                    .try_for_each(|source| {
-                        let commitment_source: Result<CommitmentSource, anyhow::Error> =
-                            source.try_into();
-                        if let Some(id) = commitment_source.unwrap().id() {
-                            txp.creation_transaction_ids_by_nullifier
-                                .insert(nullifier, TransactionId(id));
+                let swap_record = cache.get_swap_by_nullifier(storage, &nullifier).await?;
+                if let Some(source) = swap_record
+                    .as_ref()
+                    .and_then(|record| record.source.clone())
+                {
+                    match CommitmentSource::try_from(source) {
+                        Ok(source) => {
+                            if let Some(id) = source.id() {
+                                txp.creation_transaction_ids_by_nullifier
+                                    .insert(nullifier, TransactionId(id));
+                            }
                         }
+                        Err(error) => warnings.push(PerspectiveWarning::InvalidSwapSource {
+                            nullifier,
+                            reason: error.to_string(),
+                        }),
+                    }
+                }
 
-                        Some(())
-                    });
-
-                let output_1_record = storage
-                    .get_note(&claim.body.output_1_commitment)
-                    .await?
-                    .ok_or(anyhow!(
-                        "Error generating TxP: SwapClaim output 1 commitment not found",
-                    ))?;
-
-                let output_2_record = storage
-                    .get_note(&claim.body.output_2_commitment)
-                    .await?
-                    .ok_or(anyhow!(
-                        "Error generating TxP: SwapClaim output 2 commitment not found"
-                    ))?;
-
-                txp.advice_notes
-                    .insert(claim.body.output_1_commitment, output_1_record.note.clone());
-                txp.advice_notes
-                    .insert(claim.body.output_2_commitment, output_2_record.note.clone());
+                let outputs = [
+                    claim.body.output_1_commitment,
+                    claim.body.output_2_commitment,
+                ];
+                let mut missing = Vec::new();
+                for commitment in outputs {
+                    match cache.get_note(storage, &commitment).await? {
+                        Some(record) => {
+                            txp.advice_notes.insert(commitment, record.note);
+                        }
+                        None => missing.push(commitment),
+                    }
+                }
+
+                // A claim we know nothing about belongs to someone else, so only a claim we
+                // partially know is worth a warning.
+                if swap_record.is_some() || missing.len() < outputs.len() {
+                    warnings.extend(missing.into_iter().map(|commitment| {
+                        PerspectiveWarning::UnknownSwapClaimOutput { commitment }
+                    }));
+                }
             }
             Action::DelegatorVote(v) => {
                 let nullifier = v.body.nullifier;
                 // An error here indicates we don't know the nullifier, so we omit it from the Perspective.
                 if let Some(spendable_note_record) =
-                    storage.get_note_by_nullifier(&nullifier).await?
+                    cache.get_note_by_nullifier(storage, &nullifier).await?
                 {
                     txp.spend_nullifiers
                         .insert(nullifier, spendable_note_record.note.clone());
                 }
             }
+            Action::ActionLiquidityTournamentVote(v) => {
+                let nullifier = v.body.nullifier;
+                // As with delegator votes, the staked note is only known if it is ours.
+                if let Some(spendable_note_record) =
+                    cache.get_note_by_nullifier(storage, &nullifier).await?
+                {
+                    txp.spend_nullifiers
+                        .insert(nullifier, spendable_note_record.note.clone());
+                }
+            }
+            Action::PositionOpen(_) => {
+                // Position metadata is encrypted to our outgoing viewing key, so the view can
+                // only decrypt it with the key derived from it.
+                txp.position_metadata_key = Some(position_metadata_key(fvk));
+            }
             _ => {}
         }
     }
@@ -261,32 +743,28 @@ async fn transaction_info_inner(
     let min_view = tx.view_from_perspective(&txp);
     let mut address_views = BTreeMap::new();
     let mut asset_ids = BTreeSet::new();
+    let mut has_visible_output = false;
     for action_view in min_view.action_views() {
         match action_view {
             ActionView::Spend(SpendView::Visible { note, .. }) => {
                 address_views.insert(
                     note.address().encode_to_vec(),
-                    fvk.view_address(note.address()),
+                    transparent::view_address(fvk, note.address()),
                 );
                 asset_ids.insert(note.asset_id());
             }
             ActionView::Output(OutputView::Visible { note, .. }) => {
                 address_views.insert(
                     note.address().encode_to_vec(),
-                    fvk.view_address(note.address()),
+                    transparent::view_address(fvk, note.address()),
                 );
                 asset_ids.insert(note.asset_id());
-
-                // Also add an AddressView for the return address in the memo.
-                let memo = tx.decrypt_memo(&fvk)?;
-                address_views.insert(
-                    memo.return_address().encode_to_vec(),
-                    fvk.view_address(note.address()),
-                );
+                has_visible_output = true;
             }
             ActionView::Swap(SwapView::Visible { swap_plaintext, .. }) => {
                 let address = swap_plaintext.claim_address.clone();
-                let address_view = fvk.view_address(swap_plaintext.claim_address.clone());
+                let address_view =
+                    transparent::view_address(fvk, swap_plaintext.claim_address.clone());
                 address_views.insert(address.encode_to_vec(), address_view);
                 asset_ids.insert(swap_plaintext.trading_pair.asset_1());
                 asset_ids.insert(swap_plaintext.trading_pair.asset_2());
@@ -297,14 +775,14 @@ async fn transaction_info_inner(
                 // Both will be sent to the same address so this only needs to be added once
                 address_views.insert(
                     output_1.address().encode_to_vec(),
-                    fvk.view_address(output_1.address()),
+                    transparent::view_address(fvk, output_1.address()),
                 );
                 asset_ids.insert(output_1.asset_id());
                 asset_ids.insert(output_2.asset_id());
             }
             ActionView::DelegatorVote(DelegatorVoteView::Visible { note, .. }) => {
                 let address = note.address();
-                let address_view = fvk.view_address(address.clone());
+                let address_view = transparent::view_address(fvk, address.clone());
                 address_views.insert(address.encode_to_vec(), address_view);
                 asset_ids.insert(note.asset_id());
             }
@@ -325,16 +803,121 @@ async fn transaction_info_inner(
         }
     }
 
+    // Also add an AddressView for the return address in the memo. The memo is shared by all
+    // outputs, so it is decrypted once, and only if there is one to decrypt.
+    if has_visible_output && tx.transaction_body().memo.is_some() {
+        match tx.decrypt_memo(fvk) {
+            Ok(memo) => {
+                address_views.insert(
+                    memo.return_address().encode_to_vec(),
+                    transparent::view_address(fvk, memo.return_address()),
+                );
+            }
+            Err(error) => warnings.push(PerspectiveWarning::UnreadableMemo {
+                reason: error.to_string(),
+            }),
+        }
+    }
+
+    // Some actions are public, so their assets and addresses are read from the action itself.
+    // Tokens derived from the action, such as LP NFTs, auction NFTs and staking tokens, may not
+    // be in storage yet, so their metadata is derived too.
+    let mut derived_denoms = Vec::new();
+    for action in tx.actions() {
+        match action {
+            Action::PositionOpen(open) => {
+                asset_ids.insert(open.position.phi.pair.asset_1());
+                asset_ids.insert(open.position.phi.pair.asset_2());
+                derived_denoms.push(LpNft::new(open.position.id(), State::Opened).denom());
+            }
+            Action::PositionClose(close) => {
+                derived_denoms.push(LpNft::new(close.position_id, State::Opened).denom());
+                derived_denoms.push(LpNft::new(close.position_id, State::Closed).denom());
+            }
+            Action::PositionWithdraw(withdraw) => {
+                let burned = match withdraw.sequence {
+                    0 => State::Closed,
+                    sequence => State::Withdrawn {
+                        sequence: sequence - 1,
+                    },
+                };
+                let minted = State::Withdrawn {
+                    sequence: withdraw.sequence,
+                };
+                derived_denoms.push(LpNft::new(withdraw.position_id, burned).denom());
+                derived_denoms.push(LpNft::new(withdraw.position_id, minted).denom());
+            }
+            Action::Ics20Withdrawal(withdrawal) => {
+                asset_ids.insert(withdrawal.denom.id());
+                address_views.insert(
+                    withdrawal.return_address.encode_to_vec(),
+                    transparent::view_address(fvk, withdrawal.return_address.clone()),
+                );
+            }
+            Action::Delegate(delegate) => {
+                asset_ids.insert(*STAKING_TOKEN_ASSET_ID);
+                derived_denoms.push(DelegationToken::from(delegate.validator_identity).denom());
+            }
+            Action::Undelegate(undelegate) => {
+                asset_ids.insert(*STAKING_TOKEN_ASSET_ID);
+                derived_denoms.push(DelegationToken::from(undelegate.validator_identity).denom());
+                derived_denoms.push(undelegate.unbonding_token().denom());
+            }
+            Action::UndelegateClaim(claim) => {
+                asset_ids.insert(*STAKING_TOKEN_ASSET_ID);
+                derived_denoms.push(
+                    UnbondingToken::new(
+                        claim.body.validator_identity,
+                        claim.body.unbonding_start_height,
+                    )
+                    .denom(),
+                );
+            }
+            Action::ActionLiquidityTournamentVote(vote) => {
+                asset_ids.extend(vote.body.incentivized_id());
+                asset_ids.insert(vote.body.value.asset_id);
+                address_views.insert(
+                    vote.body.rewards_recipient.encode_to_vec(),
+                    transparent::view_address(fvk, vote.body.rewards_recipient.clone()),
+                );
+            }
+            Action::ActionDutchAuctionSchedule(schedule) => {
+                derived_denoms.push(AuctionNft::new(schedule.description.id(), 0).metadata);
+            }
+            Action::ActionDutchAuctionEnd(end) => {
+                // Ending an auction burns the opened auction NFT and mints the closed one.
+                derived_denoms.push(AuctionNft::new(end.auction_id, 0).metadata);
+                derived_denoms.push(AuctionNft::new(end.auction_id, 1).metadata);
+            }
+            Action::ActionDutchAuctionWithdraw(withdraw) => {
+                derived_denoms.push(
+                    AuctionNft::new(withdraw.auction_id, withdraw.seq.saturating_sub(1)).metadata,
+                );
+                derived_denoms.push(AuctionNft::new(withdraw.auction_id, withdraw.seq).metadata);
+            }
+            _ => {}
+        }
+    }
+
     // Now, extend the TxP with information helpful to understand the data it can view:
 
     let mut denoms = Vec::new();
 
     for id in asset_ids {
-        if let Some(denom) = storage.get_asset(&id).await? {
+        if let Some(denom) = cache.get_asset(storage, &id).await? {
             denoms.push(denom.clone());
         }
     }
 
+    for metadata in derived_denoms {
+        // Prefer stored metadata, which already has a customized symbol.
+        let denom = match cache.get_asset(storage, &metadata.id()).await? {
+            Some(denom) => denom,
+            None => Metadata::try_from(customize_symbol_inner(metadata.to_proto())?)?,
+        };
+        denoms.push(denom);
+    }
+
     txp.denoms.extend(denoms.into_iter());
 
     txp.address_views = address_views.into_values().collect();
@@ -342,25 +925,27 @@ async fn transaction_info_inner(
     // Finally, compute the full TxV from the full TxP:
     let txv = tx.view_from_perspective(&txp);
 
-    Ok((txp.into(), txv.into()))
+    Ok(PerspectiveAndView { txp, txv, warnings })
 }
 
-async fn add_swap_claim_txn_to_perspective(
-    storage: &Storage<IdbDatabase>,
+async fn add_swap_claim_txn_to_perspective<Db: Database>(
+    storage: &Storage<Db>,
+    cache: &mut PerspectiveCache,
     fvk: &FullViewingKey,
-    txp: &mut penumbra_transaction::TransactionPerspective,
+    txp: &mut TransactionPerspective,
     commitment: &StateCommitment,
     swap_position: Position,
 ) -> Result<(), WasmError> {
     let derived_nullifier_from_swap =
         Nullifier::derive(fvk.nullifier_key(), swap_position, commitment);
 
-    let transaction_infos = storage.get_transaction_infos().await?;
+    let transaction_infos = cache.get_transaction_infos(storage).await?;
 
     for transaction_info in transaction_infos {
         transaction_info
             .transaction
-            .and_then(|transaction| transaction.body)
+            .as_ref()
+            .and_then(|transaction| transaction.body.as_ref())
             .iter()
             .for_each(|body| {
                 for action in body.actions.iter() {
@@ -392,7 +977,7 @@ async fn add_swap_claim_txn_to_perspective(
 
 #[wasm_bindgen]
 pub async fn transaction_summary(txv: &[u8]) -> WasmResult<Vec<u8>> {
-    let transaction_view = TransactionViewComponent::decode(txv)?;
+    let transaction_view = TransactionView::decode(txv)?;
     let tx_summary = transaction_view.summary();
 
     Ok(tx_summary.encode_to_vec())
@@ -8,3 +8,36 @@ pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 }
+
+/// The camelCase name of an action, keyed by the protobuf `variant_index` that `Action` and
+/// `ActionPlan` share.
+pub fn action_name(variant_index: usize) -> &'static str {
+    match variant_index {
+        1 => "spend",
+        2 => "output",
+        3 => "swap",
+        4 => "swapClaim",
+        16 => "validatorDefinition",
+        17 => "ibcRelay",
+        18 => "proposalSubmit",
+        19 => "proposalWithdraw",
+        20 => "validatorVote",
+        21 => "delegatorVote",
+        22 => "proposalDepositClaim",
+        30 => "positionOpen",
+        31 => "positionClose",
+        32 => "positionWithdraw",
+        40 => "delegate",
+        41 => "undelegate",
+        42 => "undelegateClaim",
+        50 => "communityPoolSpend",
+        51 => "communityPoolOutput",
+        52 => "communityPoolDeposit",
+        53 => "actionDutchAuctionSchedule",
+        54 => "actionDutchAuctionEnd",
+        55 => "actionDutchAuctionWithdraw",
+        70 => "actionLiquidityTournamentVote",
+        200 => "ics20Withdrawal",
+        _ => "other",
+    }
+}
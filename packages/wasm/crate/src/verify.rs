@@ -0,0 +1,308 @@
+use decaf377::Fr;
+use penumbra_dex::swap::proof::SwapProofPublic;
+use penumbra_dex::swap_claim::SwapClaimProofPublic;
+use penumbra_fee::GasPrices;
+use penumbra_funding::liquidity_tournament::proof::LiquidityTournamentVoteProofPublic;
+use penumbra_governance::DelegatorVoteProofPublic;
+use penumbra_proof_params::{
+    CONVERT_PROOF_VERIFICATION_KEY, DELEGATOR_VOTE_PROOF_VERIFICATION_KEY,
+    OUTPUT_PROOF_VERIFICATION_KEY, SPEND_PROOF_VERIFICATION_KEY, SWAPCLAIM_PROOF_VERIFICATION_KEY,
+    SWAP_PROOF_VERIFICATION_KEY,
+};
+use penumbra_proto::DomainType;
+use penumbra_shielded_pool::output::OutputProofPublic;
+use penumbra_shielded_pool::SpendProofPublic;
+use penumbra_stake::undelegate_claim::UndelegateClaimProofPublic;
+use penumbra_stake::UnbondingToken;
+use penumbra_tct as tct;
+use penumbra_transaction::gas::GasCost;
+use penumbra_transaction::{Action, Transaction};
+use penumbra_txhash::{AuthorizingData, EffectHash, EffectingData};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+
+use crate::error::WasmResult;
+use crate::utils;
+
+/// The outcome of one verification step.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum Check {
+    Passed,
+    Failed {
+        reason: String,
+    },
+    /// The step cannot be performed offline, or was not requested.
+    Skipped {
+        reason: String,
+    },
+}
+
+impl Check {
+    fn from_result<E: ToString>(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Check::Passed,
+            Err(e) => Check::Failed {
+                reason: e.to_string(),
+            },
+        }
+    }
+
+    fn skipped(reason: &str) -> Self {
+        Check::Skipped {
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Check::Failed { .. })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionDiagnostic {
+    pub index: usize,
+    pub action: String,
+    /// The spend authorization signature, for actions that carry one.
+    pub signature: Option<Check>,
+    pub proof: Check,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReport {
+    /// False if any check failed. Skipped checks do not make a transaction invalid.
+    pub valid: bool,
+    pub binding_signature: Check,
+    pub anchor: Check,
+    pub fee: Check,
+    pub actions: Vec<ActionDiagnostic>,
+}
+
+/// Verify a built transaction before broadcasting it
+/// Arguments:
+///     transaction: `byte representation inner Transaction`
+///     sct_root: `byte representation inner MerkleRoot`, the root the transaction was built against
+///     gas_prices: `byte representation inner GasPrices`, optional; the fee is only checked when given
+///     vote_anchor: `byte representation inner MerkleRoot`, optional; the SCT root at the start of
+///                  the proposal or voting epoch, vote proofs are only checked when given
+/// Returns: `VerificationReport`
+#[wasm_bindgen]
+pub fn verify_transaction(
+    transaction: &[u8],
+    sct_root: &[u8],
+    gas_prices: Option<Vec<u8>>,
+    vote_anchor: Option<Vec<u8>>,
+) -> WasmResult<JsValue> {
+    utils::set_panic_hook();
+
+    let tx = Transaction::decode(transaction)?;
+    let sct_root = tct::Root::decode(sct_root)?;
+    let gas_prices = gas_prices
+        .map(|gas_prices| GasPrices::decode(gas_prices.as_slice()))
+        .transpose()?;
+    let vote_anchor = vote_anchor
+        .map(|vote_anchor| tct::Root::decode(vote_anchor.as_slice()))
+        .transpose()?;
+
+    let report = verify_transaction_inner(&tx, sct_root, gas_prices.as_ref(), vote_anchor);
+
+    let result = serde_wasm_bindgen::to_value(&report)?;
+    Ok(result)
+}
+
+pub fn verify_transaction_inner(
+    tx: &Transaction,
+    sct_root: tct::Root,
+    gas_prices: Option<&GasPrices>,
+    vote_anchor: Option<tct::Root>,
+) -> VerificationReport {
+    let effect_hash = tx.effect_hash();
+
+    // The binding signature covers the balance commitments of every action together with the fee,
+    // so it fails for unbalanced transactions as well as for bad signatures.
+    let binding_signature = Check::from_result(
+        tx.binding_verification_key()
+            .verify(tx.auth_hash().as_bytes(), tx.binding_sig()),
+    );
+
+    let anchor = if tx.anchor == sct_root {
+        Check::Passed
+    } else {
+        Check::Failed {
+            reason: format!(
+                "transaction anchor {} does not match the SCT root {}",
+                tx.anchor, sct_root
+            ),
+        }
+    };
+
+    let fee = match gas_prices {
+        None => Check::skipped("no gas prices were supplied"),
+        Some(gas_prices) => {
+            let paid = &tx.transaction_body.transaction_parameters.fee;
+            let required = gas_prices.fee(&tx.gas_cost());
+            if paid.asset_id() != required.asset_id() {
+                Check::Failed {
+                    reason: "fee is not paid in the asset of the supplied gas prices".to_string(),
+                }
+            } else if paid.amount() < required.amount() {
+                Check::Failed {
+                    reason: format!(
+                        "fee {} is below the required {}",
+                        paid.amount(),
+                        required.amount()
+                    ),
+                }
+            } else {
+                Check::Passed
+            }
+        }
+    };
+
+    let actions: Vec<ActionDiagnostic> = tx
+        .actions()
+        .enumerate()
+        .map(|(index, action)| verify_action(index, action, tx.anchor, vote_anchor, &effect_hash))
+        .collect();
+
+    let valid = !binding_signature.is_failed()
+        && !anchor.is_failed()
+        && !fee.is_failed()
+        && actions.iter().all(|action| {
+            !action.proof.is_failed() && !action.signature.as_ref().is_some_and(Check::is_failed)
+        });
+
+    VerificationReport {
+        valid,
+        binding_signature,
+        anchor,
+        fee,
+        actions,
+    }
+}
+
+fn verify_action(
+    index: usize,
+    action: &Action,
+    anchor: tct::Root,
+    vote_anchor: Option<tct::Root>,
+    effect_hash: &EffectHash,
+) -> ActionDiagnostic {
+    let (signature, proof) = match action {
+        Action::Spend(spend) => (
+            Some(Check::from_result(
+                spend.body.rk.verify(effect_hash.as_ref(), &spend.auth_sig),
+            )),
+            Check::from_result(spend.proof.verify(
+                &SPEND_PROOF_VERIFICATION_KEY,
+                SpendProofPublic {
+                    anchor,
+                    balance_commitment: spend.body.balance_commitment,
+                    nullifier: spend.body.nullifier,
+                    rk: spend.body.rk,
+                },
+            )),
+        ),
+        Action::Output(output) => (
+            None,
+            Check::from_result(output.proof.verify(
+                &OUTPUT_PROOF_VERIFICATION_KEY,
+                OutputProofPublic {
+                    balance_commitment: output.body.balance_commitment,
+                    note_commitment: output.body.note_payload.note_commitment,
+                },
+            )),
+        ),
+        Action::Swap(swap) => (
+            None,
+            Check::from_result(swap.proof.verify(
+                &SWAP_PROOF_VERIFICATION_KEY,
+                SwapProofPublic {
+                    balance_commitment: swap.balance_commitment_inner(),
+                    swap_commitment: swap.body.payload.commitment,
+                    fee_commitment: swap.body.fee_commitment,
+                },
+            )),
+        ),
+        Action::SwapClaim(swap_claim) => (
+            None,
+            Check::from_result(swap_claim.proof.verify(
+                &SWAPCLAIM_PROOF_VERIFICATION_KEY,
+                SwapClaimProofPublic {
+                    anchor,
+                    nullifier: swap_claim.body.nullifier,
+                    claim_fee: swap_claim.body.fee,
+                    output_data: swap_claim.body.output_data,
+                    note_commitment_1: swap_claim.body.output_1_commitment,
+                    note_commitment_2: swap_claim.body.output_2_commitment,
+                },
+            )),
+        ),
+        Action::UndelegateClaim(claim) => (
+            None,
+            Check::from_result(
+                claim.proof.verify(
+                    &CONVERT_PROOF_VERIFICATION_KEY,
+                    UndelegateClaimProofPublic {
+                        balance_commitment: claim.body.balance_commitment,
+                        unbonding_id: UnbondingToken::new(
+                            claim.body.validator_identity,
+                            claim.body.unbonding_start_height,
+                        )
+                        .id(),
+                        penalty: claim.body.penalty,
+                    },
+                ),
+            ),
+        ),
+        // Vote proofs are made against the SCT root at the start of the proposal or voting epoch,
+        // which is chain state the transaction does not carry.
+        Action::DelegatorVote(vote) => (
+            Some(Check::from_result(
+                vote.body.rk.verify(effect_hash.as_ref(), &vote.auth_sig),
+            )),
+            match vote_anchor {
+                None => Check::skipped("requires the SCT root at the start of the proposal"),
+                Some(anchor) => Check::from_result(vote.proof.verify(
+                    &DELEGATOR_VOTE_PROOF_VERIFICATION_KEY,
+                    DelegatorVoteProofPublic {
+                        anchor,
+                        balance_commitment: vote.body.value.commit(Fr::from(0u64)),
+                        nullifier: vote.body.nullifier,
+                        rk: vote.body.rk,
+                        start_position: vote.body.start_position,
+                    },
+                )),
+            },
+        ),
+        Action::ActionLiquidityTournamentVote(vote) => (
+            Some(Check::from_result(
+                vote.body.rk.verify(effect_hash.as_ref(), &vote.auth_sig),
+            )),
+            match vote_anchor {
+                None => Check::skipped("requires the SCT root at the start of the voting epoch"),
+                Some(anchor) => Check::from_result(vote.proof.verify(
+                    &DELEGATOR_VOTE_PROOF_VERIFICATION_KEY,
+                    LiquidityTournamentVoteProofPublic {
+                        anchor,
+                        value: vote.body.value,
+                        nullifier: vote.body.nullifier,
+                        rk: vote.body.rk,
+                        start_position: vote.body.start_position,
+                    },
+                )),
+            },
+        ),
+        _ => (None, Check::skipped("action has no proof")),
+    };
+
+    ActionDiagnostic {
+        index,
+        action: utils::action_name(action.variant_index()).to_string(),
+        signature,
+        proof,
+    }
+}
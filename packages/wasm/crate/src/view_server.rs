@@ -1,13 +1,16 @@
 use std::collections::BTreeMap;
 
+use decaf377_fmd::Clue;
 use indexed_db_futures::IdbDatabase;
 use penumbra_compact_block::{CompactBlock, StatePayload};
-use penumbra_keys::{Address, FullViewingKey};
+use penumbra_keys::keys::{AddressIndex, WalletId};
+use penumbra_keys::{Address, AddressView, FullViewingKey};
 use penumbra_proto::DomainType;
 use penumbra_sct::Nullifier;
 use penumbra_shielded_pool::{note, Note};
 use penumbra_tct::Witness::*;
 use penumbra_tct::{self as tct, StateCommitment};
+use penumbra_transaction::{Action, Transaction};
 use serde::{Deserialize, Serialize};
 use serde_wasm_bindgen::Serializer;
 use tct::storage::{StoreCommitment, StoreHash, StoredPosition, Updates};
@@ -15,11 +18,14 @@ use tct::{Forgotten, Tree};
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::JsValue;
 
-use crate::error::WasmResult;
+use crate::error::{WasmError, WasmResult};
+use crate::fmd::{clues_by_commitment, FmdFilter, NoteClue};
+use crate::invoice::{record_invoice_payment, Invoice, InvoiceUpdate};
 use crate::keys::is_controlled_inner;
 use crate::note_record::SpendableNoteRecord;
 use crate::storage::{init_idb_storage, Storage};
 use crate::swap_record::SwapRecord;
+use crate::transparent;
 use crate::utils;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -32,39 +38,124 @@ pub struct StoredTree {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ScanBlockResult {
-    height: u64,
-    sct_updates: Updates,
-    new_notes: Vec<SpendableNoteRecord>,
-    new_swaps: Vec<SwapRecord>,
+    pub height: u64,
+    pub wallet_id: WalletId,
+    pub sct_updates: Updates,
+    pub new_notes: Vec<SpendableNoteRecord>,
+    pub new_swaps: Vec<SwapRecord>,
+    pub invoice_updates: Vec<InvoiceUpdate>,
 }
 
 impl ScanBlockResult {
     pub fn new(
         height: u64,
+        wallet_id: WalletId,
         sct_updates: Updates,
         new_notes: Vec<SpendableNoteRecord>,
         new_swaps: Vec<SwapRecord>,
+        invoice_updates: Vec<InvoiceUpdate>,
     ) -> ScanBlockResult {
         Self {
             height,
+            wallet_id,
             sct_updates,
             new_notes,
             new_swaps,
+            invoice_updates,
         }
     }
 }
 
-#[wasm_bindgen]
-pub struct ViewServer {
-    latest_height: u64,
+/// A full viewing key tracked by the view server, along with the records found for it since the
+/// last flush.
+struct Wallet {
     fvk: FullViewingKey,
     notes: BTreeMap<note::StateCommitment, SpendableNoteRecord>,
     swaps: BTreeMap<tct::StateCommitment, SwapRecord>,
+    /// The latest state of each invoice paid since the last flush, by the index of its address.
+    invoices: BTreeMap<AddressIndex, Invoice>,
+}
+
+impl Wallet {
+    fn new(fvk: FullViewingKey) -> Self {
+        Self {
+            fvk,
+            notes: Default::default(),
+            swaps: Default::default(),
+            invoices: Default::default(),
+        }
+    }
+
+    fn note_record(
+        &self,
+        note: &Note,
+        payload: &StatePayload,
+        position: tct::Position,
+        height: u64,
+    ) -> SpendableNoteRecord {
+        let nullifier = Nullifier::derive(self.fvk.nullifier_key(), position, payload.commitment());
+        let address_index = transparent::note_address_index(&self.fvk, &note.address());
+
+        SpendableNoteRecord {
+            note_commitment: *payload.commitment(),
+            height_spent: None,
+            height_created: height,
+            note: note.clone(),
+            address_index,
+            nullifier,
+            position,
+            source: payload.source().clone(),
+            return_address: None,
+        }
+    }
+}
+
+/// Records found in a block, set aside until the whole block has been applied to the SCT, so
+/// that a block that fails partway leaves no trace in the wallets.
+#[derive(Default)]
+struct BlockRecords {
+    /// Note records, by the index of the wallet that owns them.
+    notes: Vec<(usize, SpendableNoteRecord)>,
+    /// Swap records, by the index of the wallet that owns them.
+    swaps: Vec<(usize, SwapRecord)>,
+    /// Invoices credited by the block's notes, by the index of the wallet that owns them.
+    invoices: Vec<(usize, Invoice)>,
+}
+
+impl BlockRecords {
+    fn is_empty(&self) -> bool {
+        self.notes.is_empty() && self.swaps.is_empty()
+    }
+
+    /// The latest state of a wallet's invoice credited by this block.
+    fn invoice(&self, index: usize, address_index: &AddressIndex) -> Option<&Invoice> {
+        self.invoices
+            .iter()
+            .rev()
+            .find(|(i, invoice)| *i == index && invoice.address_index == *address_index)
+            .map(|(_, invoice)| invoice)
+    }
+}
+
+/// Scans compact blocks for one or more wallets at once.
+///
+/// Every payload is trial decrypted against each wallet's full viewing key, and a commitment is
+/// witnessed in the shared SCT if any wallet owns it. The first wallet is the primary one, used by
+/// `flush_updates`; records for all wallets are available through `flush_wallet_updates`.
+#[wasm_bindgen]
+pub struct ViewServer {
+    latest_height: u64,
+    wallets: Vec<Wallet>,
     sct: Tree,
     storage: Storage<IdbDatabase>,
+    /// The wallet data database, which holds the invoices that scanned notes may pay.
+    wallet_data: Storage<IdbDatabase>,
     last_position: Option<StoredPosition>,
     last_forgotten: Option<Forgotten>,
-    genesis_advice: Option<BTreeMap<StateCommitment, Note>>,
+    genesis_advice: Option<BTreeMap<StateCommitment, (usize, Note)>>,
+    next_genesis_chunk: u64,
+    diverged_at: Option<(u64, &'static str)>,
+    fmd: Option<FmdFilter>,
 }
 
 #[wasm_bindgen]
@@ -76,30 +167,35 @@ impl ViewServer {
     ///     epoch_duration: `u64`
     ///     stored_tree: `StoredTree`
     ///     idb_constants: `IndexedDbConstants`
+    ///     wallet_data_constants: `IndexedDbConstants` of the wallet data database
     /// Returns: `ViewServer`
     #[wasm_bindgen]
     pub async fn new(
         full_viewing_key: &[u8],
         stored_tree: JsValue,
         idb_constants: JsValue,
+        wallet_data_constants: JsValue,
     ) -> WasmResult<ViewServer> {
         utils::set_panic_hook();
 
         let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
         let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+        let wallet_data_constants = serde_wasm_bindgen::from_value(wallet_data_constants)?;
         let stored_tree: StoredTree = serde_wasm_bindgen::from_value(stored_tree)?;
         let tree = load_tree(stored_tree);
 
         let view_server = Self {
             latest_height: u64::MAX,
-            fvk,
-            notes: Default::default(),
+            wallets: vec![Wallet::new(fvk)],
             sct: tree,
-            swaps: Default::default(),
             storage: init_idb_storage(constants).await?,
+            wallet_data: init_idb_storage(wallet_data_constants).await?,
             last_position: None,
             last_forgotten: None,
             genesis_advice: None,
+            next_genesis_chunk: 0,
+            diverged_at: None,
+            fmd: None,
         };
         Ok(view_server)
     }
@@ -109,68 +205,86 @@ impl ViewServer {
     pub async fn new_snapshot(
         full_viewing_key: &[u8],
         idb_constants: JsValue,
+        wallet_data_constants: JsValue,
         compact_frontier: &[u8],
     ) -> WasmResult<ViewServer> {
         utils::set_panic_hook();
 
         let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
         let constants = serde_wasm_bindgen::from_value(idb_constants)?;
+        let wallet_data_constants = serde_wasm_bindgen::from_value(wallet_data_constants)?;
 
         let tree: Tree = bincode::deserialize(compact_frontier)
-            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize frontier: {}", e)))
-            .expect("frontier snapshot");
+            .map_err(|e| WasmError::InvalidFrontier(e.to_string()))?;
 
         let view_server = Self {
             latest_height: u64::MAX,
-            fvk,
-            notes: Default::default(),
+            wallets: vec![Wallet::new(fvk)],
             sct: tree,
-            swaps: Default::default(),
             storage: init_idb_storage(constants).await?,
+            wallet_data: init_idb_storage(wallet_data_constants).await?,
             last_position: None,
             last_forgotten: None,
             genesis_advice: None,
+            next_genesis_chunk: 0,
+            diverged_at: None,
+            fmd: None,
         };
         Ok(view_server)
     }
 
     /// Scans a chunk of the genesis block for notes that can be trial decrypted with the viewing key.
+    /// Chunks must be supplied in order, starting at offset 0, with each `start` equal to the
+    /// number of state payloads already scanned.
     #[wasm_bindgen]
     pub async fn scan_genesis_chunk(
         &mut self,
         start: u64,
         partial_compact_block: &[u8],
         skip_trial_decrypt: bool,
-    ) {
+    ) -> WasmResult<()> {
         utils::set_panic_hook();
 
-        let partial_block =
-            CompactBlock::decode(partial_compact_block).expect("decode genesis compact block");
+        let partial_block = CompactBlock::decode(partial_compact_block)?;
 
         // Initialize advice storage on first chunk
         if start == 0 {
             self.genesis_advice = Some(BTreeMap::new());
+            self.next_genesis_chunk = 0;
         }
 
-        let genesis_advice = self
-            .genesis_advice
-            .as_mut()
-            .ok_or_else(|| anyhow::anyhow!("genesis_advice not initialized"))
-            .expect("genesis advice");
+        let expected = self.next_genesis_chunk;
+        let genesis_advice = match self.genesis_advice.as_mut() {
+            Some(advice) if start == expected => advice,
+            _ => {
+                return Err(WasmError::GenesisChunkOutOfOrder {
+                    expected,
+                    actual: start,
+                })
+            }
+        };
 
         // Attempt to decrypt each state payload in this chunk
         for state_payload in &partial_block.state_payloads {
             if let StatePayload::Note { note: payload, .. } = state_payload {
                 let note_opt = (!skip_trial_decrypt)
-                    .then(|| payload.trial_decrypt(&self.fvk))
+                    .then(|| {
+                        self.wallets.iter().enumerate().find_map(|(index, wallet)| {
+                            payload.trial_decrypt(&wallet.fvk).map(|note| (index, note))
+                        })
+                    })
                     .flatten();
-                if let Some(note) = note_opt {
+                if let Some(owned_note) = note_opt {
                     // It's safe to avoid recomputing the note commitment here because
                     // trial_decrypt checks that the decrypted data is consistent
-                    genesis_advice.insert(payload.note_commitment, note);
+                    genesis_advice.insert(payload.note_commitment, owned_note);
                 }
             }
         }
+
+        self.next_genesis_chunk = start + partial_block.state_payloads.len() as u64;
+
+        Ok(())
     }
 
     /// Reconstructs the state commitment tree (SCT) from the full genesis block using
@@ -180,20 +294,275 @@ impl ViewServer {
         utils::set_panic_hook();
 
         let full_block = CompactBlock::decode(full_compact_block)?;
+        self.check_block_sequence(full_block.height)?;
+
+        let height = full_block.height;
+        let sct = self.sct.clone();
+        match self.apply_genesis_block(full_block) {
+            Ok(records) => Ok(self.commit_block(height, records)),
+            Err(error) => {
+                self.sct = sct;
+                Err(error)
+            }
+        }
+    }
+
+    /// Scans block for notes, swaps
+    /// Returns true if the block contains new notes, swaps or false if the block is empty for us
+    ///     compact_block: `v1::CompactBlock`
+    /// Scan results are saved in-memory rather than returned
+    /// Use `flush_updates()` to get the scan results
+    /// Returns: `bool`
+    #[wasm_bindgen]
+    pub async fn scan_block(
+        &mut self,
+        compact_block: &[u8],
+        skip_trial_decrypt: bool,
+    ) -> WasmResult<bool> {
+        utils::set_panic_hook();
+
+        let block = CompactBlock::decode(compact_block)?;
+        self.scan_block_inner(block, BTreeMap::new(), skip_trial_decrypt)
+            .await
+    }
+
+    /// Enables FMD for `scan_block_with_clues`
+    /// Detection keys are derived for the default address of the first `account_count` accounts
+    /// of every wallet, and the expected false positive rate comes from the FMD parameters in storage.
+    /// Notes sent to any other address (one-time, invoice, forwarding or transparent addresses, or
+    /// accounts past `account_count`) are never detected. Unless `skip_undetected` is set, every
+    /// payload is still trial decrypted and FMD only gathers `fmd_stats`; with it, those notes are
+    /// not found.
+    ///     account_count: `u32`
+    ///     skip_undetected: `bool`
+    #[wasm_bindgen]
+    pub async fn enable_fmd(
+        &mut self,
+        account_count: u32,
+        skip_undetected: bool,
+    ) -> WasmResult<()> {
+        utils::set_panic_hook();
+
+        let params = self
+            .storage
+            .get_fmd_params()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("FmdParameters not available"))?;
+        let mut filter = FmdFilter::new(params, account_count, skip_undetected);
+        for wallet in &self.wallets {
+            filter.add_wallet(&wallet.fvk);
+        }
+        self.fmd = Some(filter);
+
+        Ok(())
+    }
+
+    /// Scans block for notes, swaps, examining the FMD clue of each note payload. If `enable_fmd`
+    /// was called with `skip_undetected`, payloads whose clue does not match our detection keys are
+    /// not trial decrypted. Payloads without a clue are always trial decrypted.
+    /// Behaves like `scan_block` unless `enable_fmd` has been called.
+    ///     compact_block: `v1::CompactBlock`
+    ///     clues: `NoteClue[]`
+    /// Returns: `bool`
+    #[wasm_bindgen]
+    pub async fn scan_block_with_clues(
+        &mut self,
+        compact_block: &[u8],
+        clues: JsValue,
+        skip_trial_decrypt: bool,
+    ) -> WasmResult<bool> {
+        utils::set_panic_hook();
+
+        let block = CompactBlock::decode(compact_block)?;
+        let clues: Vec<NoteClue> = serde_wasm_bindgen::from_value(clues)?;
+        let clues = clues_by_commitment(clues)?;
+        self.scan_block_inner(block, clues, skip_trial_decrypt)
+            .await
+    }
+
+    /// Reports how many clues were examined and the observed false positive rate
+    /// Returns: `FmdStats`, or `undefined` if FMD pre-filtering is not enabled
+    #[wasm_bindgen]
+    pub fn fmd_stats(&self) -> WasmResult<JsValue> {
+        utils::set_panic_hook();
+
+        let stats = self.fmd.as_ref().map(FmdFilter::stats);
+        Ok(serde_wasm_bindgen::to_value(&stats)?)
+    }
+
+    /// Adds another wallet to scan for in the same pass
+    /// Wallets must be added before the first block is scanned, since commitments that were
+    /// already forgotten cannot be witnessed retroactively.
+    ///     full_viewing_key: `byte representation inner FullViewingKey`
+    #[wasm_bindgen]
+    pub fn add_wallet(&mut self, full_viewing_key: &[u8]) -> WasmResult<()> {
+        utils::set_panic_hook();
+
+        let fvk: FullViewingKey = FullViewingKey::decode(full_viewing_key)?;
+
+        if self.latest_height != u64::MAX || self.genesis_advice.is_some() {
+            return Err(anyhow::anyhow!("wallets must be added before scanning begins").into());
+        }
+        if self
+            .wallets
+            .iter()
+            .any(|wallet| wallet.fvk.wallet_id() == fvk.wallet_id())
+        {
+            return Err(
+                anyhow::anyhow!("wallet {} is already being scanned", fvk.wallet_id()).into(),
+            );
+        }
+
+        if let Some(filter) = self.fmd.as_mut() {
+            filter.add_wallet(&fvk);
+        }
+        self.wallets.push(Wallet::new(fvk));
+
+        Ok(())
+    }
+
+    /// Get new notes, swaps, paid invoices, SCT state updates for the primary wallet
+    /// Function also clears state
+    /// Returns: `ScanBlockResult`
+    #[wasm_bindgen]
+    pub fn flush_updates(&mut self) -> WasmResult<JsValue> {
+        utils::set_panic_hook();
+
+        let sct_updates = self.take_sct_updates();
+        let updates = self.take_wallet_updates(0, sct_updates);
+
+        let serializer = Serializer::new().serialize_large_number_types_as_bigints(true);
+        let result = updates.serialize(&serializer)?;
+        Ok(result)
+    }
+
+    /// Get new notes, swaps, paid invoices, SCT state updates for every wallet
+    /// Function also clears state. The SCT updates are shared by all wallets and repeated in
+    /// each result, so they only need to be persisted once.
+    /// Returns: `ScanBlockResult[]`, in the order the wallets were added
+    #[wasm_bindgen]
+    pub fn flush_wallet_updates(&mut self) -> WasmResult<JsValue> {
+        utils::set_panic_hook();
+
+        let sct_updates = self.take_sct_updates();
+        let updates: Vec<ScanBlockResult> = (0..self.wallets.len())
+            .map(|index| self.take_wallet_updates(index, sct_updates.clone()))
+            .collect();
+
+        let serializer = Serializer::new().serialize_large_number_types_as_bigints(true);
+        let result = updates.serialize(&serializer)?;
+        Ok(result)
+    }
+
+    /// Fills in the return address of notes created by a transaction
+    /// Should be called with the transactions fetched for blocks that contained new notes. The
+    /// memo is decrypted with the wallet that owns each output, and its return address is
+    /// attached to the note record. Notes that have not been flushed yet are updated in place;
+    /// notes that were already flushed are updated in storage.
+    ///     transaction: `byte representation inner Transaction`
+    /// Returns: `SpendableNoteRecord[]` of the enriched records
+    #[wasm_bindgen]
+    pub async fn enrich_transaction(&mut self, transaction: &[u8]) -> WasmResult<JsValue> {
+        utils::set_panic_hook();
 
-        let mut found_new_data: bool = false;
+        let tx = Transaction::decode(transaction)?;
+        let commitments: Vec<note::StateCommitment> = tx
+            .actions()
+            .filter_map(|action| match action {
+                Action::Output(output) => Some(output.body.note_payload.note_commitment),
+                _ => None,
+            })
+            .collect();
+
+        let mut enriched = Vec::new();
+        for wallet in self.wallets.iter_mut() {
+            let Some(return_address) = memo_return_address(&wallet.fvk, &tx) else {
+                continue;
+            };
+
+            for commitment in &commitments {
+                if let Some(record) = wallet.notes.get_mut(commitment) {
+                    record.return_address = Some(return_address.clone());
+                    enriched.push(record.clone());
+                } else if let Some(mut record) = self.storage.get_note(commitment).await? {
+                    if record.return_address.is_some()
+                        || !is_controlled_inner(&wallet.fvk, &record.note.address())
+                    {
+                        continue;
+                    }
+                    record.return_address = Some(return_address.clone());
+                    self.storage.save_note(&record).await?;
+                    enriched.push(record);
+                }
+            }
+        }
+
+        let serializer = Serializer::new().serialize_large_number_types_as_bigints(true);
+        let result = enriched.serialize(&serializer)?;
+        Ok(result)
+    }
+
+    /// SCT root can be compared with the root obtained by GRPC and verify that there is no divergence
+    /// Returns: `Uint8Array representing a Root`
+    #[wasm_bindgen]
+    pub fn get_sct_root(&mut self) -> WasmResult<Vec<u8>> {
+        utils::set_panic_hook();
+
+        let root = self.sct.root();
+        Ok(root.encode_to_vec())
+    }
+
+    /// Checks if address is controlled by any of the view server full viewing keys
+    #[wasm_bindgen]
+    pub fn is_controlled_address(&self, address: &[u8]) -> WasmResult<bool> {
+        utils::set_panic_hook();
+
+        let address: Address = Address::decode(address)?;
+        Ok(self
+            .wallets
+            .iter()
+            .any(|wallet| is_controlled_inner(&wallet.fvk, &address)))
+    }
+}
+
+impl ViewServer {
+    async fn scan_block_inner(
+        &mut self,
+        block: CompactBlock,
+        clues: BTreeMap<note::StateCommitment, Clue>,
+        skip_trial_decrypt: bool,
+    ) -> WasmResult<bool> {
+        self.check_block_sequence(block.height)?;
+
+        // A block is applied in full or not at all: if it fails partway, the tree is restored so
+        // that scanning the block again does not insert its commitments twice.
+        let height = block.height;
+        let sct = self.sct.clone();
+        match self.apply_block(block, clues, skip_trial_decrypt).await {
+            Ok(records) => Ok(self.commit_block(height, records)),
+            Err(error) => {
+                self.sct = sct;
+                Err(error)
+            }
+        }
+    }
+
+    /// Inserts the genesis block into the SCT using the advice gathered from its chunks.
+    fn apply_genesis_block(&mut self, full_block: CompactBlock) -> WasmResult<BlockRecords> {
+        let mut records = BlockRecords::default();
 
         let genesis_advice = self
             .genesis_advice
-            .as_mut()
+            .as_ref()
             .ok_or_else(|| anyhow::anyhow!("genesis_advice not initialized"))?;
 
         if genesis_advice.is_empty() {
             // If there are no notes we care about in this block, just insert the block root into the
-            // tree instead of processing each commitment individually
+            // tree instead of processing each commitment individually. The root comes from the
+            // compact block itself, so there is nothing to check it against.
             self.sct
                 .insert_block(full_block.block_root)
-                .expect("inserting a block root must succeed");
+                .map_err(sct_full)?;
         } else {
             // If we found at least one note for us in this block, we have to explicitly construct the
             // whole block in the SCT by inserting each commitment one at a time
@@ -201,35 +570,19 @@ impl ViewServer {
                 // We proceed commitment by commitment, querying our in-memory advice
                 // to see if we have any data for the commitment and act accordingly
                 match genesis_advice.get(payload.commitment()) {
-                    Some(note) => {
-                        let position = self.sct.insert(Keep, *payload.commitment())?;
-
-                        let source = payload.source().clone();
-                        let nullifier = Nullifier::derive(
-                            self.fvk.nullifier_key(),
+                    Some((index, note)) => {
+                        let position = self
+                            .sct
+                            .insert(Keep, *payload.commitment())
+                            .map_err(sct_full)?;
+
+                        let note_record = self.wallets[*index].note_record(
+                            note,
+                            &payload,
                             position,
-                            payload.commitment(),
+                            full_block.height,
                         );
-                        let address_index = self
-                            .fvk
-                            .incoming()
-                            .index_for_diversifier(note.diversifier());
-
-                        let note_record = SpendableNoteRecord {
-                            note_commitment: *payload.commitment(),
-                            height_spent: None,
-                            height_created: full_block.height,
-                            note: note.clone(),
-                            address_index,
-                            nullifier,
-                            position,
-                            source,
-                            return_address: None,
-                        };
-                        self.notes
-                            .insert(*payload.commitment(), note_record.clone());
-
-                        found_new_data = true;
+                        records.notes.push((*index, note_record));
                     }
                     None => {
                         // Don't remember this commitment; it wasn't ours, and
@@ -237,37 +590,31 @@ impl ViewServer {
                         // Just insert and forget
                         self.sct
                             .insert(tct::Witness::Forget, *payload.commitment())
-                            .expect("inserting a commitment must succeed");
+                            .map_err(sct_full)?;
                     }
                 }
             }
 
             // End the block in the commitment tree
-            self.sct.end_block().expect("ending the block must succed");
+            let block_root = self.sct.end_block().map_err(sct_full)?;
+            self.check_root(
+                full_block.height,
+                "block",
+                block_root == full_block.block_root,
+            )?;
         }
 
-        self.latest_height = full_block.height;
-
-        Ok(found_new_data)
+        Ok(records)
     }
 
-    /// Scans block for notes, swaps
-    /// Returns true if the block contains new notes, swaps or false if the block is empty for us
-    ///     compact_block: `v1::CompactBlock`
-    /// Scan results are saved in-memory rather than returned
-    /// Use `flush_updates()` to get the scan results
-    /// Returns: `bool`
-    #[wasm_bindgen]
-    pub async fn scan_block(
+    /// Inserts a block into the SCT, returning the records found in it for our wallets.
+    async fn apply_block(
         &mut self,
-        compact_block: &[u8],
+        block: CompactBlock,
+        clues: BTreeMap<note::StateCommitment, Clue>,
         skip_trial_decrypt: bool,
-    ) -> WasmResult<bool> {
-        utils::set_panic_hook();
-
-        let block = CompactBlock::decode(compact_block)?;
-
-        let mut found_new_data: bool = false;
+    ) -> WasmResult<BlockRecords> {
+        let mut records = BlockRecords::default();
 
         let mut note_advice = BTreeMap::new();
         let mut swap_advice = BTreeMap::new();
@@ -278,18 +625,46 @@ impl ViewServer {
         for state_payload in &block.state_payloads {
             match state_payload {
                 StatePayload::Note { note: payload, .. } => {
-                    let note_opt = (!skip_trial_decrypt)
-                        .then(|| payload.trial_decrypt(&self.fvk))
-                        .flatten();
-                    if let Some(note) = note_opt {
+                    // Without a clue or an FMD filter, nothing is known about the payload.
+                    let clue = clues.get(&payload.note_commitment);
+                    let detected = match (self.fmd.as_mut(), clue) {
+                        (Some(filter), Some(clue)) => Some(filter.detects(clue)),
+                        _ => None,
+                    };
+
+                    // Undetected payloads are only skipped if the filter was explicitly told to,
+                    // since notes sent to anything but a default address are never detected.
+                    let skip_undetected =
+                        self.fmd.as_ref().is_some_and(FmdFilter::skips_undetected);
+                    if skip_trial_decrypt || (detected == Some(false) && skip_undetected) {
+                        continue;
+                    }
+
+                    let owned_note = self.wallets.iter().enumerate().find_map(|(index, wallet)| {
+                        payload.trial_decrypt(&wallet.fvk).map(|note| (index, note))
+                    });
+                    if let Some(filter) = self.fmd.as_mut() {
+                        match (detected, owned_note.is_some()) {
+                            // The clue matched one of our detection keys, but the note isn't ours.
+                            (Some(true), false) => filter.record_false_positive(),
+                            // The note is ours, but was sent to an address without a detection key.
+                            (Some(false), true) => filter.record_missed(),
+                            _ => {}
+                        }
+                    }
+                    if let Some(owned_note) = owned_note {
                         // It's safe to avoid recomputing the note commitment here because
                         // trial_decrypt checks that the decrypted data is consistent
-                        note_advice.insert(payload.note_commitment, note);
+                        note_advice.insert(payload.note_commitment, owned_note);
                     }
                 }
                 StatePayload::Swap { swap: payload, .. } => {
                     let swap_opt = (!skip_trial_decrypt)
-                        .then(|| payload.trial_decrypt(&self.fvk))
+                        .then(|| {
+                            self.wallets.iter().enumerate().find_map(|(index, wallet)| {
+                                payload.trial_decrypt(&wallet.fvk).map(|swap| (index, swap))
+                            })
+                        })
                         .flatten();
                     if let Some(swap) = swap_opt {
                         // It's safe to avoid recomputing the note commitment here because
@@ -300,7 +675,14 @@ impl ViewServer {
                 StatePayload::RolledUp { commitment, .. } => {
                     // Query the storage to find out if we have stored advice for this note commitment.
                     if let Some(note) = self.storage.read_advice(*commitment).await? {
-                        note_advice.insert(*commitment, note);
+                        // Advice is stored for swap outputs, so attribute the note to the wallet
+                        // controlling its address, falling back to the primary wallet.
+                        let index = self
+                            .wallets
+                            .iter()
+                            .position(|wallet| is_controlled_inner(&wallet.fvk, &note.address()))
+                            .unwrap_or_default();
+                        note_advice.insert(*commitment, (index, note));
                     }
                 }
             }
@@ -308,10 +690,9 @@ impl ViewServer {
 
         if note_advice.is_empty() && swap_advice.is_empty() {
             // If there are no notes we care about in this block, just insert the block root into the
-            // tree instead of processing each commitment individually
-            self.sct
-                .insert_block(block.block_root)
-                .expect("inserting a block root must succeed");
+            // tree instead of processing each commitment individually. The root comes from the
+            // compact block itself, so there is nothing to check it against.
+            self.sct.insert_block(block.block_root).map_err(sct_full)?;
         } else {
             // If we found at least one note for us in this block, we have to explicitly construct the
             // whole block in the SCT by inserting each commitment one at a time
@@ -324,46 +705,44 @@ impl ViewServer {
                     note_advice.get(payload.commitment()),
                     swap_advice.get(payload.commitment()),
                 ) {
-                    (Some(note), None) => {
-                        let position = self.sct.insert(Keep, *payload.commitment())?;
-
-                        let source = payload.source().clone();
-                        let nullifier = Nullifier::derive(
-                            self.fvk.nullifier_key(),
-                            position,
-                            payload.commitment(),
-                        );
-                        let address_index = self
-                            .fvk
-                            .incoming()
-                            .index_for_diversifier(note.diversifier());
-
-                        let note_record = SpendableNoteRecord {
-                            note_commitment: *payload.commitment(),
-                            height_spent: None,
-                            height_created: block.height,
-                            note: note.clone(),
-                            address_index,
-                            nullifier,
-                            position,
-                            source,
-                            return_address: None,
-                        };
-                        self.notes
-                            .insert(*payload.commitment(), note_record.clone());
-
-                        found_new_data = true;
+                    (Some((index, note)), None) => {
+                        let position = self
+                            .sct
+                            .insert(Keep, *payload.commitment())
+                            .map_err(sct_full)?;
+
+                        let wallet = &self.wallets[*index];
+                        let note_record =
+                            wallet.note_record(note, &payload, position, block.height);
+                        let pending = records
+                            .invoice(*index, &note_record.address_index)
+                            .or_else(|| wallet.invoices.get(&note_record.address_index));
+                        let invoice = record_invoice_payment(
+                            &self.wallet_data,
+                            &wallet.fvk.wallet_id(),
+                            pending,
+                            &note_record,
+                        )
+                        .await?;
+                        if let Some(invoice) = invoice {
+                            records.invoices.push((*index, invoice));
+                        }
+                        records.notes.push((*index, note_record));
                     }
-                    (None, Some(swap)) => {
-                        let position = self.sct.insert(Keep, *payload.commitment())?;
+                    (None, Some((index, swap))) => {
+                        let position = self
+                            .sct
+                            .insert(Keep, *payload.commitment())
+                            .map_err(sct_full)?;
                         let output_data = *block
                             .swap_outputs
                             .get(&swap.trading_pair)
                             .ok_or_else(|| anyhow::anyhow!("server gave invalid compact block"))?;
 
+                        let wallet = &self.wallets[*index];
                         let source = payload.source().clone();
                         let nullifier = Nullifier::derive(
-                            self.fvk.nullifier_key(),
+                            wallet.fvk.nullifier_key(),
                             position,
                             payload.commitment(),
                         );
@@ -384,9 +763,7 @@ impl ViewServer {
                             output_data,
                             height_claimed: None,
                         };
-                        self.swaps.insert(*payload.commitment(), swap_record);
-
-                        found_new_data = true;
+                        records.swaps.push((*index, swap_record));
                     }
                     (None, None) => {
                         // Don't remember this commitment; it wasn't ours, and
@@ -394,33 +771,59 @@ impl ViewServer {
                         // Just insert and forget
                         self.sct
                             .insert(tct::Witness::Forget, *payload.commitment())
-                            .expect("inserting a commitment must succeed");
+                            .map_err(sct_full)?;
+                    }
+                    (Some(_), Some(_)) => {
+                        // A well-formed block never has a note and a swap with the same
+                        // commitment, but a malicious or broken server could send one.
+                        return Err(WasmError::AmbiguousCommitment {
+                            height: block.height,
+                            commitment: *payload.commitment(),
+                        });
                     }
-                    (Some(_), Some(_)) => unreachable!("swap and note commitments are distinct"),
                 }
             }
 
             // End the block in the commitment tree
-            self.sct.end_block().expect("ending the block must succed");
+            let block_root = self.sct.end_block().map_err(sct_full)?;
+            self.check_root(block.height, "block", block_root == block.block_root)?;
         }
 
         // If we've also reached the end of the epoch, end the epoch in the commitment tree
-        if block.epoch_root.is_some() {
-            self.sct.end_epoch().expect("ending the epoch must succeed");
+        if let Some(expected_epoch_root) = block.epoch_root {
+            let epoch_root = self.sct.end_epoch().map_err(sct_full)?;
+            self.check_root(block.height, "epoch", epoch_root == expected_epoch_root)?;
         }
 
-        self.latest_height = block.height;
-
-        Ok(found_new_data)
+        Ok(records)
     }
 
-    /// Get new notes, swaps, SCT state updates
-    /// Function also clears state
-    /// Returns: `ScanBlockResult`
-    #[wasm_bindgen]
-    pub fn flush_updates(&mut self) -> WasmResult<JsValue> {
-        utils::set_panic_hook();
+    /// Hands the records of a fully applied block to their wallets and advances the scan height.
+    /// Returns: whether the block contained new notes or swaps for any wallet
+    fn commit_block(&mut self, height: u64, records: BlockRecords) -> bool {
+        let found_new_data = !records.is_empty();
 
+        for (index, record) in records.notes {
+            self.wallets[index]
+                .notes
+                .insert(record.note_commitment, record);
+        }
+        for (index, record) in records.swaps {
+            self.wallets[index]
+                .swaps
+                .insert(record.swap_commitment, record);
+        }
+        for (index, invoice) in records.invoices {
+            self.wallets[index]
+                .invoices
+                .insert(invoice.address_index, invoice);
+        }
+        self.latest_height = height;
+
+        found_new_data
+    }
+
+    fn take_sct_updates(&mut self) -> Updates {
         let sct_updates: Updates = self
             .sct
             .updates(
@@ -429,42 +832,68 @@ impl ViewServer {
             )
             .collect::<Updates>();
 
-        let updates = ScanBlockResult {
-            height: self.latest_height,
-            sct_updates: sct_updates.clone(),
-            new_notes: self.notes.clone().into_values().collect(),
-            new_swaps: self.swaps.clone().into_values().collect(),
-        };
-
-        self.notes = Default::default();
-        self.swaps = Default::default();
-
         self.last_position = sct_updates.set_position;
         self.last_forgotten = sct_updates.set_forgotten;
 
-        let serializer = Serializer::new().serialize_large_number_types_as_bigints(true);
-        let result = updates.serialize(&serializer)?;
-        Ok(result)
+        sct_updates
     }
 
-    /// SCT root can be compared with the root obtained by GRPC and verify that there is no divergence
-    /// Returns: `Uint8Array representing a Root`
-    #[wasm_bindgen]
-    pub fn get_sct_root(&mut self) -> WasmResult<Vec<u8>> {
-        utils::set_panic_hook();
+    fn take_wallet_updates(&mut self, index: usize, sct_updates: Updates) -> ScanBlockResult {
+        let wallet = &mut self.wallets[index];
 
-        let root = self.sct.root();
-        Ok(root.encode_to_vec())
+        ScanBlockResult {
+            height: self.latest_height,
+            wallet_id: wallet.fvk.wallet_id(),
+            sct_updates,
+            new_notes: std::mem::take(&mut wallet.notes).into_values().collect(),
+            new_swaps: std::mem::take(&mut wallet.swaps).into_values().collect(),
+            invoice_updates: std::mem::take(&mut wallet.invoices)
+                .into_values()
+                .map(Invoice::update)
+                .collect(),
+        }
     }
 
-    /// Checks if address is controlled by view server full viewing key
-    #[wasm_bindgen]
-    pub fn is_controlled_address(&self, address: &[u8]) -> WasmResult<bool> {
-        utils::set_panic_hook();
+    /// Ensures blocks are scanned strictly in order, one height at a time, and that nothing
+    /// more is scanned once the local SCT has diverged from the chain.
+    fn check_block_sequence(&self, height: u64) -> WasmResult<()> {
+        if let Some((height, root)) = self.diverged_at {
+            return Err(WasmError::SctDivergence { height, root });
+        }
 
-        let address: Address = Address::decode(address)?;
-        Ok(is_controlled_inner(&self.fvk, &address))
+        // `u64::MAX` marks a view server that has not scanned any block yet.
+        if self.latest_height != u64::MAX && height != self.latest_height + 1 {
+            return Err(WasmError::BlockOutOfSequence {
+                expected: self.latest_height + 1,
+                actual: height,
+            });
+        }
+
+        Ok(())
     }
+
+    /// Records the first height at which a locally computed root disagreed with the one in the
+    /// compact block. The tree cannot be trusted past this point, so the view server must be
+    /// reset from storage before scanning can resume.
+    fn check_root(&mut self, height: u64, root: &'static str, matches: bool) -> WasmResult<()> {
+        if matches {
+            return Ok(());
+        }
+
+        self.diverged_at.get_or_insert((height, root));
+        Err(WasmError::SctDivergence { height, root })
+    }
+}
+
+/// Decrypts the transaction memo with `fvk` and returns a view of its return address, or `None`
+/// if the transaction has no memo or the memo is not readable by `fvk`.
+pub fn memo_return_address(fvk: &FullViewingKey, tx: &Transaction) -> Option<AddressView> {
+    let memo = tx.decrypt_memo(fvk).ok()?;
+    Some(transparent::view_address(fvk, memo.return_address()))
+}
+
+fn sct_full(error: impl std::fmt::Display) -> WasmError {
+    WasmError::SctFull(error.to_string())
 }
 
 pub fn load_tree(stored_tree: StoredTree) -> Tree {
@@ -0,0 +1,101 @@
+use penumbra_keys::keys::AddressIndex;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use penumbra_wasm::address_book::set_address_label_inner;
+use penumbra_wasm::storage::{AddressLabel, LabelTarget};
+
+use crate::utils::fixtures::{fvk, storage};
+
+mod utils;
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+fn address_target(account: u32) -> LabelTarget {
+    let address = fvk().payment_address(AddressIndex::new(account)).0;
+    LabelTarget::Address(address.to_string())
+}
+
+#[wasm_bindgen_test]
+async fn labels_accounts_and_addresses() {
+    let storage = storage();
+
+    let account = AddressLabel {
+        target: LabelTarget::Account(1),
+        label: "Savings".to_string(),
+    };
+    let recipient = AddressLabel {
+        target: address_target(7),
+        label: "Alice".to_string(),
+    };
+    set_address_label_inner(&storage, account.clone())
+        .await
+        .unwrap();
+    set_address_label_inner(&storage, recipient.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        storage
+            .get_address_label(&LabelTarget::Account(1))
+            .await
+            .unwrap(),
+        Some(account.clone())
+    );
+    assert_eq!(
+        storage.get_address_label(&address_target(7)).await.unwrap(),
+        Some(recipient.clone())
+    );
+    assert_eq!(
+        storage
+            .get_address_label(&LabelTarget::Account(2))
+            .await
+            .unwrap(),
+        None
+    );
+
+    let labels = storage.get_address_labels().await.unwrap();
+    assert_eq!(labels.len(), 2);
+    assert!(labels.contains(&account));
+    assert!(labels.contains(&recipient));
+}
+
+#[wasm_bindgen_test]
+async fn relabelling_replaces_the_entry() {
+    let storage = storage();
+
+    for name in ["Old name", "  New name  "] {
+        let label = AddressLabel {
+            target: LabelTarget::Account(0),
+            label: name.to_string(),
+        };
+        set_address_label_inner(&storage, label).await.unwrap();
+    }
+
+    assert_eq!(
+        storage.get_address_labels().await.unwrap(),
+        vec![AddressLabel {
+            target: LabelTarget::Account(0),
+            label: "New name".to_string(),
+        }]
+    );
+}
+
+#[wasm_bindgen_test]
+async fn rejects_invalid_labels() {
+    let storage = storage();
+
+    let empty = AddressLabel {
+        target: LabelTarget::Account(0),
+        label: " ".to_string(),
+    };
+    let bad_address = AddressLabel {
+        target: LabelTarget::Address("penumbra1notanaddress".to_string()),
+        label: "Bob".to_string(),
+    };
+
+    assert!(set_address_label_inner(&storage, empty).await.is_err());
+    assert!(set_address_label_inner(&storage, bad_address)
+        .await
+        .is_err());
+    assert!(storage.get_address_labels().await.unwrap().is_empty());
+}
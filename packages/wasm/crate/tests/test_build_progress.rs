@@ -0,0 +1,173 @@
+use std::cell::Cell;
+
+use penumbra_auction::auction::dutch::{ActionDutchAuctionSchedule, DutchAuctionDescription};
+use penumbra_keys::FullViewingKey;
+use penumbra_proto::DomainType;
+use penumbra_stake::{DelegationToken, IdentityKey};
+use penumbra_tct as tct;
+use penumbra_transaction::{ActionPlan, TransactionPlan, WitnessData};
+use penumbra_wasm::build::{
+    build_action_inner, build_actions_with_progress_inner, BuildProgress, BuildStage,
+};
+use std::str::FromStr;
+
+use crate::utils::fixtures::{spend_key, staking_value, VALIDATOR};
+
+mod utils;
+
+fn wallet() -> FullViewingKey {
+    spend_key().full_viewing_key().clone()
+}
+
+/// A plan of auction schedules, which are built without proofs or proving keys.
+fn plan(auctions: u8) -> TransactionPlan {
+    let output_id = DelegationToken::from(IdentityKey::from_str(VALIDATOR).unwrap()).id();
+    let actions = (0..auctions)
+        .map(|nonce| {
+            ActionPlan::ActionDutchAuctionSchedule(ActionDutchAuctionSchedule {
+                description: DutchAuctionDescription {
+                    input: staking_value(1_000),
+                    output_id,
+                    max_output: 2_000u64.into(),
+                    min_output: 1_000u64.into(),
+                    start_height: 100,
+                    end_height: 200,
+                    step_count: 10,
+                    nonce: [nonce; 32],
+                },
+            })
+        })
+        .collect();
+
+    TransactionPlan {
+        actions,
+        ..Default::default()
+    }
+}
+
+fn witness() -> WitnessData {
+    WitnessData {
+        anchor: tct::Tree::new().root(),
+        state_commitment_proofs: Default::default(),
+    }
+}
+
+#[test]
+fn progress_is_reported_per_action() {
+    let fvk = wallet();
+    let plan = plan(2);
+    let mut reported = Vec::new();
+
+    let partial = build_actions_with_progress_inner(
+        &fvk,
+        &plan,
+        &witness(),
+        vec![],
+        |progress| {
+            reported.push(progress);
+            Ok(())
+        },
+        || false,
+    )
+    .unwrap();
+
+    assert!(partial.complete);
+    assert_eq!(partial.actions.len(), 2);
+    let stages: Vec<_> = reported
+        .iter()
+        .map(
+            |BuildProgress {
+                 index,
+                 total,
+                 stage,
+             }| {
+                assert_eq!(*total, 2);
+                (*index, *stage)
+            },
+        )
+        .collect();
+    assert_eq!(
+        stages,
+        vec![
+            (0, BuildStage::Planned),
+            (1, BuildStage::Planned),
+            (0, BuildStage::Proving),
+            (0, BuildStage::Proved),
+            (1, BuildStage::Proving),
+            (1, BuildStage::Proved),
+        ]
+    );
+}
+
+#[test]
+fn cancelled_builds_resume_where_they_stopped() {
+    let fvk = wallet();
+    let plan = plan(3);
+    let cancelled = Cell::new(false);
+
+    // Cancel as soon as the first action is done.
+    let partial = build_actions_with_progress_inner(
+        &fvk,
+        &plan,
+        &witness(),
+        vec![],
+        |progress| {
+            if progress.stage == BuildStage::Proved {
+                cancelled.set(true);
+            }
+            Ok(())
+        },
+        || cancelled.get(),
+    )
+    .unwrap();
+    assert!(!partial.complete);
+    assert_eq!(partial.actions.len(), 1);
+
+    let mut resumed_at = Vec::new();
+    let resumed = build_actions_with_progress_inner(
+        &fvk,
+        &plan,
+        &witness(),
+        partial.actions,
+        |progress| {
+            if progress.stage == BuildStage::Proving {
+                resumed_at.push(progress.index);
+            }
+            Ok(())
+        },
+        || false,
+    )
+    .unwrap();
+    assert!(resumed.complete);
+    assert_eq!(resumed_at, vec![1, 2]);
+
+    let expected: Vec<_> = plan
+        .actions
+        .iter()
+        .map(|action| {
+            build_action_inner(plan.clone(), action.clone(), fvk.clone(), witness())
+                .unwrap()
+                .encode_to_vec()
+        })
+        .collect();
+    let actions: Vec<_> = resumed.actions.iter().map(|a| a.encode_to_vec()).collect();
+    assert_eq!(actions, expected);
+}
+
+#[test]
+fn resuming_with_too_many_actions_fails() {
+    let fvk = wallet();
+    let complete =
+        build_actions_with_progress_inner(&fvk, &plan(2), &witness(), vec![], |_| Ok(()), || false)
+            .unwrap();
+
+    let result = build_actions_with_progress_inner(
+        &fvk,
+        &plan(1),
+        &witness(),
+        complete.actions,
+        |_| Ok(()),
+        || false,
+    );
+    assert!(result.is_err());
+}
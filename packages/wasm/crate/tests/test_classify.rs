@@ -0,0 +1,275 @@
+use penumbra_asset::{Value, ValueView, STAKING_TOKEN_ASSET_ID};
+use penumbra_keys::keys::AddressIndex;
+use penumbra_keys::{Address, FullViewingKey, PayloadKey};
+use penumbra_num::Amount;
+use penumbra_proof_params::GROTH16_PROOF_LENGTH_BYTES;
+use penumbra_proto::core::component::shielded_pool::v1::{ZkOutputProof, ZkSpendProof};
+use penumbra_proto::core::component::stake::v1 as stake_pb;
+use penumbra_sct::epoch::Epoch;
+use penumbra_shielded_pool::{
+    Note, NoteView, Output, OutputPlan, OutputProof, OutputView, Rseed, Spend, SpendPlan,
+    SpendProof, SpendView,
+};
+use penumbra_stake::rate::RateData;
+use penumbra_stake::{DelegationToken, IdentityKey};
+use penumbra_tct::Position;
+use penumbra_transaction::memo::{MemoCiphertext, MemoPlaintext};
+use penumbra_transaction::view::TransactionBodyView;
+use penumbra_transaction::{ActionView, MemoPlaintextView, MemoView, Transaction, TransactionView};
+use rand_core::OsRng;
+use std::str::FromStr;
+
+use penumbra_wasm::classify::{classify_transaction_inner, NetEffect, TransactionKind};
+use penumbra_wasm::error::WasmError;
+use penumbra_wasm::transparent::transparent_address;
+
+use crate::utils::fixtures::{fvk, staking_value, OTHER_FVK, VALIDATOR};
+
+mod utils;
+
+fn address(fvk: &FullViewingKey, account: u32) -> Address {
+    fvk.payment_address(AddressIndex::new(account)).0
+}
+
+/// How `viewer` sees a note of `value` sent to `address`.
+fn note_view(viewer: &FullViewingKey, address: &Address, value: Value) -> NoteView {
+    NoteView {
+        value: ValueView::UnknownAssetId {
+            amount: value.amount,
+            asset_id: value.asset_id,
+        },
+        rseed: Rseed::generate(&mut OsRng),
+        address: viewer.view_address(address.clone()),
+    }
+}
+
+/// A visible spend of a note of `value` sent to `address`. The spend is never proven or
+/// signed, since classification only looks at the view.
+fn spend(viewer: &FullViewingKey, address: &Address, value: Value) -> ActionView {
+    let note = Note::generate(&mut OsRng, address, value);
+    let spend = Spend {
+        body: SpendPlan::new(&mut OsRng, note, Position::from(0u64)).spend_body(viewer),
+        auth_sig: [0u8; 64].into(),
+        proof: SpendProof::try_from(ZkSpendProof {
+            inner: vec![0; GROTH16_PROOF_LENGTH_BYTES],
+        })
+        .unwrap(),
+    };
+    ActionView::Spend(SpendView::Visible {
+        spend,
+        note: note_view(viewer, address, value),
+    })
+}
+
+/// A visible, unproven output of `value` to `address`.
+fn output(viewer: &FullViewingKey, address: &Address, value: Value) -> ActionView {
+    let payload_key = PayloadKey::random_key(&mut OsRng);
+    let output = Output {
+        body: OutputPlan::new(&mut OsRng, value, address.clone())
+            .output_body(viewer.outgoing(), &payload_key),
+        proof: OutputProof::try_from(ZkOutputProof {
+            inner: vec![0; GROTH16_PROOF_LENGTH_BYTES],
+        })
+        .unwrap(),
+    };
+    ActionView::Output(OutputView::Visible {
+        output,
+        note: note_view(viewer, address, value),
+        payload_key,
+    })
+}
+
+/// A memo carrying `return_address`, as seen by `viewer`.
+fn memo_view(viewer: &FullViewingKey, return_address: &Address) -> MemoView {
+    let plaintext =
+        MemoPlaintext::new(return_address.clone(), "classify test".to_string()).unwrap();
+    MemoView::Visible {
+        plaintext: MemoPlaintextView {
+            return_address: viewer.view_address(return_address.clone()),
+            text: plaintext.text().to_string(),
+        },
+        ciphertext: MemoCiphertext::encrypt(PayloadKey::random_key(&mut OsRng), &plaintext)
+            .unwrap(),
+    }
+}
+
+fn transaction_view(action_views: Vec<ActionView>, memo_view: Option<MemoView>) -> TransactionView {
+    let tx = Transaction::default();
+    TransactionView {
+        body_view: TransactionBodyView {
+            action_views,
+            transaction_parameters: Default::default(),
+            detection_data: None,
+            memo_view,
+        },
+        binding_sig: tx.binding_sig,
+        anchor: tx.anchor,
+    }
+}
+
+fn net_effect(account: u32, amount: &str) -> NetEffect {
+    NetEffect {
+        account,
+        asset_id: *STAKING_TOKEN_ASSET_ID,
+        amount: amount.to_string(),
+    }
+}
+
+#[test]
+fn payments_classify_as_send_and_receive() {
+    let sender = fvk();
+    let receiver = FullViewingKey::from_str(OTHER_FVK).unwrap();
+    let sender_address = address(&sender, 0);
+    let receiver_address = address(&receiver, 0);
+
+    let sent = classify_transaction_inner(
+        &sender,
+        &transaction_view(
+            vec![
+                spend(&sender, &sender_address, staking_value(1_000u64)),
+                output(&sender, &receiver_address, staking_value(600u64)),
+                output(&sender, &sender_address, staking_value(400u64)),
+            ],
+            Some(memo_view(&sender, &sender_address)),
+        ),
+    )
+    .unwrap();
+    assert_eq!(sent.kind, TransactionKind::Send);
+    assert_eq!(sent.counterparties, vec![receiver_address.clone()]);
+    assert_eq!(sent.net_effects, vec![net_effect(0, "-600")]);
+    assert!(sent.fee.is_some());
+
+    // The receiver only sees its own output, and the sender's return address in the memo.
+    let received = classify_transaction_inner(
+        &receiver,
+        &transaction_view(
+            vec![output(&receiver, &receiver_address, staking_value(600u64))],
+            Some(memo_view(&receiver, &sender_address)),
+        ),
+    )
+    .unwrap();
+    assert_eq!(received.kind, TransactionKind::Receive);
+    assert_eq!(received.counterparties, vec![sender_address]);
+    assert_eq!(received.net_effects, vec![net_effect(0, "600")]);
+    assert!(received.fee.is_none());
+}
+
+#[test]
+fn transfers_between_accounts_classify_as_internal() {
+    let fvk = fvk();
+
+    let classification = classify_transaction_inner(
+        &fvk,
+        &transaction_view(
+            vec![
+                spend(&fvk, &address(&fvk, 0), staking_value(1_000u64)),
+                output(&fvk, &address(&fvk, 1), staking_value(1_000u64)),
+            ],
+            Some(memo_view(&fvk, &address(&fvk, 0))),
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(classification.kind, TransactionKind::InternalTransfer);
+    assert!(classification.counterparties.is_empty());
+    assert_eq!(
+        classification.net_effects,
+        vec![net_effect(0, "-1000"), net_effect(1, "1000")]
+    );
+}
+
+#[test]
+fn transparent_return_addresses_are_not_counterparties() {
+    let fvk = fvk();
+    let own_address = address(&fvk, 0);
+
+    // A note the wallet sent itself from elsewhere, say over IBC, with its t-addr to return to.
+    let classification = classify_transaction_inner(
+        &fvk,
+        &transaction_view(
+            vec![output(&fvk, &own_address, staking_value(1_000u64))],
+            Some(memo_view(&fvk, &transparent_address(&fvk).unwrap())),
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(classification.kind, TransactionKind::Receive);
+    assert!(classification.counterparties.is_empty());
+}
+
+#[test]
+fn delegations_classify_by_action() {
+    let fvk = fvk();
+    let identity_key = IdentityKey::from_str(VALIDATOR).unwrap();
+    let rate_data: RateData = stake_pb::RateData {
+        identity_key: Some(DelegationToken::from(identity_key).validator().into()),
+        validator_reward_rate: Some(Amount::from(0u64).into()),
+        validator_exchange_rate: Some(Amount::from(100_000_000u64).into()),
+        ..Default::default()
+    }
+    .try_into()
+    .unwrap();
+    let epoch = Epoch {
+        index: 1,
+        start_height: 0,
+    };
+
+    let classification = classify_transaction_inner(
+        &fvk,
+        &transaction_view(
+            vec![
+                spend(&fvk, &address(&fvk, 0), staking_value(1_000u64)),
+                ActionView::Delegate(rate_data.build_delegate(epoch, 1_000u64.into())),
+            ],
+            None,
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(classification.kind, TransactionKind::Delegate);
+    assert_eq!(classification.net_effects, vec![net_effect(0, "-1000")]);
+}
+
+#[test]
+fn amounts_beyond_i128_are_rejected() {
+    let fvk = fvk();
+    let own_address = address(&fvk, 0);
+    let value = Value {
+        amount: u128::MAX.into(),
+        asset_id: *STAKING_TOKEN_ASSET_ID,
+    };
+
+    let result = classify_transaction_inner(
+        &fvk,
+        &transaction_view(vec![output(&fvk, &own_address, value)], None),
+    );
+
+    assert!(matches!(
+        result,
+        Err(WasmError::NetEffectOverflow { account: 0, asset_id }) if asset_id == *STAKING_TOKEN_ASSET_ID
+    ));
+}
+
+#[test]
+fn net_effects_that_overflow_when_summed_are_rejected() {
+    let fvk = fvk();
+    let own_address = address(&fvk, 0);
+    let half = Value {
+        amount: (i128::MAX as u128 / 2 + 1).into(),
+        asset_id: *STAKING_TOKEN_ASSET_ID,
+    };
+
+    // Each output fits in an i128 on its own, but together they do not.
+    let result = classify_transaction_inner(
+        &fvk,
+        &transaction_view(
+            vec![
+                output(&fvk, &own_address, half),
+                output(&fvk, &own_address, half),
+            ],
+            None,
+        ),
+    );
+
+    assert!(matches!(result, Err(WasmError::NetEffectOverflow { .. })));
+}
@@ -0,0 +1,401 @@
+use decaf377::{Fq, Fr};
+use penumbra_asset::{Value, STAKING_TOKEN_ASSET_ID};
+use penumbra_dex::lp::plan::{PositionOpenPlan, PositionWithdrawPlan};
+use penumbra_dex::lp::position::Position as LiquidityPosition;
+use penumbra_dex::lp::Reserves;
+use penumbra_dex::swap::{SwapPlaintext, SwapPlan};
+use penumbra_dex::swap_claim::SwapClaimPlan;
+use penumbra_dex::{BatchSwapOutputData, DirectedTradingPair, PositionClose, TradingPair};
+use penumbra_fee::Fee;
+use penumbra_funding::liquidity_tournament::ActionLiquidityTournamentVotePlan;
+use penumbra_governance::{DelegatorVotePlan, Vote};
+use penumbra_proto::DomainType;
+use penumbra_sct::epoch::Epoch;
+use penumbra_shielded_pool::{Ics20Withdrawal, Note, OutputPlan, SpendPlan};
+use penumbra_stake::{
+    Delegate, DelegationToken, IdentityKey, Penalty, Undelegate, UndelegateClaimPlan,
+};
+use penumbra_tct::{Position, StateCommitment, Tree, Witness};
+use penumbra_transaction::{ActionPlan, AuthorizationData, TransactionPlan, WitnessData};
+use penumbra_wasm::build::build_serial_inner;
+use penumbra_wasm::keys::load_proving_key;
+use penumbra_wasm::tx::authorize_inner;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::utils::fixtures::{address, ics20_withdrawal, spend_key, staking_value, VALIDATOR};
+
+mod utils;
+
+fn load_proving_keys() {
+    let spend_key: &[u8] = include_bytes!("../../../keys/keys/spend_pk.bin");
+    let output_key: &[u8] = include_bytes!("../../../keys/keys/output_pk.bin");
+    let delegator_vote_key: &[u8] = include_bytes!("../../../keys/keys/delegator_vote_pk.bin");
+    let swap_key: &[u8] = include_bytes!("../../../keys/keys/swap_pk.bin");
+    let swap_claim_key: &[u8] = include_bytes!("../../../keys/keys/swapclaim_pk.bin");
+    let convert_key: &[u8] = include_bytes!("../../../keys/keys/convert_pk.bin");
+
+    load_proving_key(spend_key, "spend").expect("can load spend key");
+    load_proving_key(output_key, "output").expect("can load output key");
+    load_proving_key(delegator_vote_key, "delegatorVote").expect("can load delegator vote key");
+    load_proving_key(swap_key, "swap").expect("can load swap key");
+    load_proving_key(swap_claim_key, "swapClaim").expect("can load swap claim key");
+    load_proving_key(convert_key, "undelegateClaim").expect("can load convert key");
+}
+
+/// Compares `bytes` with the hex snapshot `tests/snapshots/<name>.hex`. A missing snapshot is a
+/// failure; set `UPDATE_SNAPSHOTS=1` to record or re-record snapshots after an intended change.
+fn assert_snapshot(name: &str, bytes: &[u8]) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots", name]
+        .iter()
+        .collect::<PathBuf>()
+        .with_extension("hex");
+    let actual = hex::encode(bytes);
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "{name} has no snapshot at {}; run with UPDATE_SNAPSHOTS=1 to record it",
+            path.display()
+        )
+    });
+    assert!(
+        expected.trim() == actual,
+        "{name} differs from its snapshot at {}",
+        path.display()
+    );
+}
+
+fn delegation_token() -> DelegationToken {
+    DelegationToken::from(IdentityKey::from_str(VALIDATOR).unwrap())
+}
+
+/// The commitments a plan spends or claims, and the plan itself, both drawn from `rng`.
+type Scenario = fn(&mut ChaCha20Rng, Position) -> (Vec<StateCommitment>, Vec<ActionPlan>);
+
+/// A scenario planned and authorized from one seed, with the SCT its notes were committed to.
+struct Authorized {
+    plan: TransactionPlan,
+    auth_data: AuthorizationData,
+    witness_data: WitnessData,
+}
+
+/// Plans and authorizes a scenario from `seed`. The commitments are inserted before the vote
+/// start position.
+fn authorize(scenario: Scenario, seed: u64) -> Authorized {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+
+    // Commitments are inserted at the start of epoch 0, and votes start at epoch 1.
+    let start_position = Position::from((1, 0, 0));
+    let (commitments, actions) = scenario(&mut rng, start_position);
+
+    let mut sct = Tree::new();
+    for commitment in &commitments {
+        sct.insert(Witness::Keep, *commitment).unwrap();
+    }
+    sct.end_epoch().unwrap();
+
+    let plan = TransactionPlan {
+        actions,
+        ..Default::default()
+    };
+    let auth_data = authorize_inner(&spend_key(), &plan, &mut rng).unwrap();
+    let witness_data = WitnessData {
+        anchor: sct.root(),
+        state_commitment_proofs: commitments
+            .iter()
+            .map(|commitment| (*commitment, sct.witness(*commitment).unwrap()))
+            .collect(),
+    };
+
+    Authorized {
+        plan,
+        auth_data,
+        witness_data,
+    }
+}
+
+/// Builds an authorized scenario, returning the encoded transaction.
+fn build(authorized: Authorized) -> Vec<u8> {
+    load_proving_keys();
+    build_serial_inner(
+        spend_key().full_viewing_key().clone(),
+        authorized.plan,
+        authorized.witness_data,
+        authorized.auth_data,
+    )
+    .unwrap()
+    .encode_to_vec()
+}
+
+fn assert_reproducible(name: &str, scenario: Scenario) {
+    let authorized = authorize(scenario, 1);
+    let reauthorized = authorize(scenario, 1);
+    let other = authorize(scenario, 2);
+
+    let plan = authorized.plan.encode_to_vec();
+    let auth_data = authorized.auth_data.encode_to_vec();
+    assert_eq!(
+        plan,
+        reauthorized.plan.encode_to_vec(),
+        "{name} plan is not reproducible"
+    );
+    assert_eq!(
+        auth_data,
+        reauthorized.auth_data.encode_to_vec(),
+        "{name} authorization is not reproducible"
+    );
+    assert_ne!(
+        plan,
+        other.plan.encode_to_vec(),
+        "{name} plan ignores the seed"
+    );
+    assert_snapshot(&format!("{name}_plan"), &plan);
+    assert_snapshot(&format!("{name}_authorization"), &auth_data);
+
+    // Proofs take their blinding factors from the plan and the binding signature is
+    // deterministic, so the transaction is reproducible once the plan and signatures are.
+    let tx = build(authorized);
+    assert_eq!(
+        tx,
+        build(reauthorized),
+        "{name} transaction is not reproducible"
+    );
+    assert_snapshot(&format!("{name}_transaction"), &tx);
+}
+
+#[test]
+fn spends_and_outputs_are_reproducible() {
+    assert_reproducible("spend_output", |rng, _| {
+        let note = Note::generate(rng, &address(), staking_value(1_000));
+        let actions = vec![
+            SpendPlan::new(rng, note.clone(), 0u64.into()).into(),
+            OutputPlan::new(rng, staking_value(1_000), address()).into(),
+        ];
+        (vec![note.commit()], actions)
+    });
+}
+
+#[test]
+fn swaps_are_reproducible() {
+    assert_reproducible("swap", |rng, _| {
+        let note = Note::generate(rng, &address(), staking_value(100));
+        let swap_plaintext = SwapPlaintext::new(
+            rng,
+            TradingPair::new(*STAKING_TOKEN_ASSET_ID, delegation_token().id()),
+            100u64.into(),
+            0u64.into(),
+            Fee::from_staking_token_amount(0u64.into()),
+            address(),
+        );
+        let actions = vec![
+            SpendPlan::new(rng, note.clone(), 0u64.into()).into(),
+            SwapPlan::new(rng, swap_plaintext).into(),
+        ];
+        (vec![note.commit()], actions)
+    });
+}
+
+#[test]
+fn undelegate_claims_are_reproducible() {
+    assert_reproducible("undelegate_claim", |rng, _| {
+        let claim = UndelegateClaimPlan {
+            validator_identity: IdentityKey::from_str(VALIDATOR).unwrap(),
+            unbonding_start_height: 100,
+            penalty: Penalty::from_percent(0),
+            unbonding_amount: 1_000u64.into(),
+            balance_blinding: Fr::rand(rng),
+            proof_blinding_r: Fq::rand(rng),
+            proof_blinding_s: Fq::rand(rng),
+        };
+        (vec![], vec![ActionPlan::UndelegateClaim(claim)])
+    });
+}
+
+#[test]
+fn delegator_votes_are_reproducible() {
+    assert_reproducible("delegator_vote", |rng, start_position| {
+        let value = Value {
+            amount: 1_000u64.into(),
+            asset_id: delegation_token().id(),
+        };
+        let note = Note::generate(rng, &address(), value);
+        let vote = DelegatorVotePlan::new(
+            rng,
+            1,
+            start_position,
+            Vote::Yes,
+            note.clone(),
+            0u64.into(),
+            1_000u64.into(),
+        );
+        (vec![note.commit()], vec![ActionPlan::DelegatorVote(vote)])
+    });
+}
+
+#[test]
+fn liquidity_tournament_votes_are_reproducible() {
+    assert_reproducible("liquidity_tournament_vote", |rng, start_position| {
+        let value = Value {
+            amount: 1_000u64.into(),
+            asset_id: delegation_token().id(),
+        };
+        let note = Note::generate(rng, &address(), value);
+        let vote = ActionLiquidityTournamentVotePlan::new(
+            rng,
+            delegation_token().denom().base_denom(),
+            address(),
+            note.clone(),
+            0u64.into(),
+            start_position,
+        );
+        (vec![note.commit()], vec![vote.into()])
+    });
+}
+
+#[test]
+fn swap_claims_are_reproducible() {
+    assert_reproducible("swap_claim", |rng, _| {
+        let trading_pair = TradingPair::new(*STAKING_TOKEN_ASSET_ID, delegation_token().id());
+        let swap_plaintext = SwapPlaintext::new(
+            rng,
+            trading_pair,
+            100u64.into(),
+            0u64.into(),
+            Fee::from_staking_token_amount(0u64.into()),
+            address(),
+        );
+        let swap_commitment = swap_plaintext.swap_commitment();
+        // The swap is the first commitment of block 0, so the batch output data for that
+        // block has the default position prefix.
+        let claim = SwapClaimPlan {
+            swap_plaintext,
+            position: 0u64.into(),
+            output_data: BatchSwapOutputData {
+                delta_1: 100u64.into(),
+                delta_2: 0u64.into(),
+                lambda_1: 0u64.into(),
+                lambda_2: 50u64.into(),
+                unfilled_1: 0u64.into(),
+                unfilled_2: 0u64.into(),
+                height: 0,
+                trading_pair,
+                sct_position_prefix: Default::default(),
+            },
+            epoch_duration: 100,
+            proof_blinding_r: Fq::rand(rng),
+            proof_blinding_s: Fq::rand(rng),
+        };
+        (vec![swap_commitment], vec![ActionPlan::SwapClaim(claim)])
+    });
+}
+
+#[test]
+fn delegations_are_reproducible() {
+    assert_reproducible("delegate", |rng, _| {
+        let note = Note::generate(rng, &address(), staking_value(1_000));
+        let delegation = Value {
+            amount: 1_000u64.into(),
+            asset_id: delegation_token().id(),
+        };
+        let actions = vec![
+            SpendPlan::new(rng, note.clone(), 0u64.into()).into(),
+            ActionPlan::Delegate(Delegate {
+                validator_identity: IdentityKey::from_str(VALIDATOR).unwrap(),
+                epoch_index: 1,
+                unbonded_amount: 1_000u64.into(),
+                delegation_amount: 1_000u64.into(),
+            }),
+            OutputPlan::new(rng, delegation, address()).into(),
+        ];
+        (vec![note.commit()], actions)
+    });
+}
+
+#[test]
+fn undelegations_are_reproducible() {
+    assert_reproducible("undelegate", |rng, _| {
+        let value = Value {
+            amount: 1_000u64.into(),
+            asset_id: delegation_token().id(),
+        };
+        let note = Note::generate(rng, &address(), value);
+        let undelegate = Undelegate {
+            validator_identity: IdentityKey::from_str(VALIDATOR).unwrap(),
+            from_epoch: Epoch {
+                index: 1,
+                start_height: 100,
+            },
+            unbonded_amount: 1_000u64.into(),
+            delegation_amount: 1_000u64.into(),
+        };
+        let unbonding = Value {
+            amount: 1_000u64.into(),
+            asset_id: undelegate.unbonding_token().id(),
+        };
+        let actions = vec![
+            SpendPlan::new(rng, note.clone(), 0u64.into()).into(),
+            ActionPlan::Undelegate(undelegate),
+            OutputPlan::new(rng, unbonding, address()).into(),
+        ];
+        (vec![note.commit()], actions)
+    });
+}
+
+#[test]
+fn ics20_withdrawals_are_reproducible() {
+    assert_reproducible("ics20_withdrawal", |rng, _| {
+        let note = Note::generate(rng, &address(), staking_value(1_000));
+        let withdrawal = ics20_withdrawal(1_000, Some(&address()));
+        let actions = vec![
+            SpendPlan::new(rng, note.clone(), 0u64.into()).into(),
+            ActionPlan::Ics20Withdrawal(Ics20Withdrawal::try_from(withdrawal).unwrap()),
+        ];
+        (vec![note.commit()], actions)
+    });
+}
+
+#[test]
+fn position_actions_are_reproducible() {
+    assert_reproducible("position", |rng, _| {
+        let note = Note::generate(rng, &address(), staking_value(1_000));
+        let reserves = Reserves {
+            r1: 1_000u64.into(),
+            r2: 0u64.into(),
+        };
+        // The position nonce is drawn from the seed.
+        let position = LiquidityPosition::new(
+            &mut *rng,
+            DirectedTradingPair::new(*STAKING_TOKEN_ASSET_ID, delegation_token().id()),
+            30,
+            1u64.into(),
+            1u64.into(),
+            reserves.clone(),
+        );
+        let position_id = position.id();
+        let pair = position.phi.pair;
+        let actions = vec![
+            SpendPlan::new(rng, note.clone(), 0u64.into()).into(),
+            ActionPlan::PositionOpen(PositionOpenPlan {
+                position,
+                metadata: None,
+            }),
+            ActionPlan::PositionClose(PositionClose { position_id }),
+            ActionPlan::PositionWithdraw(PositionWithdrawPlan {
+                reserves,
+                position_id,
+                pair,
+                sequence: 0,
+                rewards: vec![],
+            }),
+        ];
+        (vec![note.commit()], actions)
+    });
+}
@@ -0,0 +1,172 @@
+use decaf377::Fr;
+use penumbra_keys::keys::{AddressIndex, Bip44Path, SeedPhrase, SpendKey};
+use penumbra_shielded_pool::{Note, OutputPlan, SpendPlan};
+use penumbra_tct::Position;
+use penumbra_transaction::{ActionPlan, TransactionPlan};
+use penumbra_wasm::error::WasmError;
+use penumbra_wasm::tx::{
+    assemble_authorization_inner, authorization_payload_inner, ExternalSignatures, SigningPayload,
+};
+use rand_core::OsRng;
+
+use crate::utils::fixtures::{spend_key, staking_value};
+
+mod utils;
+
+/// Stands in for a hardware wallet or custodian: signs a payload with a spend key it holds.
+fn software_sign(spend_key: &SpendKey, payload: &SigningPayload) -> ExternalSignatures {
+    let effect_hash = hex::decode(&payload.effect_hash).unwrap();
+    let sign = |randomizers: &[String]| -> Vec<String> {
+        randomizers
+            .iter()
+            .map(|randomizer| {
+                let bytes: [u8; 32] = hex::decode(randomizer).unwrap().try_into().unwrap();
+                let randomizer = Fr::from_bytes_checked(&bytes).unwrap();
+                let signature = spend_key
+                    .spend_auth_key()
+                    .randomize(&randomizer)
+                    .sign(OsRng, &effect_hash);
+                hex::encode(signature.to_bytes())
+            })
+            .collect()
+    };
+
+    ExternalSignatures {
+        spend_auths: sign(&payload.spend_randomizers),
+        delegator_vote_auths: sign(&payload.delegator_vote_randomizers),
+        lqt_vote_auths: sign(&payload.lqt_vote_randomizers),
+    }
+}
+
+fn plan(spend_key: &SpendKey) -> TransactionPlan {
+    let address = spend_key
+        .full_viewing_key()
+        .payment_address(AddressIndex::new(0))
+        .0;
+    let note = Note::generate(&mut OsRng, &address, staking_value(1_000));
+
+    TransactionPlan {
+        actions: vec![
+            ActionPlan::Spend(SpendPlan::new(
+                &mut OsRng,
+                note.clone(),
+                Position::from(0u64),
+            )),
+            ActionPlan::Spend(SpendPlan::new(&mut OsRng, note, Position::from(1u64))),
+            ActionPlan::Output(OutputPlan::new(&mut OsRng, staking_value(1_000), address)),
+        ],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn payload_has_a_randomizer_per_signed_action() {
+    let spend_key = spend_key();
+    let plan = plan(&spend_key);
+
+    let payload = authorization_payload_inner(spend_key.full_viewing_key(), &plan).unwrap();
+
+    assert_eq!(
+        payload.effect_hash,
+        hex::encode(
+            plan.effect_hash(spend_key.full_viewing_key())
+                .unwrap()
+                .as_ref()
+        )
+    );
+    assert_eq!(payload.spend_randomizers.len(), 2);
+    assert!(payload.delegator_vote_randomizers.is_empty());
+    assert!(payload.lqt_vote_randomizers.is_empty());
+}
+
+#[test]
+fn external_signatures_assemble_into_authorization_data() {
+    let spend_key = spend_key();
+    let fvk = spend_key.full_viewing_key();
+    let plan = plan(&spend_key);
+
+    let payload = authorization_payload_inner(fvk, &plan).unwrap();
+    let signatures = software_sign(&spend_key, &payload);
+    let auth_data = assemble_authorization_inner(fvk, &plan, &signatures).unwrap();
+
+    assert_eq!(auth_data.effect_hash, Some(plan.effect_hash(fvk).unwrap()));
+    assert_eq!(auth_data.spend_auths.len(), 2);
+    assert!(auth_data.delegator_vote_auths.is_empty());
+}
+
+#[test]
+fn signatures_from_another_key_are_rejected() {
+    let spend_key = spend_key();
+    let fvk = spend_key.full_viewing_key();
+    let plan = plan(&spend_key);
+    let other_key =
+        SpendKey::from_seed_phrase_bip44(SeedPhrase::generate(OsRng), &Bip44Path::new(0));
+
+    let payload = authorization_payload_inner(fvk, &plan).unwrap();
+    let signatures = software_sign(&other_key, &payload);
+    let result = assemble_authorization_inner(fvk, &plan, &signatures);
+
+    assert!(matches!(
+        result,
+        Err(WasmError::InvalidAuthorization {
+            kind: "spend",
+            index: 0
+        })
+    ));
+}
+
+#[test]
+fn signatures_in_the_wrong_order_are_rejected() {
+    let spend_key = spend_key();
+    let fvk = spend_key.full_viewing_key();
+    let plan = plan(&spend_key);
+
+    let payload = authorization_payload_inner(fvk, &plan).unwrap();
+    let mut signatures = software_sign(&spend_key, &payload);
+    signatures.spend_auths.reverse();
+
+    assert!(matches!(
+        assemble_authorization_inner(fvk, &plan, &signatures),
+        Err(WasmError::InvalidAuthorization { kind: "spend", .. })
+    ));
+}
+
+#[test]
+fn missing_signatures_are_rejected() {
+    let spend_key = spend_key();
+    let fvk = spend_key.full_viewing_key();
+    let plan = plan(&spend_key);
+
+    let payload = authorization_payload_inner(fvk, &plan).unwrap();
+    let mut signatures = software_sign(&spend_key, &payload);
+    signatures.spend_auths.pop();
+
+    assert!(matches!(
+        assemble_authorization_inner(fvk, &plan, &signatures),
+        Err(WasmError::AuthorizationCount {
+            kind: "spend",
+            expected: 2,
+            actual: 1
+        })
+    ));
+}
+
+#[test]
+fn malformed_signatures_are_rejected() {
+    let spend_key = spend_key();
+    let fvk = spend_key.full_viewing_key();
+    let plan = plan(&spend_key);
+
+    let signatures = ExternalSignatures {
+        spend_auths: vec!["not hex".to_string(), "abcd".to_string()],
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        assemble_authorization_inner(fvk, &plan, &signatures),
+        Err(WasmError::InvalidAuthorization {
+            kind: "spend",
+            index: 0
+        })
+    ));
+}
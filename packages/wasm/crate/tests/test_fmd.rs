@@ -0,0 +1,112 @@
+use decaf377_fmd::{Clue, Precision};
+use penumbra_keys::keys::AddressIndex;
+use penumbra_keys::{Address, FullViewingKey};
+use penumbra_shielded_pool::fmd::Parameters;
+use rand_core::OsRng;
+use std::str::FromStr;
+
+use penumbra_wasm::fmd::FmdFilter;
+
+use crate::utils::fixtures::{fvk, OTHER_FVK};
+
+mod utils;
+
+fn params() -> Parameters {
+    Parameters {
+        precision: Default::default(),
+        as_of_block_height: 0,
+    }
+}
+
+/// A clue for `address`, created from the address's own clue key.
+fn clue(address: &Address, precision_bits: u8) -> Clue {
+    address
+        .clue_key()
+        .expand()
+        .unwrap()
+        .create_clue(Precision::new(precision_bits).unwrap(), OsRng)
+        .unwrap()
+}
+
+#[test]
+fn detects_clues_for_own_accounts() {
+    let fvk = fvk();
+    let mut filter = FmdFilter::new(params(), 2, true);
+    filter.add_wallet(&fvk);
+
+    let (address, _) = fvk.payment_address(AddressIndex::new(1));
+    let clue = clue(&address, 16);
+
+    assert!(filter.detects(&clue));
+    assert_eq!(filter.stats().detected, 1);
+}
+
+#[test]
+fn skips_clues_for_accounts_without_detection_keys() {
+    let fvk = fvk();
+    let mut filter = FmdFilter::new(params(), 2, true);
+    filter.add_wallet(&fvk);
+
+    // Account 7 is outside the range the filter derived detection keys for.
+    let (address, _) = fvk.payment_address(AddressIndex::new(7));
+    let clue = clue(&address, 24);
+
+    assert!(!filter.detects(&clue));
+    assert_eq!(filter.stats().examined, 1);
+    assert_eq!(filter.stats().detected, 0);
+}
+
+#[test]
+fn detects_clues_for_every_wallet() {
+    let fvk = fvk();
+    let other_fvk = FullViewingKey::from_str(OTHER_FVK).unwrap();
+    let mut filter = FmdFilter::new(params(), 1, true);
+    filter.add_wallet(&fvk);
+    filter.add_wallet(&other_fvk);
+
+    for wallet in [&fvk, &other_fvk] {
+        let (address, _) = wallet.payment_address(AddressIndex::new(0));
+        let clue = clue(&address, 16);
+        assert!(filter.detects(&clue));
+    }
+}
+
+#[test]
+fn reports_false_positive_rate() {
+    let fvk = fvk();
+    let mut filter = FmdFilter::new(params(), 1, true);
+    filter.add_wallet(&fvk);
+
+    // With zero bits of precision every clue matches, so every detection is a false positive.
+    let (foreign_address, _) = fvk.payment_address(AddressIndex::new(3));
+    for _ in 0..4 {
+        let clue = clue(&foreign_address, 0);
+        assert!(filter.detects(&clue));
+        filter.record_false_positive();
+    }
+
+    let stats = filter.stats();
+    assert_eq!(stats.examined, 4);
+    assert_eq!(stats.false_positives, 4);
+    assert_eq!(stats.observed_false_positive_rate, 1.0);
+    assert_eq!(stats.expected_false_positive_rate, 1.0);
+}
+
+#[test]
+fn reports_missed_notes_apart_from_negatives() {
+    let fvk = fvk();
+    let mut filter = FmdFilter::new(params(), 1, false);
+    filter.add_wallet(&fvk);
+    assert!(!filter.skips_undetected());
+
+    // One-time addresses of the wallet have no detection key.
+    let (address, _) = fvk.ephemeral_address(OsRng, AddressIndex::new(0));
+    let clue = clue(&address, 24);
+    assert!(!filter.detects(&clue));
+    filter.record_missed();
+
+    let stats = filter.stats();
+    assert_eq!(stats.examined, 1);
+    assert_eq!(stats.missed, 1);
+    assert_eq!(stats.observed_false_positive_rate, 0.0);
+}
@@ -0,0 +1,134 @@
+use penumbra_keys::keys::AddressIndex;
+use penumbra_keys::Address;
+use std::str::FromStr;
+
+use penumbra_wasm::forwarding::{
+    forwarding_address_inner, penumbra_forwarding_address, ForwardingChain, ForwardingIndex,
+};
+use penumbra_wasm::keys::forwarding_addr_inner;
+
+use crate::utils::fixtures::fvk;
+
+mod utils;
+
+fn index(chain: ForwardingChain, sequence: u16) -> ForwardingIndex {
+    ForwardingIndex {
+        chain,
+        account: 3,
+        sequence,
+    }
+}
+
+#[test]
+fn noble_keeps_its_randomizer_scheme() {
+    let mut randomizer = [0xff; 12];
+    randomizer[10..].copy_from_slice(&1234u16.to_le_bytes());
+    let expected = fvk()
+        .payment_address(AddressIndex {
+            account: 3,
+            randomizer,
+        })
+        .0;
+
+    assert_eq!(
+        penumbra_forwarding_address(&fvk(), index(ForwardingChain::Noble, 1234)),
+        expected
+    );
+    assert_eq!(forwarding_addr_inner(1234, Some(3), &fvk()), expected);
+}
+
+#[test]
+fn forwarding_addresses_differ_per_chain() {
+    let addresses: Vec<Address> = ForwardingChain::ALL
+        .into_iter()
+        .map(|chain| penumbra_forwarding_address(&fvk(), index(chain, 7)))
+        .collect();
+
+    for (i, address) in addresses.iter().enumerate() {
+        assert!(!addresses[i + 1..].contains(address));
+    }
+}
+
+#[test]
+fn forwarding_addresses_are_recognized() {
+    let fvk = fvk();
+    for chain in ForwardingChain::ALL {
+        for sequence in [0, 1234, u16::MAX] {
+            let expected = index(chain, sequence);
+            let address = penumbra_forwarding_address(&fvk, expected);
+
+            let address_index = fvk.address_index(&address).unwrap();
+            assert_eq!(
+                ForwardingIndex::from_address_index(&address_index),
+                Some(expected)
+            );
+        }
+    }
+}
+
+#[test]
+fn other_addresses_are_not_forwarding_addresses() {
+    let ephemeral = fvk()
+        .ephemeral_address(rand_core::OsRng, AddressIndex::new(0))
+        .0;
+    let ephemeral_index = fvk().address_index(&ephemeral).unwrap();
+
+    assert_eq!(
+        ForwardingIndex::from_address_index(&AddressIndex::new(0)),
+        None
+    );
+    assert_eq!(ForwardingIndex::from_address_index(&ephemeral_index), None);
+}
+
+#[test]
+fn encodings_match_the_counterparty_chain() {
+    let fvk = fvk();
+
+    let noble =
+        forwarding_address_inner(&fvk, index(ForwardingChain::Noble, 1), "channel-4").unwrap();
+    let address = Address::try_from(noble.address.clone()).unwrap();
+    assert_eq!(
+        noble.encoding,
+        address.noble_forwarding_address("channel-4").to_string()
+    );
+
+    let packet_forward =
+        forwarding_address_inner(&fvk, index(ForwardingChain::PacketForward, 1), "channel-4")
+            .unwrap();
+    let address = Address::try_from(packet_forward.address.clone()).unwrap();
+    let memo: serde_json::Value = serde_json::from_str(&packet_forward.encoding).unwrap();
+    assert_eq!(
+        memo,
+        serde_json::json!({
+            "forward": {
+                "receiver": address.to_string(),
+                "port": "transfer",
+                "channel": "channel-4",
+            }
+        })
+    );
+
+    let ibc = forwarding_address_inner(&fvk, index(ForwardingChain::Ibc, 1), "channel-4").unwrap();
+    let address = Address::try_from(ibc.address.clone()).unwrap();
+    assert_eq!(ibc.encoding, address.compat_encoding());
+    assert!(ibc.encoding.starts_with("penumbracompat1"));
+}
+
+#[test]
+fn packet_forward_memos_need_a_channel_id() {
+    let result = forwarding_address_inner(
+        &fvk(),
+        index(ForwardingChain::PacketForward, 1),
+        r#"channel-4","receiver":"cosmos1"#,
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn chains_round_trip_through_their_names() {
+    for chain in ForwardingChain::ALL {
+        assert_eq!(ForwardingChain::from_str(chain.as_str()).unwrap(), chain);
+    }
+    assert!(ForwardingChain::from_str("osmosis").is_err());
+}
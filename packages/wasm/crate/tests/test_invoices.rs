@@ -0,0 +1,171 @@
+use penumbra_asset::Value;
+use penumbra_keys::keys::AddressIndex;
+use penumbra_keys::Address;
+use penumbra_proto::DomainType;
+use penumbra_sct::{CommitmentSource, Nullifier};
+use penumbra_shielded_pool::Note;
+use penumbra_stake::{DelegationToken, IdentityKey};
+use rand_core::OsRng;
+use std::str::FromStr;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use penumbra_wasm::invoice::{
+    create_invoice_inner, get_invoice_address, get_invoice_status_inner, record_invoice_payment,
+    InvoiceState,
+};
+use penumbra_wasm::note_record::SpendableNoteRecord;
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+use crate::utils::fixtures::{fvk, staking_value, storage, VALIDATOR};
+
+mod utils;
+
+/// A note record as the view server would produce it for a payment to `address`.
+fn received_note(address: &Address, value: Value, position: u64) -> SpendableNoteRecord {
+    let fvk = fvk();
+    let note = Note::generate(&mut OsRng, address, value);
+    let position = position.into();
+    SpendableNoteRecord {
+        note_commitment: note.commit(),
+        nullifier: Nullifier::derive(fvk.nullifier_key(), position, &note.commit()),
+        address_index: fvk.incoming().index_for_diversifier(address.diversifier()),
+        note,
+        height_created: 1,
+        height_spent: None,
+        position,
+        source: CommitmentSource::Genesis,
+        return_address: None,
+    }
+}
+
+#[wasm_bindgen_test]
+fn invoice_addresses_are_deterministic() {
+    let fvk_bytes = fvk().encode_to_vec();
+
+    let first = get_invoice_address(&fvk_bytes, 0, "invoice-1").unwrap();
+    let again = get_invoice_address(&fvk_bytes, 0, "invoice-1").unwrap();
+    let other_invoice = get_invoice_address(&fvk_bytes, 0, "invoice-2").unwrap();
+    let other_account = get_invoice_address(&fvk_bytes, 1, "invoice-1").unwrap();
+
+    assert_eq!(first, again);
+    assert_ne!(first, other_invoice);
+    assert_ne!(first, other_account);
+    assert!(get_invoice_address(&fvk_bytes, 0, "").is_err());
+
+    let address = Address::decode(first.as_slice()).unwrap();
+    let index = fvk().address_index(&address).unwrap();
+    assert_eq!(index.account, 0);
+    assert_ne!(index, AddressIndex::new(0));
+}
+
+#[wasm_bindgen_test]
+async fn invoices_move_from_unpaid_to_paid() {
+    let storage = storage();
+    let fvk = fvk();
+    let address = create_invoice_inner(&storage, &fvk, 2, "order-42", staking_value(1_000))
+        .await
+        .unwrap();
+
+    let status = get_invoice_status_inner(&storage, &fvk, 2, "order-42")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(status.state, InvoiceState::Unpaid);
+
+    let first_payment = received_note(&address, staking_value(400), 0);
+    let invoice = record_invoice_payment(&storage, &fvk.wallet_id(), None, &first_payment)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(invoice.state(), InvoiceState::Partial);
+    assert_eq!(invoice.received, 400u64.into());
+
+    // Nothing is saved until the scan is flushed.
+    let stored = get_invoice_status_inner(&storage, &fvk, 2, "order-42")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.state, InvoiceState::Unpaid);
+
+    // Scanning the same note again does not credit it twice.
+    let rescanned =
+        record_invoice_payment(&storage, &fvk.wallet_id(), Some(&invoice), &first_payment)
+            .await
+            .unwrap();
+    assert!(rescanned.is_none());
+
+    let second_payment = received_note(&address, staking_value(600), 1);
+    let invoice =
+        record_invoice_payment(&storage, &fvk.wallet_id(), Some(&invoice), &second_payment)
+            .await
+            .unwrap()
+            .unwrap();
+    assert_eq!(invoice.state(), InvoiceState::Paid);
+    assert_eq!(invoice.received, 1_000u64.into());
+
+    storage.save_invoice(&invoice).await.unwrap();
+    let stored = get_invoice_status_inner(&storage, &fvk, 2, "order-42")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored, invoice.clone().status());
+
+    // Once saved, a rescan of either payment is still not credited again.
+    for payment in [first_payment, second_payment] {
+        let rescanned = record_invoice_payment(&storage, &fvk.wallet_id(), None, &payment)
+            .await
+            .unwrap();
+        assert!(rescanned.is_none());
+    }
+}
+
+#[wasm_bindgen_test]
+async fn unrelated_notes_do_not_pay_invoices() {
+    let storage = storage();
+    let fvk = fvk();
+    let address = create_invoice_inner(&storage, &fvk, 0, "order-7", staking_value(1_000))
+        .await
+        .unwrap();
+
+    let other_asset = Value {
+        amount: 1_000u64.into(),
+        asset_id: DelegationToken::from(IdentityKey::from_str(VALIDATOR).unwrap()).id(),
+    };
+    let wrong_asset = received_note(&address, other_asset, 0);
+    let default_address = fvk.payment_address(AddressIndex::new(0)).0;
+    let wrong_address = received_note(&default_address, staking_value(1_000), 1);
+
+    for note in [wrong_asset, wrong_address] {
+        let status = record_invoice_payment(&storage, &fvk.wallet_id(), None, &note)
+            .await
+            .unwrap();
+        assert!(status.is_none());
+    }
+
+    let status = get_invoice_status_inner(&storage, &fvk, 0, "order-7")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(status.state, InvoiceState::Unpaid);
+}
+
+#[wasm_bindgen_test]
+async fn invoice_ids_are_unique_per_account() {
+    let storage = storage();
+    let fvk = fvk();
+
+    create_invoice_inner(&storage, &fvk, 0, "order-1", staking_value(10))
+        .await
+        .unwrap();
+    assert!(
+        create_invoice_inner(&storage, &fvk, 0, "order-1", staking_value(20))
+            .await
+            .is_err()
+    );
+    create_invoice_inner(&storage, &fvk, 1, "order-1", staking_value(20))
+        .await
+        .unwrap();
+
+    assert_eq!(storage.get_invoices().await.unwrap().len(), 2);
+}
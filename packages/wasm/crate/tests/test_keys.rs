@@ -3,11 +3,14 @@ extern crate core;
 use penumbra_keys::keys::{
     AddressIndex, Bip44Path, SeedPhrase, SpendKey, SpendKeyBytes, WalletId, SPENDKEY_LEN_BYTES,
 };
-use penumbra_keys::{Address, FullViewingKey};
+use penumbra_keys::{Address, AddressView, FullViewingKey};
 use penumbra_proto::{DomainType, Message};
+use penumbra_wasm::error::WasmError;
 use penumbra_wasm::keys::{
-    forwarding_addr_inner, generate_spend_key, get_address_by_index, get_full_viewing_key,
-    get_wallet_id, is_controlled_address,
+    address_view, derive_addresses_inner, export_full_viewing_key, export_spend_key,
+    forwarding_addr_inner, generate_seed_phrase, generate_spend_key,
+    generate_spend_key_for_account, get_address_by_index, get_full_viewing_key, get_wallet_id,
+    import_full_viewing_key, import_spend_key, is_controlled_address, validate_seed_phrase,
 };
 use rand_core::OsRng;
 use std::str::FromStr;
@@ -181,3 +184,167 @@ fn test_forwarding_addr_different_fvks() {
     // Check that the addresses are different
     assert_ne!(address1, address2);
 }
+
+#[test]
+fn account_zero_is_the_default_spend_key() {
+    assert_eq!(
+        generate_spend_key_for_account(TEST_SEED_PHRASE, 0).unwrap(),
+        generate_spend_key(TEST_SEED_PHRASE).unwrap()
+    );
+}
+
+#[test]
+fn accounts_derive_their_own_spend_keys() {
+    let account_1 = generate_spend_key_for_account(TEST_SEED_PHRASE, 1).unwrap();
+    let expected = SpendKey::from_seed_phrase_bip44(
+        SeedPhrase::from_str(TEST_SEED_PHRASE).unwrap(),
+        &Bip44Path::new(1),
+    );
+
+    assert_eq!(account_1, expected.encode_to_vec());
+    assert_ne!(account_1, generate_spend_key(TEST_SEED_PHRASE).unwrap());
+}
+
+#[test]
+fn generated_seed_phrases_validate() {
+    for word_count in [12, 24] {
+        let seed_phrase = generate_seed_phrase(word_count).unwrap();
+        assert_eq!(seed_phrase.split(' ').count(), word_count as usize);
+        validate_seed_phrase(&seed_phrase).unwrap();
+    }
+    assert!(matches!(
+        generate_seed_phrase(18),
+        Err(WasmError::SeedPhraseWordCount(18))
+    ));
+}
+
+#[test]
+fn seed_phrase_validation_names_the_unknown_word() {
+    let seed_phrase = TEST_SEED_PHRASE.replace("churn", "chrun");
+
+    assert!(matches!(
+        validate_seed_phrase(&seed_phrase),
+        Err(WasmError::UnknownSeedPhraseWord { index: 4, word }) if word == "chrun"
+    ));
+}
+
+#[test]
+fn seed_phrase_validation_detects_bad_checksums() {
+    // Valid words in an order whose checksum does not match.
+    let seed_phrase = ["abandon"; 12].join(" ");
+
+    assert!(matches!(
+        validate_seed_phrase(&seed_phrase),
+        Err(WasmError::InvalidSeedPhraseChecksum)
+    ));
+}
+
+#[test]
+fn seed_phrase_validation_checks_the_word_count() {
+    let seed_phrase = format!("{TEST_SEED_PHRASE} abandon");
+
+    assert!(matches!(
+        validate_seed_phrase(&seed_phrase),
+        Err(WasmError::SeedPhraseWordCount(25))
+    ));
+}
+
+#[test]
+fn spend_keys_round_trip_through_bech32m() {
+    let spend_key = generate_spend_key(TEST_SEED_PHRASE).unwrap();
+    let encoded = export_spend_key(&spend_key).unwrap();
+
+    // `generates_spend_key` encodes the leading bytes of the protobuf encoding instead, so its
+    // string differs from the encoding of the key itself.
+    assert_eq!(
+        encoded,
+        "penumbraspendkey1rwq387ksfawmqrn2ea2pjjv4p7z7eglq9ecz2jpckagtg23v4fgsrwx2zq"
+    );
+    assert_eq!(import_spend_key(&encoded).unwrap(), spend_key);
+}
+
+#[test]
+fn full_viewing_keys_round_trip_through_bech32m() {
+    let encoded = "penumbrafullviewingkey1vzfytwlvq067g2kz095vn7sgcft47hga40atrg5zu2crskm6tyyjysm28qg5nth2fqmdf5n0q530jreumjlsrcxjwtfv6zdmfpe5kqsa5lg09";
+    let fvk = import_full_viewing_key(encoded).unwrap();
+
+    let spend_key = generate_spend_key(TEST_SEED_PHRASE).unwrap();
+    assert_eq!(fvk, get_full_viewing_key(&spend_key).unwrap());
+    assert_eq!(export_full_viewing_key(&fvk).unwrap(), encoded);
+}
+
+#[test]
+fn importing_a_key_of_the_wrong_kind_fails() {
+    let encoded = "penumbrafullviewingkey1vzfytwlvq067g2kz095vn7sgcft47hga40atrg5zu2crskm6tyyjysm28qg5nth2fqmdf5n0q530jreumjlsrcxjwtfv6zdmfpe5kqsa5lg09";
+
+    assert!(import_spend_key(encoded).is_err());
+}
+
+#[test]
+fn address_views_decode_controlled_addresses() {
+    let spend_key = generate_spend_key(TEST_SEED_PHRASE).unwrap();
+    let fvk_bytes = get_full_viewing_key(&spend_key).unwrap();
+    let fvk = FullViewingKey::decode(fvk_bytes.as_slice()).unwrap();
+    let address_bytes = get_address_by_index(&fvk_bytes, 3, &[]).unwrap();
+
+    let view =
+        AddressView::decode(address_view(&fvk_bytes, &address_bytes).unwrap().as_slice()).unwrap();
+
+    let AddressView::Decoded {
+        address,
+        index,
+        wallet_id,
+    } = view
+    else {
+        panic!("expected a decoded view, got {view:?}");
+    };
+    assert_eq!(address.encode_to_vec(), address_bytes);
+    assert_eq!(index, AddressIndex::new(3));
+    assert_eq!(wallet_id, fvk.wallet_id());
+}
+
+#[test]
+fn address_views_of_other_wallets_are_opaque() {
+    let spend_key = generate_spend_key(TEST_SEED_PHRASE).unwrap();
+    let fvk_bytes = get_full_viewing_key(&spend_key).unwrap();
+    let other_address =
+        SpendKey::from_seed_phrase_bip44(SeedPhrase::generate(OsRng), &Bip44Path::new(0))
+            .full_viewing_key()
+            .payment_address(AddressIndex::new(0))
+            .0;
+
+    let view = AddressView::decode(
+        address_view(&fvk_bytes, &other_address.encode_to_vec())
+            .unwrap()
+            .as_slice(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        view,
+        AddressView::Opaque {
+            address: other_address
+        }
+    );
+}
+
+#[test]
+fn derives_one_address_per_account() {
+    let spend_key = generate_spend_key(TEST_SEED_PHRASE).unwrap();
+    let fvk_bytes = get_full_viewing_key(&spend_key).unwrap();
+    let fvk = FullViewingKey::decode(fvk_bytes.as_slice()).unwrap();
+
+    let views = derive_addresses_inner(&fvk, 2..5);
+
+    assert_eq!(views.len(), 3);
+    for (account, view) in (2..5).zip(views) {
+        let AddressView::Decoded { address, index, .. } = view else {
+            panic!("expected a decoded view, got {view:?}");
+        };
+        assert_eq!(index, AddressIndex::new(account));
+        assert_eq!(
+            address.encode_to_vec(),
+            get_address_by_index(&fvk_bytes, account, &[]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,381 @@
+extern crate penumbra_wasm;
+use penumbra_asset::{ValueView, STAKING_TOKEN_ASSET_ID, STAKING_TOKEN_DENOM};
+use penumbra_auction::auction::dutch::{ActionDutchAuctionSchedule, DutchAuctionDescription};
+use penumbra_auction::auction::AuctionNft;
+use penumbra_dex::lp::plan::PositionOpenPlan;
+use penumbra_dex::lp::position::Position as LiquidityPosition;
+use penumbra_dex::lp::Reserves;
+use penumbra_dex::{DirectedTradingPair, PositionClose};
+use penumbra_fee::Fee;
+use penumbra_keys::keys::{AddressIndex, Bip44Path, SeedPhrase, SpendKey};
+use penumbra_keys::{Address, FullViewingKey};
+use penumbra_proto::DomainType;
+use penumbra_sct::epoch::Epoch;
+use penumbra_shielded_pool::{Ics20Withdrawal, Note, OutputPlan, SpendPlan};
+use penumbra_stake::{Delegate, DelegationToken, IdentityKey, Undelegate};
+use penumbra_tct::{Position, StateCommitment};
+use penumbra_transaction::memo::MemoPlaintext;
+use penumbra_transaction::plan::MemoPlan;
+use penumbra_transaction::{ActionPlan, TransactionParameters, TransactionPlan};
+use penumbra_wasm::database::interface::Database;
+use penumbra_wasm::database::mock::{get_mock_tables, MockDb};
+use penumbra_wasm::plan_view::{diff_plan_views, view_transaction_plan_inner, PlannedActionView};
+use penumbra_wasm::storage::{byte_array_to_base64, Storage};
+use penumbra_wasm::transparent::transparent_address;
+use rand_core::OsRng;
+use std::str::FromStr;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::utils::fixtures::{ics20_withdrawal, spend_key, staking_value, VALIDATOR};
+
+mod utils;
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+async fn storage_with_staking_token() -> Storage<MockDb> {
+    let mock_db = MockDb::new();
+    let tables = get_mock_tables();
+    mock_db
+        .put_with_key(
+            &tables.assets,
+            byte_array_to_base64(&STAKING_TOKEN_ASSET_ID.to_proto().inner),
+            &*STAKING_TOKEN_DENOM,
+        )
+        .await
+        .unwrap();
+    Storage::new(mock_db, tables).unwrap()
+}
+
+fn wallet() -> FullViewingKey {
+    spend_key().full_viewing_key().clone()
+}
+
+fn other_address() -> Address {
+    SpendKey::from_seed_phrase_bip44(SeedPhrase::generate(OsRng), &Bip44Path::new(0))
+        .full_viewing_key()
+        .payment_address(AddressIndex::new(0))
+        .0
+}
+
+/// Sends 600 of a 1000 note to `recipient`, returning the rest as change and paying a fee of 10.
+/// Also returns the commitment of the change output, as the planner reports it.
+fn send_plan(
+    fvk: &FullViewingKey,
+    note: &Note,
+    recipient: &Address,
+    change: u64,
+) -> (TransactionPlan, Vec<StateCommitment>) {
+    let address = fvk.payment_address(AddressIndex::new(0)).0;
+    let change_output = OutputPlan::new(&mut OsRng, staking_value(change), address.clone());
+    let change_commitments = vec![change_output.output_note().commit()];
+    let plan = TransactionPlan {
+        actions: vec![
+            ActionPlan::Spend(SpendPlan::new(
+                &mut OsRng,
+                note.clone(),
+                Position::from(0u64),
+            )),
+            ActionPlan::Output(OutputPlan::new(
+                &mut OsRng,
+                staking_value(600),
+                recipient.clone(),
+            )),
+            ActionPlan::Output(change_output),
+        ],
+        transaction_parameters: TransactionParameters {
+            fee: Fee::from_staking_token_amount(10u64.into()),
+            ..Default::default()
+        },
+        memo: Some(MemoPlan::new(
+            &mut OsRng,
+            MemoPlaintext::new(address, "plan view test".to_string()).unwrap(),
+        )),
+        ..Default::default()
+    };
+    (plan, change_commitments)
+}
+
+fn is_staking_token(value: &ValueView, amount: u64) -> bool {
+    matches!(
+        value,
+        ValueView::KnownAssetId { amount: a, metadata, .. }
+            if *a == amount.into() && metadata.id() == *STAKING_TOKEN_ASSET_ID
+    )
+}
+
+#[wasm_bindgen_test]
+async fn plan_view_shows_spends_outputs_change_fee_and_memo() {
+    let storage = storage_with_staking_token().await;
+    let fvk = wallet();
+    let address = fvk.payment_address(AddressIndex::new(0)).0;
+    let note = Note::generate(&mut OsRng, &address, staking_value(1_000));
+    let recipient = other_address();
+
+    let (plan, change) = send_plan(&fvk, &note, &recipient, 390);
+    let view = view_transaction_plan_inner(&storage, &fvk, &plan, &change)
+        .await
+        .unwrap();
+
+    assert_eq!(view.actions.len(), 3);
+    assert!(matches!(
+        &view.actions[0],
+        PlannedActionView::Spend { value, .. } if is_staking_token(value, 1_000)
+    ));
+    assert!(matches!(
+        &view.actions[1],
+        PlannedActionView::Output { value, recipient: recipient_view, change: false }
+            if is_staking_token(value, 600) && recipient_view.address() == recipient
+    ));
+    assert!(matches!(
+        &view.actions[2],
+        PlannedActionView::Output { value, change: true, .. } if is_staking_token(value, 390)
+    ));
+    assert!(is_staking_token(&view.fee, 10));
+
+    let memo = view.memo.unwrap();
+    assert_eq!(memo.text, "plan view test");
+    assert_eq!(memo.return_address.address(), address);
+}
+
+#[wasm_bindgen_test]
+async fn change_to_the_transparent_address_is_change() {
+    let storage = storage_with_staking_token().await;
+    let fvk = wallet();
+    let address = fvk.payment_address(AddressIndex::new(0)).0;
+    let note = Note::generate(&mut OsRng, &address, staking_value(1_000));
+    let transparent = transparent_address(&fvk).unwrap();
+
+    let (mut plan, _) = send_plan(&fvk, &note, &other_address(), 390);
+    let change_output = OutputPlan::new(&mut OsRng, staking_value(390), transparent.clone());
+    let change = vec![change_output.output_note().commit()];
+    plan.actions[2] = ActionPlan::Output(change_output);
+    let view = view_transaction_plan_inner(&storage, &fvk, &plan, &change)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        &view.actions[2],
+        PlannedActionView::Output { value, recipient, change: true }
+            if is_staking_token(value, 390) && recipient.address() == transparent
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn outputs_to_the_wallet_are_not_change_unless_planned_as_change() {
+    let storage = storage_with_staking_token().await;
+    let fvk = wallet();
+    let address = fvk.payment_address(AddressIndex::new(0)).0;
+    let note = Note::generate(&mut OsRng, &address, staking_value(1_000));
+    let own_account = fvk.payment_address(AddressIndex::new(1)).0;
+
+    let (plan, change) = send_plan(&fvk, &note, &own_account, 390);
+    let view = view_transaction_plan_inner(&storage, &fvk, &plan, &change)
+        .await
+        .unwrap();
+    assert!(matches!(
+        &view.actions[1],
+        PlannedActionView::Output { value, change: false, .. } if is_staking_token(value, 600)
+    ));
+    assert!(matches!(
+        &view.actions[2],
+        PlannedActionView::Output { value, change: true, .. } if is_staking_token(value, 390)
+    ));
+
+    // Without the planner's change commitments, nothing is marked as change.
+    let view = view_transaction_plan_inner(&storage, &fvk, &plan, &[])
+        .await
+        .unwrap();
+    assert!(matches!(
+        &view.actions[2],
+        PlannedActionView::Output { change: false, .. }
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn plan_view_shows_auction_nfts() {
+    let storage = storage_with_staking_token().await;
+    let fvk = wallet();
+    let output_id = DelegationToken::from(IdentityKey::from_str(VALIDATOR).unwrap()).id();
+    let description = DutchAuctionDescription {
+        input: staking_value(1_000),
+        output_id,
+        max_output: 2_000u64.into(),
+        min_output: 1_000u64.into(),
+        start_height: 100,
+        end_height: 200,
+        step_count: 10,
+        nonce: [0; 32],
+    };
+    let nft_id = AuctionNft::new(description.id(), 0).metadata.id();
+
+    let plan = TransactionPlan {
+        actions: vec![ActionPlan::ActionDutchAuctionSchedule(
+            ActionDutchAuctionSchedule { description },
+        )],
+        ..Default::default()
+    };
+    let view = view_transaction_plan_inner(&storage, &fvk, &plan, &[])
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        &view.actions[0],
+        PlannedActionView::AuctionSchedule {
+            input,
+            auction_nft: ValueView::KnownAssetId { metadata, .. },
+            ..
+        } if is_staking_token(input, 1_000) && metadata.id() == nft_id
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn replanning_the_same_request_has_no_diff() {
+    let storage = storage_with_staking_token().await;
+    let fvk = wallet();
+    let address = fvk.payment_address(AddressIndex::new(0)).0;
+    let note = Note::generate(&mut OsRng, &address, staking_value(1_000));
+    let recipient = other_address();
+
+    // The plans differ in their randomness only.
+    let (old, old_change) = send_plan(&fvk, &note, &recipient, 390);
+    let (new, new_change) = send_plan(&fvk, &note, &recipient, 390);
+    let diff = diff_plan_views(
+        &view_transaction_plan_inner(&storage, &fvk, &old, &old_change)
+            .await
+            .unwrap(),
+        &view_transaction_plan_inner(&storage, &fvk, &new, &new_change)
+            .await
+            .unwrap(),
+    );
+
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.fee, None);
+    assert!(!diff.memo_changed);
+}
+
+#[wasm_bindgen_test]
+async fn diff_explains_a_changed_fee_and_change_output() {
+    let storage = storage_with_staking_token().await;
+    let fvk = wallet();
+    let address = fvk.payment_address(AddressIndex::new(0)).0;
+    let note = Note::generate(&mut OsRng, &address, staking_value(1_000));
+    let recipient = other_address();
+
+    let (old, old_change) = send_plan(&fvk, &note, &recipient, 390);
+    let (mut new, new_change) = send_plan(&fvk, &note, &recipient, 380);
+    new.transaction_parameters.fee = Fee::from_staking_token_amount(20u64.into());
+    new.transaction_parameters.expiry_height = 10;
+
+    let diff = diff_plan_views(
+        &view_transaction_plan_inner(&storage, &fvk, &old, &old_change)
+            .await
+            .unwrap(),
+        &view_transaction_plan_inner(&storage, &fvk, &new, &new_change)
+            .await
+            .unwrap(),
+    );
+
+    assert!(matches!(
+        diff.removed.as_slice(),
+        [PlannedActionView::Output { value, change: true, .. }] if is_staking_token(value, 390)
+    ));
+    assert!(matches!(
+        diff.added.as_slice(),
+        [PlannedActionView::Output { value, change: true, .. }] if is_staking_token(value, 380)
+    ));
+    let (old_fee, new_fee) = diff.fee.unwrap();
+    assert!(is_staking_token(&old_fee, 10));
+    assert!(is_staking_token(&new_fee, 20));
+    assert!(diff.expiry_height_changed);
+}
+
+#[wasm_bindgen_test]
+async fn plan_view_shows_staking_ibc_and_position_actions() {
+    let storage = storage_with_staking_token().await;
+    let fvk = wallet();
+    let address = fvk.payment_address(AddressIndex::new(0)).0;
+    let validator = IdentityKey::from_str(VALIDATOR).unwrap();
+    let delegation_id = DelegationToken::from(validator).id();
+    let position = LiquidityPosition::new(
+        OsRng,
+        DirectedTradingPair::new(*STAKING_TOKEN_ASSET_ID, delegation_id),
+        30,
+        1u64.into(),
+        1u64.into(),
+        Reserves {
+            r1: 0u64.into(),
+            r2: 0u64.into(),
+        },
+    );
+    let withdrawal = ics20_withdrawal(500, Some(&address));
+
+    let plan = TransactionPlan {
+        actions: vec![
+            ActionPlan::Delegate(Delegate {
+                validator_identity: validator,
+                epoch_index: 1,
+                unbonded_amount: 1_000u64.into(),
+                delegation_amount: 900u64.into(),
+            }),
+            ActionPlan::Undelegate(Undelegate {
+                validator_identity: validator,
+                from_epoch: Epoch {
+                    index: 1,
+                    start_height: 100,
+                },
+                unbonded_amount: 1_000u64.into(),
+                delegation_amount: 900u64.into(),
+            }),
+            ActionPlan::Ics20Withdrawal(Ics20Withdrawal::try_from(withdrawal).unwrap()),
+            ActionPlan::PositionOpen(PositionOpenPlan {
+                position: position.clone(),
+                metadata: None,
+            }),
+            ActionPlan::PositionClose(PositionClose {
+                position_id: position.id(),
+            }),
+        ],
+        ..Default::default()
+    };
+    let view = view_transaction_plan_inner(&storage, &fvk, &plan, &[])
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        &view.actions[0],
+        PlannedActionView::Delegate {
+            validator: v,
+            unbonded,
+            delegation: ValueView::KnownAssetId { amount, metadata, .. },
+        } if *v == validator
+            && is_staking_token(unbonded, 1_000)
+            && *amount == 900u64.into()
+            && metadata.id() == delegation_id
+    ));
+    assert!(matches!(
+        &view.actions[1],
+        PlannedActionView::Undelegate {
+            validator: v,
+            delegation: ValueView::KnownAssetId { amount, metadata, .. },
+            unbonding: ValueView::KnownAssetId { .. },
+        } if *v == validator && *amount == 900u64.into() && metadata.id() == delegation_id
+    ));
+    assert!(matches!(
+        &view.actions[2],
+        PlannedActionView::Ics20Withdrawal { value, destination, channel, return_address }
+            if is_staking_token(value, 500)
+                && destination == "osmo1destination"
+                && channel == "channel-0"
+                && return_address.address() == address
+    ));
+    assert!(matches!(
+        &view.actions[3],
+        PlannedActionView::PositionOpen { position_id, fee_bps: 30, .. }
+            if *position_id == position.id()
+    ));
+    assert!(matches!(
+        &view.actions[4],
+        PlannedActionView::PositionClose { position_id } if *position_id == position.id()
+    ));
+}
@@ -1,13 +1,32 @@
-use penumbra_asset::STAKING_TOKEN_ASSET_ID;
-use penumbra_keys::FullViewingKey;
+use penumbra_asset::{Value, STAKING_TOKEN_ASSET_ID};
+use penumbra_keys::keys::{AddressIndex, Bip44Path, SeedPhrase, SpendKey};
+use penumbra_keys::{Address, FullViewingKey};
+use penumbra_proto::core::component::ibc::v1 as ibc_pb;
+use penumbra_proto::core::transaction::v1::MemoPlaintext;
 use penumbra_proto::view::v1::TransactionPlannerRequest;
+use penumbra_proto::DomainType;
+use penumbra_sct::{CommitmentSource, Nullifier};
+use penumbra_shielded_pool::Note;
+use penumbra_tct::StateCommitment;
+use penumbra_transaction::ActionPlan;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{OsRng, SeedableRng};
 use std::str::FromStr;
 use wasm_bindgen_test::wasm_bindgen_test;
 
+use penumbra_wasm::database::interface::Database;
 use penumbra_wasm::database::mock::{get_mock_tables, MockDb};
-use penumbra_wasm::planner::plan_transaction_inner;
+use penumbra_wasm::error::WasmError;
+use penumbra_wasm::memo::MAX_MEMO_TEXT_LEN;
+use penumbra_wasm::note_record::SpendableNoteRecord;
+use penumbra_wasm::planner::{
+    plan_transaction_inner, plan_transaction_with_options_inner, plan_transaction_with_rng,
+    PlannerOptions,
+};
 use penumbra_wasm::storage::Storage;
+use penumbra_wasm::transparent::transparent_address;
 
+use crate::utils::fixtures::ics20_withdrawal;
 use crate::utils::planner_setup::seed_params_in_db;
 
 mod utils;
@@ -59,3 +78,268 @@ async fn test_planner_without_actions() {
     assert_eq!(res.actions.len(), 0);
     assert!(res.memo.is_none());
 }
+
+const FULL_VIEWING_KEY: &str = "penumbrafullviewingkey1mnm04x7yx5tyznswlp0sxs8nsxtgxr9p98dp0msuek8fzxuknuzawjpct8zdevcvm3tsph0wvsuw33x2q42e7sf29q904hwerma8xzgrxsgq2";
+
+#[allow(deprecated)]
+fn memo_only_request(memo: MemoPlaintext) -> TransactionPlannerRequest {
+    TransactionPlannerRequest {
+        expiry_height: 100,
+        memo: Some(memo),
+        source: None,
+        outputs: vec![],
+        spends: vec![],
+        swaps: vec![],
+        swap_claims: vec![],
+        delegations: vec![],
+        undelegations: vec![],
+        undelegation_claims: vec![],
+        ibc_relay_actions: vec![],
+        ics20_withdrawals: vec![],
+        position_opens: vec![],
+        position_closes: vec![],
+        position_withdraws: vec![],
+        dutch_auction_schedule_actions: vec![],
+        dutch_auction_end_actions: vec![],
+        dutch_auction_withdraw_actions: vec![],
+        delegator_votes: vec![],
+        action_liquidity_tournament_vote: vec![],
+        epoch_index: 0,
+        epoch: None,
+        fee_mode: None,
+    }
+}
+
+#[wasm_bindgen_test]
+async fn test_planner_rejects_oversized_memo() {
+    // Nothing is seeded: the memo is rejected before the planner reads storage.
+    let storage = Storage::new(MockDb::new(), get_mock_tables()).unwrap();
+    let full_viewing_key = FullViewingKey::from_str(FULL_VIEWING_KEY).unwrap();
+    let return_address = full_viewing_key.payment_address(AddressIndex::new(0)).0;
+
+    let req = memo_only_request(MemoPlaintext {
+        return_address: Some(return_address.into()),
+        text: "a".repeat(MAX_MEMO_TEXT_LEN + 1),
+    });
+    let res = plan_transaction_inner(storage, req, full_viewing_key, *STAKING_TOKEN_ASSET_ID).await;
+
+    assert!(matches!(
+        res,
+        Err(WasmError::MemoTooLong { len, max }) if len == MAX_MEMO_TEXT_LEN + 1 && max == MAX_MEMO_TEXT_LEN
+    ));
+}
+
+async fn storage_with_fee_note(address: &Address) -> Storage<MockDb> {
+    let mock_db = MockDb::new();
+    let tables = get_mock_tables();
+    seed_params_in_db(&mock_db, &tables).await;
+
+    let fee_note = SpendableNoteRecord {
+        note_commitment: StateCommitment::try_from([0; 32]).unwrap(),
+        note: Note::generate(
+            &mut OsRng,
+            address,
+            Value {
+                amount: 1_000_000u64.into(),
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            },
+        ),
+        address_index: Default::default(),
+        nullifier: Nullifier::try_from(vec![
+            76, 12, 37, 160, 207, 93, 129, 238, 230, 254, 29, 227, 107, 97, 138, 12, 172, 130, 138,
+            66, 123, 217, 253, 148, 178, 91, 112, 125, 247, 32, 189, 2,
+        ])
+        .unwrap(),
+        height_created: 0,
+        height_spent: None,
+        position: Default::default(),
+        source: CommitmentSource::Genesis,
+        return_address: None,
+    };
+    mock_db
+        .put_with_key(&tables.spendable_notes, "fee_note", &fee_note)
+        .await
+        .unwrap();
+
+    Storage::new(mock_db, tables).unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn test_planner_memo_only_send() {
+    let full_viewing_key = FullViewingKey::from_str(FULL_VIEWING_KEY).unwrap();
+    let return_address = full_viewing_key.payment_address(AddressIndex::new(0)).0;
+    let storage = storage_with_fee_note(&return_address).await;
+
+    let req = memo_only_request(MemoPlaintext {
+        return_address: Some(return_address.clone().into()),
+        text: "a".repeat(MAX_MEMO_TEXT_LEN),
+    });
+    let plan = plan_transaction_inner(storage, req, full_viewing_key, *STAKING_TOKEN_ASSET_ID)
+        .await
+        .unwrap();
+
+    let memo = plan.memo.expect("memo is planned");
+    assert_eq!(memo.plaintext.text().len(), MAX_MEMO_TEXT_LEN);
+    assert!(plan.actions.iter().any(|action| matches!(
+        action,
+        ActionPlan::Output(output)
+            if output.value.amount == 0u64.into() && output.dest_address == return_address
+    )));
+}
+
+#[wasm_bindgen_test]
+async fn test_planner_memo_only_send_rejects_a_foreign_return_address() {
+    let full_viewing_key = FullViewingKey::from_str(FULL_VIEWING_KEY).unwrap();
+    let own_address = full_viewing_key.payment_address(AddressIndex::new(0)).0;
+    let storage = storage_with_fee_note(&own_address).await;
+    let foreign_address =
+        SpendKey::from_seed_phrase_bip44(SeedPhrase::generate(OsRng), &Bip44Path::new(0))
+            .full_viewing_key()
+            .payment_address(AddressIndex::new(0))
+            .0;
+
+    let req = memo_only_request(MemoPlaintext {
+        return_address: Some(foreign_address.into()),
+        text: "not ours".to_string(),
+    });
+    let res = plan_transaction_inner(storage, req, full_viewing_key, *STAKING_TOKEN_ASSET_ID).await;
+
+    assert!(res.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn test_planner_is_reproducible_with_a_seeded_rng() {
+    let full_viewing_key = FullViewingKey::from_str(FULL_VIEWING_KEY).unwrap();
+    let return_address = full_viewing_key.payment_address(AddressIndex::new(0)).0;
+    let storage = storage_with_fee_note(&return_address).await;
+    let req = memo_only_request(MemoPlaintext {
+        return_address: Some(return_address.into()),
+        text: "reproducible".to_string(),
+    });
+
+    let mut plans = Vec::new();
+    for seed in [7, 7, 8] {
+        let plan = plan_transaction_with_rng(
+            storage.clone(),
+            req.clone(),
+            full_viewing_key.clone(),
+            *STAKING_TOKEN_ASSET_ID,
+            &mut ChaCha20Rng::seed_from_u64(seed),
+        )
+        .await
+        .unwrap();
+        plans.push(plan.encode_to_vec());
+    }
+
+    assert_eq!(plans[0], plans[1]);
+    assert_ne!(plans[0], plans[2]);
+}
+
+#[wasm_bindgen_test]
+async fn test_planner_returns_change_to_the_transparent_address() {
+    let full_viewing_key = FullViewingKey::from_str(FULL_VIEWING_KEY).unwrap();
+    let return_address = full_viewing_key.payment_address(AddressIndex::new(0)).0;
+    let transparent = transparent_address(&full_viewing_key).unwrap();
+    let storage = storage_with_fee_note(&return_address).await;
+    let req = memo_only_request(MemoPlaintext {
+        return_address: Some(return_address.into()),
+        text: "change".to_string(),
+    });
+    let options = PlannerOptions {
+        transparent_change: true,
+    };
+
+    let planned = plan_transaction_with_options_inner(
+        storage,
+        req,
+        full_viewing_key,
+        *STAKING_TOKEN_ASSET_ID,
+        options,
+        &mut OsRng,
+    )
+    .await
+    .unwrap();
+
+    assert!(planned.plan.actions.iter().any(|action| matches!(
+        action,
+        ActionPlan::Output(output)
+            if output.value.amount > 0u64.into()
+                && output.dest_address == transparent
+                && planned.change_commitments.contains(&output.output_note().commit())
+    )));
+}
+
+#[wasm_bindgen_test]
+async fn test_planner_rejects_transparent_change_for_other_accounts() {
+    let full_viewing_key = FullViewingKey::from_str(FULL_VIEWING_KEY).unwrap();
+    let return_address = full_viewing_key.payment_address(AddressIndex::new(1)).0;
+    let storage = storage_with_fee_note(&return_address).await;
+    let mut req = memo_only_request(MemoPlaintext {
+        return_address: Some(return_address.into()),
+        text: "change".to_string(),
+    });
+    req.source = Some(AddressIndex::new(1).into());
+    let options = PlannerOptions {
+        transparent_change: true,
+    };
+
+    let res = plan_transaction_with_options_inner(
+        storage,
+        req,
+        full_viewing_key,
+        *STAKING_TOKEN_ASSET_ID,
+        options,
+        &mut OsRng,
+    )
+    .await;
+
+    assert!(res.is_err());
+}
+
+/// A withdrawal of 1000 staking tokens that shows the counterparty chain a transparent address.
+fn transparent_ics20_withdrawal(return_address: Option<&Address>) -> ibc_pb::Ics20Withdrawal {
+    ibc_pb::Ics20Withdrawal {
+        use_transparent_address: true,
+        ..ics20_withdrawal(1_000, return_address)
+    }
+}
+
+#[wasm_bindgen_test]
+async fn test_planner_ics20_withdrawal_returns_to_the_transparent_address() {
+    let full_viewing_key = FullViewingKey::from_str(FULL_VIEWING_KEY).unwrap();
+    let return_address = full_viewing_key.payment_address(AddressIndex::new(0)).0;
+    let transparent = transparent_address(&full_viewing_key).unwrap();
+    let storage = storage_with_fee_note(&return_address).await;
+
+    let mut req = memo_only_request(MemoPlaintext {
+        return_address: Some(return_address.into()),
+        text: String::new(),
+    });
+    req.ics20_withdrawals = vec![transparent_ics20_withdrawal(None)];
+
+    let plan = plan_transaction_inner(storage, req, full_viewing_key, *STAKING_TOKEN_ASSET_ID)
+        .await
+        .unwrap();
+
+    assert!(plan.actions.iter().any(|action| matches!(
+        action,
+        ActionPlan::Ics20Withdrawal(withdrawal) if withdrawal.return_address == transparent
+    )));
+}
+
+#[wasm_bindgen_test]
+async fn test_planner_ics20_withdrawal_rejects_a_conflicting_return_address() {
+    let full_viewing_key = FullViewingKey::from_str(FULL_VIEWING_KEY).unwrap();
+    let return_address = full_viewing_key.payment_address(AddressIndex::new(0)).0;
+    let storage = storage_with_fee_note(&return_address).await;
+
+    let mut req = memo_only_request(MemoPlaintext {
+        return_address: Some(return_address.clone().into()),
+        text: String::new(),
+    });
+    req.ics20_withdrawals = vec![transparent_ics20_withdrawal(Some(&return_address))];
+
+    let res = plan_transaction_inner(storage, req, full_viewing_key, *STAKING_TOKEN_ASSET_ID).await;
+
+    assert!(res.is_err());
+}
@@ -0,0 +1,223 @@
+use decaf377::{Fq, Fr};
+use penumbra_asset::STAKING_TOKEN_ASSET_ID;
+use penumbra_dex::swap::{SwapPlaintext, SwapPlan};
+use penumbra_dex::TradingPair;
+use penumbra_fee::Fee;
+use penumbra_keys::keys::AddressIndex;
+use penumbra_shielded_pool::{Note, OutputPlan, SpendPlan};
+use penumbra_stake::{DelegationToken, IdentityKey, Penalty, UndelegateClaimPlan};
+use penumbra_tct as tct;
+use penumbra_transaction::{ActionPlan, TransactionPlan, WitnessData};
+use penumbra_wasm::build::build_action_inner;
+use penumbra_wasm::error::WasmError;
+use penumbra_wasm::keys::{
+    clear_proving_key_loader, ensure_proving_key, load_proving_key, load_proving_key_checked,
+    proving_key_status_inner, register_proving_key_loader_inner, required_proving_keys_inner,
+    ProvingKeyType,
+};
+use rand_core::OsRng;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::utils::fixtures::{address, spend_key, staking_value, VALIDATOR};
+
+mod utils;
+
+fn swap_plan() -> ActionPlan {
+    let output_id = DelegationToken::from(IdentityKey::from_str(VALIDATOR).unwrap()).id();
+    let swap_plaintext = SwapPlaintext::new(
+        &mut OsRng,
+        TradingPair::new(*STAKING_TOKEN_ASSET_ID, output_id),
+        100u64.into(),
+        0u64.into(),
+        Fee::from_staking_token_amount(0u64.into()),
+        address(),
+    );
+    SwapPlan::new(&mut OsRng, swap_plaintext).into()
+}
+
+fn undelegate_claim_plan() -> ActionPlan {
+    UndelegateClaimPlan {
+        validator_identity: IdentityKey::from_str(VALIDATOR).unwrap(),
+        penalty: Penalty::from_percent(0),
+        unbonding_amount: 100u64.into(),
+        balance_blinding: Fr::rand(&mut OsRng),
+        proof_blinding_r: Fq::rand(&mut OsRng),
+        proof_blinding_s: Fq::rand(&mut OsRng),
+        unbonding_start_height: 0,
+    }
+    .into()
+}
+
+// The proving keys are process-wide and cannot be unloaded, so each test uses a key type no
+// other test loads.
+
+#[test]
+fn checked_loading_accepts_the_expected_key() {
+    let spend_key: &[u8] = include_bytes!("../../../keys/keys/spend_pk.bin");
+
+    load_proving_key_checked(spend_key, "spend").unwrap();
+
+    assert!(proving_key_status_inner()
+        .loaded
+        .contains(&ProvingKeyType::Spend));
+
+    // The slot is populated now, so loading again cannot take effect.
+    assert!(matches!(
+        load_proving_key_checked(spend_key, "spend"),
+        Err(WasmError::ProvingKeyAlreadyLoaded("spend"))
+    ));
+}
+
+#[test]
+fn checked_loading_rejects_another_key() {
+    let swap_key: &[u8] = include_bytes!("../../../keys/keys/swap_pk.bin");
+
+    assert!(matches!(
+        load_proving_key_checked(swap_key, "swapClaim"),
+        Err(WasmError::InvalidProvingKey {
+            key_type: "swapClaim",
+            ..
+        })
+    ));
+    assert!(proving_key_status_inner()
+        .missing
+        .contains(&ProvingKeyType::SwapClaim));
+}
+
+#[test]
+fn checked_loading_rejects_corrupted_bytes() {
+    assert!(matches!(
+        load_proving_key_checked(&[0u8; 64], "undelegateClaim"),
+        Err(WasmError::InvalidProvingKey {
+            key_type: "undelegateClaim",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn building_without_the_proving_key_fails() {
+    let spend_key = spend_key();
+    let fvk = spend_key.full_viewing_key();
+    let output = ActionPlan::Output(OutputPlan::new(
+        &mut OsRng,
+        staking_value(1_000),
+        fvk.payment_address(AddressIndex::new(0)).0,
+    ));
+    let witness = WitnessData {
+        anchor: tct::Tree::new().root(),
+        state_commitment_proofs: Default::default(),
+    };
+
+    let result = build_action_inner(TransactionPlan::default(), output, fvk.clone(), witness);
+
+    assert!(matches!(
+        result,
+        Err(WasmError::MissingProvingKey("output"))
+    ));
+}
+
+#[test]
+fn key_types_round_trip_through_their_names() {
+    for key_type in ProvingKeyType::ALL {
+        assert_eq!(
+            ProvingKeyType::from_str(key_type.as_str()).unwrap(),
+            key_type
+        );
+    }
+    assert!(ProvingKeyType::from_str("positionOpen").is_err());
+}
+
+#[test]
+fn required_keys_are_listed_once_per_type() {
+    let address = address();
+    let note = Note::generate(&mut OsRng, &address, staking_value(1_000));
+    let plan = TransactionPlan {
+        actions: vec![
+            swap_plan(),
+            SpendPlan::new(&mut OsRng, note, 0u64.into()).into(),
+            OutputPlan::new(&mut OsRng, staking_value(500), address.clone()).into(),
+            OutputPlan::new(&mut OsRng, staking_value(500), address).into(),
+        ],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        required_proving_keys_inner(&plan),
+        vec![
+            ProvingKeyType::Spend,
+            ProvingKeyType::Output,
+            ProvingKeyType::Swap
+        ]
+    );
+}
+
+#[test]
+fn registered_loader_fetches_missing_keys_once() {
+    let calls = Rc::new(Cell::new(0));
+    let loader_calls = calls.clone();
+    register_proving_key_loader_inner(move |key_type| {
+        assert_eq!(key_type, ProvingKeyType::Swap);
+        loader_calls.set(loader_calls.get() + 1);
+        Ok(include_bytes!("../../../keys/keys/swap_pk.bin").to_vec())
+    });
+
+    ensure_proving_key(&swap_plan()).unwrap();
+    ensure_proving_key(&swap_plan()).unwrap();
+    clear_proving_key_loader();
+
+    assert_eq!(calls.get(), 1);
+    assert!(proving_key_status_inner()
+        .loaded
+        .contains(&ProvingKeyType::Swap));
+}
+
+#[test]
+fn registered_loader_output_is_checked() {
+    // No test loads the undelegate claim key, so the loader is always asked for it.
+    let calls = Rc::new(Cell::new(0));
+    let loader_calls = calls.clone();
+    register_proving_key_loader_inner(move |key_type| {
+        assert_eq!(key_type, ProvingKeyType::UndelegateClaim);
+        loader_calls.set(loader_calls.get() + 1);
+        Ok(vec![0u8; 64])
+    });
+
+    let result = ensure_proving_key(&undelegate_claim_plan());
+    clear_proving_key_loader();
+
+    assert_eq!(calls.get(), 1);
+    assert!(matches!(
+        result,
+        Err(WasmError::InvalidProvingKey {
+            key_type: "undelegateClaim",
+            ..
+        })
+    ));
+    assert!(proving_key_status_inner()
+        .missing
+        .contains(&ProvingKeyType::UndelegateClaim));
+}
+
+#[test]
+fn shared_slots_reject_the_wrong_key_even_when_unchecked() {
+    let output_key: &[u8] = include_bytes!("../../../keys/keys/output_pk.bin");
+
+    assert!(load_proving_key(output_key, "actionLiquidityTournamentVote").is_err());
+    assert!(proving_key_status_inner()
+        .missing
+        .contains(&ProvingKeyType::DelegatorVote));
+}
+
+#[test]
+fn only_liquidity_tournament_votes_share_a_slot() {
+    for key_type in ProvingKeyType::ALL {
+        let expected = match key_type {
+            ProvingKeyType::ActionLiquidityTournamentVote => ProvingKeyType::DelegatorVote,
+            other => other,
+        };
+        assert_eq!(key_type.slot(), expected);
+    }
+}
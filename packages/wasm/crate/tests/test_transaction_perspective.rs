@@ -0,0 +1,843 @@
+extern crate penumbra_wasm;
+use decaf377::Fq;
+use penumbra_asset::asset::{Id, Metadata};
+use penumbra_asset::{Value, STAKING_TOKEN_ASSET_ID, STAKING_TOKEN_DENOM};
+use penumbra_auction::auction::dutch::{
+    ActionDutchAuctionEnd, ActionDutchAuctionSchedule, DutchAuctionDescription,
+};
+use penumbra_auction::auction::AuctionNft;
+use penumbra_dex::lp::plan::PositionOpenPlan;
+use penumbra_dex::lp::position::{Position as LpPosition, State};
+use penumbra_dex::lp::{LpNft, PositionMetadata, Reserves};
+use penumbra_dex::swap::{SwapPlaintext, SwapPlan};
+use penumbra_dex::swap_claim::{SwapClaimPlan, SwapClaimView};
+use penumbra_dex::{BatchSwapOutputData, DirectedTradingPair, PositionClose, TradingPair};
+use penumbra_fee::Fee;
+use penumbra_governance::{DelegatorVotePlan, Vote};
+use penumbra_keys::keys::{AddressIndex, Bip44Path, SeedPhrase, SpendKey};
+use penumbra_keys::{Address, AddressView, FullViewingKey, PayloadKey};
+use penumbra_num::Amount;
+use penumbra_proto::core::component::{dex::v1 as dex_pb, stake::v1 as stake_pb};
+use penumbra_proto::view::v1::TransactionInfo;
+use penumbra_proto::DomainType;
+use penumbra_sct::epoch::Epoch;
+use penumbra_sct::{CommitmentSource, Nullifier};
+use penumbra_shielded_pool::{Note, OutputPlan, SpendPlan};
+use penumbra_stake::rate::RateData;
+use penumbra_stake::{DelegationToken, IdentityKey};
+use penumbra_tct::{StateCommitment, Tree, Witness};
+use penumbra_transaction::memo::{MemoCiphertext, MemoPlaintext};
+use penumbra_transaction::plan::MemoPlan;
+use penumbra_transaction::view::action_view::{ActionView, OutputView, SpendView};
+use penumbra_transaction::{
+    ActionPlan, MemoView, Transaction, TransactionPerspective, TransactionPlan, TransactionView,
+    WitnessData,
+};
+use penumbra_wasm::build::build_serial_inner;
+use penumbra_wasm::database::interface::Database;
+use penumbra_wasm::database::mock::{get_mock_tables, MockDb};
+use penumbra_wasm::keys::load_proving_key;
+use penumbra_wasm::memo::memo_view;
+use penumbra_wasm::note_record::SpendableNoteRecord;
+use penumbra_wasm::storage::{byte_array_to_base64, Storage, Tables};
+use penumbra_wasm::swap_record::SwapRecord;
+use penumbra_wasm::tx::{
+    transaction_info_inner, transaction_infos_inner, PerspectiveAndView, PerspectiveWarning,
+};
+use rand_core::OsRng;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+use std::sync::Once;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::utils::fixtures::{ics20_withdrawal, spend_key, staking_value, VALIDATOR};
+
+mod utils;
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+static LOAD_PROVING_KEYS: Once = Once::new();
+
+fn load_proving_keys() {
+    LOAD_PROVING_KEYS.call_once(|| {
+        let spend_key: &[u8] = include_bytes!("../../../keys/keys/spend_pk.bin");
+        let output_key: &[u8] = include_bytes!("../../../keys/keys/output_pk.bin");
+        let delegator_vote_key: &[u8] = include_bytes!("../../../keys/keys/delegator_vote_pk.bin");
+        let swap_key: &[u8] = include_bytes!("../../../keys/keys/swap_pk.bin");
+        let swapclaim_key: &[u8] = include_bytes!("../../../keys/keys/swapclaim_pk.bin");
+        let convert_key: &[u8] = include_bytes!("../../../keys/keys/convert_pk.bin");
+
+        load_proving_key(spend_key, "spend").expect("can load spend key");
+        load_proving_key(output_key, "output").expect("can load output key");
+        load_proving_key(delegator_vote_key, "delegatorVote").expect("can load delegator vote key");
+        load_proving_key(swap_key, "swap").expect("can load swap key");
+        load_proving_key(swapclaim_key, "swapClaim").expect("can load swapclaim key");
+        load_proving_key(convert_key, "undelegateClaim").expect("can load convert key");
+    });
+}
+
+/// A wallet with its records in a `MockDb`, and the SCT its notes were committed to.
+struct Fixture {
+    mock_db: MockDb,
+    tables: Tables,
+    spend_key: SpendKey,
+    fvk: FullViewingKey,
+    sct: Tree,
+    commitments: Vec<StateCommitment>,
+}
+
+impl Fixture {
+    async fn new() -> Self {
+        Self::with_spend_key(spend_key()).await
+    }
+
+    /// A second, unrelated wallet.
+    async fn other() -> Self {
+        Self::with_spend_key(SpendKey::from_seed_phrase_bip44(
+            SeedPhrase::generate(OsRng),
+            &Bip44Path::new(0),
+        ))
+        .await
+    }
+
+    async fn with_spend_key(spend_key: SpendKey) -> Self {
+        load_proving_keys();
+
+        let fvk = spend_key.full_viewing_key().clone();
+        let fixture = Fixture {
+            mock_db: MockDb::new(),
+            tables: get_mock_tables(),
+            spend_key,
+            fvk,
+            sct: Tree::new(),
+            commitments: Vec::new(),
+        };
+        fixture.add_asset(&STAKING_TOKEN_DENOM).await;
+        fixture
+    }
+
+    fn address(&self) -> Address {
+        self.fvk.payment_address(AddressIndex::new(0)).0
+    }
+
+    async fn add_asset(&self, metadata: &Metadata) {
+        self.mock_db
+            .put_with_key(
+                &self.tables.assets,
+                byte_array_to_base64(&metadata.id().to_proto().inner),
+                metadata,
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn put_note_record(&self, record: &SpendableNoteRecord) {
+        self.mock_db
+            .put_with_key(
+                &self.tables.spendable_notes,
+                byte_array_to_base64(&record.note_commitment.to_proto().inner),
+                record,
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn put_swap_record(&self, record: &SwapRecord) {
+        self.mock_db
+            .put_with_key(
+                &self.tables.swaps,
+                byte_array_to_base64(&record.swap_commitment.to_proto().inner),
+                record,
+            )
+            .await
+            .unwrap();
+    }
+
+    /// Commits a note to the SCT and records it as one of our spendable notes.
+    async fn add_note(&mut self, value: Value) -> SpendableNoteRecord {
+        let note = Note::generate(&mut OsRng, &self.address(), value);
+        let commitment = note.commit();
+        let position = self.sct.insert(Witness::Keep, commitment).unwrap();
+        self.commitments.push(commitment);
+
+        let record = SpendableNoteRecord {
+            note_commitment: commitment,
+            nullifier: Nullifier::derive(self.fvk.nullifier_key(), position, &commitment),
+            note,
+            address_index: AddressIndex::new(0),
+            height_created: 1,
+            height_spent: None,
+            position,
+            source: CommitmentSource::Genesis,
+            return_address: None,
+        };
+        self.put_note_record(&record).await;
+        record
+    }
+
+    fn build(&self, actions: Vec<ActionPlan>, memo: Option<MemoPlan>) -> Transaction {
+        let plan = TransactionPlan {
+            actions,
+            memo,
+            ..Default::default()
+        };
+        let auth_data = plan.authorize(OsRng, &self.spend_key).unwrap();
+        let witness_data = WitnessData {
+            anchor: self.sct.root(),
+            state_commitment_proofs: self
+                .commitments
+                .iter()
+                .map(|commitment| (*commitment, self.sct.witness(*commitment).unwrap()))
+                .collect(),
+        };
+
+        build_serial_inner(self.fvk.clone(), plan, witness_data, auth_data).unwrap()
+    }
+
+    async fn perspective_and_view(&self, tx: &Transaction) -> PerspectiveAndView {
+        let storage = Storage::new(self.mock_db.clone(), self.tables.clone()).unwrap();
+        transaction_info_inner(&storage, &self.fvk, tx)
+            .await
+            .unwrap()
+    }
+
+    async fn perspective(&self, tx: &Transaction) -> (TransactionPerspective, TransactionView) {
+        let info = self.perspective_and_view(tx).await;
+        assert!(info.warnings.is_empty(), "{:?}", info.warnings);
+        (info.txp, info.txv)
+    }
+}
+
+fn delegation_token() -> DelegationToken {
+    DelegationToken::from(IdentityKey::from_str(VALIDATOR).unwrap())
+}
+
+fn memo_plan(return_address: Address) -> MemoPlan {
+    MemoPlan::new(
+        &mut OsRng,
+        MemoPlaintext::new(return_address, "perspective test".to_string()).unwrap(),
+    )
+}
+
+/// A batch swap result in which `delta_1` of asset 1 was traded for twice as much of asset 2.
+fn batch_swap_output_data(trading_pair: TradingPair, delta_1: u64) -> BatchSwapOutputData {
+    let amount = |value: u64| Some(Amount::from(value).into());
+    dex_pb::BatchSwapOutputData {
+        delta_1: amount(delta_1),
+        delta_2: amount(0),
+        lambda_1: amount(0),
+        lambda_2: amount(delta_1 * 2),
+        unfilled_1: amount(0),
+        unfilled_2: amount(0),
+        height: 1,
+        trading_pair: Some(trading_pair.into()),
+        ..Default::default()
+    }
+    .try_into()
+    .unwrap()
+}
+
+fn has_denom(txp: &TransactionPerspective, id: &Id) -> bool {
+    txp.denoms.get(id).is_some()
+}
+
+fn has_address_view(txp: &TransactionPerspective, address: &Address) -> bool {
+    txp.address_views
+        .iter()
+        .any(|view| &view.address() == address)
+}
+
+#[wasm_bindgen_test]
+async fn spends_reveal_spent_notes() {
+    let mut fixture = Fixture::new().await;
+    let record = fixture.add_note(staking_value(1_000)).await;
+
+    let tx = fixture.build(
+        vec![SpendPlan::new(&mut OsRng, record.note.clone(), record.position).into()],
+        None,
+    );
+    let (txp, txv) = fixture.perspective(&tx).await;
+
+    assert_eq!(
+        txp.spend_nullifiers.get(&record.nullifier),
+        Some(&record.note)
+    );
+    assert!(has_denom(&txp, &STAKING_TOKEN_ASSET_ID));
+    assert!(has_address_view(&txp, &fixture.address()));
+    assert!(matches!(
+        txv.action_views().next(),
+        Some(ActionView::Spend(SpendView::Visible { .. }))
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn unknown_spends_stay_opaque() {
+    let mut fixture = Fixture::new().await;
+    let record = fixture.add_note(staking_value(1_000)).await;
+    let tx = fixture.build(
+        vec![SpendPlan::new(&mut OsRng, record.note, record.position).into()],
+        None,
+    );
+
+    // A wallet without the note record cannot open the spend.
+    let empty = Fixture::new().await;
+    let (txp, txv) = empty.perspective(&tx).await;
+
+    assert!(txp.spend_nullifiers.is_empty());
+    assert!(matches!(
+        txv.action_views().next(),
+        Some(ActionView::Spend(SpendView::Opaque { .. }))
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn outputs_reveal_recipient_and_denom() {
+    let fixture = Fixture::new().await;
+    let recipient = fixture.fvk.payment_address(AddressIndex::new(1)).0;
+
+    let tx = fixture.build(
+        vec![OutputPlan::new(&mut OsRng, staking_value(500), recipient.clone()).into()],
+        Some(memo_plan(fixture.address())),
+    );
+    let (txp, txv) = fixture.perspective(&tx).await;
+
+    assert!(has_denom(&txp, &STAKING_TOKEN_ASSET_ID));
+    assert!(has_address_view(&txp, &recipient));
+    assert!(has_address_view(&txp, &fixture.address()));
+    assert!(matches!(
+        txv.action_views().next(),
+        Some(ActionView::Output(OutputView::Visible { .. }))
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn swaps_reveal_output_data_and_claim_transaction() {
+    let mut fixture = Fixture::new().await;
+    let delegation_metadata = delegation_token().denom();
+    fixture.add_asset(&delegation_metadata).await;
+
+    let trading_pair = TradingPair::new(*STAKING_TOKEN_ASSET_ID, delegation_metadata.id());
+    let swap_plaintext = SwapPlaintext::new(
+        &mut OsRng,
+        trading_pair,
+        100u64.into(),
+        0u64.into(),
+        Fee::from_staking_token_amount(0u64.into()),
+        fixture.address(),
+    );
+    let swap_tx = fixture.build(
+        vec![SwapPlan::new(&mut OsRng, swap_plaintext.clone()).into()],
+        None,
+    );
+
+    // Commit the swap and claim it, so the claim can be linked back to the swap.
+    let swap_commitment = swap_plaintext.swap_commitment();
+    let position = fixture.sct.insert(Witness::Keep, swap_commitment).unwrap();
+    fixture.commitments.push(swap_commitment);
+    let output_data = batch_swap_output_data(trading_pair, 100);
+    fixture
+        .put_swap_record(&SwapRecord {
+            swap_commitment,
+            swap: swap_plaintext.clone(),
+            position,
+            nullifier: Nullifier::derive(fixture.fvk.nullifier_key(), position, &swap_commitment),
+            output_data,
+            height_claimed: None,
+            source: CommitmentSource::Transaction {
+                id: Some(swap_tx.id().0),
+            },
+        })
+        .await;
+
+    let claim_tx = fixture.build(
+        vec![ActionPlan::SwapClaim(SwapClaimPlan {
+            swap_plaintext,
+            position,
+            output_data,
+            epoch_duration: 1000,
+            proof_blinding_r: Fq::rand(&mut OsRng),
+            proof_blinding_s: Fq::rand(&mut OsRng),
+        })],
+        None,
+    );
+    fixture
+        .mock_db
+        .put_with_key(
+            &fixture.tables.transactions,
+            "claim",
+            &TransactionInfo {
+                height: 2,
+                id: Some(claim_tx.id().into()),
+                transaction: Some(claim_tx.into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let (txp, txv) = fixture.perspective(&swap_tx).await;
+
+    assert_eq!(txp.batch_swap_output_data, vec![output_data]);
+    assert!(txp
+        .nullification_transaction_ids_by_commitment
+        .contains_key(&swap_commitment));
+    assert!(has_denom(&txp, &STAKING_TOKEN_ASSET_ID));
+    assert!(has_denom(&txp, &delegation_metadata.id()));
+    assert!(has_address_view(&txp, &fixture.address()));
+    assert!(matches!(
+        txv.action_views().next(),
+        Some(ActionView::Swap(_))
+    ));
+}
+
+/// A claim of a swap of 100 staking tokens for delegation tokens, with the swap committed to the
+/// fixture's SCT. Returns the claim transaction and its two output notes.
+async fn swap_claim(fixture: &mut Fixture) -> (Transaction, SwapRecord, Note, Note) {
+    let delegation_metadata = delegation_token().denom();
+    fixture.add_asset(&delegation_metadata).await;
+
+    let trading_pair = TradingPair::new(*STAKING_TOKEN_ASSET_ID, delegation_metadata.id());
+    let swap_plaintext = SwapPlaintext::new(
+        &mut OsRng,
+        trading_pair,
+        100u64.into(),
+        0u64.into(),
+        Fee::from_staking_token_amount(0u64.into()),
+        fixture.address(),
+    );
+    let swap_commitment = swap_plaintext.swap_commitment();
+    let position = fixture.sct.insert(Witness::Keep, swap_commitment).unwrap();
+    fixture.commitments.push(swap_commitment);
+    let output_data = batch_swap_output_data(trading_pair, 100);
+
+    let swap_record = SwapRecord {
+        swap_commitment,
+        swap: swap_plaintext.clone(),
+        position,
+        nullifier: Nullifier::derive(fixture.fvk.nullifier_key(), position, &swap_commitment),
+        output_data,
+        height_claimed: None,
+        source: CommitmentSource::Transaction {
+            id: Some([7u8; 32]),
+        },
+    };
+    let (output_1, output_2) = swap_plaintext.output_notes(&output_data);
+
+    let tx = fixture.build(
+        vec![ActionPlan::SwapClaim(SwapClaimPlan {
+            swap_plaintext,
+            position,
+            output_data,
+            epoch_duration: 1000,
+            proof_blinding_r: Fq::rand(&mut OsRng),
+            proof_blinding_s: Fq::rand(&mut OsRng),
+        })],
+        None,
+    );
+    (tx, swap_record, output_1, output_2)
+}
+
+/// Records a swap claim output, as the view server does once the claim is scanned.
+async fn put_claim_output(fixture: &Fixture, note: &Note) {
+    fixture
+        .put_note_record(&SpendableNoteRecord {
+            note_commitment: note.commit(),
+            note: note.clone(),
+            address_index: AddressIndex::new(0),
+            nullifier: Nullifier(Fq::rand(&mut OsRng)),
+            height_created: 2,
+            height_spent: None,
+            position: Default::default(),
+            source: CommitmentSource::Genesis,
+            return_address: None,
+        })
+        .await;
+}
+
+#[wasm_bindgen_test]
+async fn swap_claims_reveal_outputs_and_swap_transaction() {
+    let mut fixture = Fixture::new().await;
+    let delegation_metadata = delegation_token().denom();
+    let (tx, swap_record, output_1, output_2) = swap_claim(&mut fixture).await;
+    fixture.put_swap_record(&swap_record).await;
+    put_claim_output(&fixture, &output_1).await;
+    put_claim_output(&fixture, &output_2).await;
+
+    let swap_tx_id = [7u8; 32];
+    let (txp, txv) = fixture.perspective(&tx).await;
+
+    assert_eq!(txp.advice_notes.get(&output_1.commit()), Some(&output_1));
+    assert_eq!(txp.advice_notes.get(&output_2.commit()), Some(&output_2));
+    assert_eq!(
+        txp.creation_transaction_ids_by_nullifier
+            .values()
+            .map(|id| id.0)
+            .collect::<Vec<_>>(),
+        vec![swap_tx_id]
+    );
+    assert!(has_denom(&txp, &STAKING_TOKEN_ASSET_ID));
+    assert!(has_denom(&txp, &delegation_metadata.id()));
+    assert!(has_address_view(&txp, &fixture.address()));
+    assert!(matches!(
+        txv.action_views().next(),
+        Some(ActionView::SwapClaim(_))
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn delegator_votes_reveal_staked_notes() {
+    let mut fixture = Fixture::new().await;
+    let delegation_metadata = delegation_token().denom();
+    fixture.add_asset(&delegation_metadata).await;
+
+    let record = fixture
+        .add_note(Value {
+            amount: 1_000u64.into(),
+            asset_id: delegation_metadata.id(),
+        })
+        .await;
+
+    let tx = fixture.build(
+        vec![ActionPlan::DelegatorVote(DelegatorVotePlan::new(
+            &mut OsRng,
+            42,
+            Default::default(),
+            Vote::Yes,
+            record.note.clone(),
+            record.position,
+            1_000u64.into(),
+        ))],
+        None,
+    );
+    let (txp, txv) = fixture.perspective(&tx).await;
+
+    assert_eq!(
+        txp.spend_nullifiers.get(&record.nullifier),
+        Some(&record.note)
+    );
+    assert!(has_denom(&txp, &delegation_metadata.id()));
+    assert!(has_address_view(&txp, &fixture.address()));
+    assert!(matches!(
+        txv.action_views().next(),
+        Some(ActionView::DelegatorVote(_))
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn dutch_auction_schedules_reveal_denoms() {
+    let fixture = Fixture::new().await;
+    let delegation_metadata = delegation_token().denom();
+    fixture.add_asset(&delegation_metadata).await;
+
+    let description = DutchAuctionDescription {
+        input: staking_value(1_000),
+        output_id: delegation_metadata.id(),
+        max_output: 2_000u64.into(),
+        min_output: 1_000u64.into(),
+        start_height: 10,
+        end_height: 20,
+        step_count: 10,
+        nonce: [0u8; 32],
+    };
+    let auction_id = description.id();
+    let opened_nft = AuctionNft::new(auction_id, 0).metadata;
+    let closed_nft = AuctionNft::new(auction_id, 1).metadata;
+
+    let tx = fixture.build(
+        vec![
+            ActionPlan::ActionDutchAuctionSchedule(ActionDutchAuctionSchedule { description }),
+            ActionPlan::ActionDutchAuctionEnd(ActionDutchAuctionEnd { auction_id }),
+        ],
+        None,
+    );
+    let (txp, txv) = fixture.perspective(&tx).await;
+
+    assert!(has_denom(&txp, &STAKING_TOKEN_ASSET_ID));
+    assert!(has_denom(&txp, &delegation_metadata.id()));
+    assert!(has_denom(&txp, &opened_nft.id()));
+    assert!(has_denom(&txp, &closed_nft.id()));
+    let views: Vec<_> = txv.action_views().collect();
+    assert!(matches!(
+        views[0],
+        ActionView::ActionDutchAuctionSchedule(_)
+    ));
+    assert!(matches!(views[1], ActionView::ActionDutchAuctionEnd(_)));
+}
+
+#[wasm_bindgen_test]
+async fn position_actions_reveal_denoms_and_metadata() {
+    let fixture = Fixture::new().await;
+    let delegation_metadata = delegation_token().denom();
+    fixture.add_asset(&delegation_metadata).await;
+    let position = LpPosition::new(
+        &mut OsRng,
+        DirectedTradingPair::new(*STAKING_TOKEN_ASSET_ID, delegation_metadata.id()),
+        30,
+        1u64.into(),
+        1u64.into(),
+        Reserves {
+            r1: 1_000u64.into(),
+            r2: 0u64.into(),
+        },
+    );
+    let position_id = position.id();
+
+    let tx = fixture.build(
+        vec![
+            ActionPlan::PositionOpen(PositionOpenPlan {
+                position,
+                metadata: Some(PositionMetadata {
+                    strategy: NonZeroU32::new(1).unwrap(),
+                    identifier: NonZeroU32::new(1).unwrap(),
+                }),
+            }),
+            ActionPlan::PositionClose(PositionClose { position_id }),
+        ],
+        None,
+    );
+    let (txp, txv) = fixture.perspective(&tx).await;
+
+    assert!(has_denom(&txp, &STAKING_TOKEN_ASSET_ID));
+    assert!(has_denom(&txp, &delegation_metadata.id()));
+    for state in [State::Opened, State::Closed] {
+        assert!(has_denom(
+            &txp,
+            &LpNft::new(position_id, state).denom().id()
+        ));
+    }
+    assert!(txp.position_metadata_key.is_some());
+
+    let views: Vec<_> = txv.action_views().collect();
+    assert!(matches!(views[0], ActionView::PositionOpen(_)));
+    assert!(matches!(views[1], ActionView::PositionClose(_)));
+}
+
+#[wasm_bindgen_test]
+async fn ics20_withdrawals_reveal_denom_and_return_address() {
+    let fixture = Fixture::new().await;
+
+    let withdrawal = ics20_withdrawal(1_000, Some(&fixture.address()));
+
+    let tx = fixture.build(
+        vec![ActionPlan::Ics20Withdrawal(withdrawal.try_into().unwrap())],
+        None,
+    );
+    let (txp, txv) = fixture.perspective(&tx).await;
+
+    assert!(has_denom(&txp, &STAKING_TOKEN_ASSET_ID));
+    assert!(has_address_view(&txp, &fixture.address()));
+    assert!(matches!(
+        txv.action_views().next(),
+        Some(ActionView::Ics20Withdrawal(_))
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn delegations_reveal_staking_tokens() {
+    let fixture = Fixture::new().await;
+    let rate_data: RateData = stake_pb::RateData {
+        identity_key: Some(delegation_token().validator().into()),
+        validator_reward_rate: Some(Amount::from(0u64).into()),
+        validator_exchange_rate: Some(Amount::from(100_000_000u64).into()),
+        ..Default::default()
+    }
+    .try_into()
+    .unwrap();
+    let epoch = Epoch {
+        index: 1,
+        start_height: 0,
+    };
+
+    let undelegate = rate_data.build_undelegate(epoch, 1_000u64.into());
+    let unbonding_metadata = undelegate.unbonding_token().denom();
+
+    let tx = fixture.build(
+        vec![
+            rate_data.build_delegate(epoch, 1_000u64.into()).into(),
+            undelegate.into(),
+        ],
+        None,
+    );
+    let (txp, txv) = fixture.perspective(&tx).await;
+
+    assert!(has_denom(&txp, &STAKING_TOKEN_ASSET_ID));
+    assert!(has_denom(&txp, &delegation_token().id()));
+    assert!(has_denom(&txp, &unbonding_metadata.id()));
+
+    let views: Vec<_> = txv.action_views().collect();
+    assert!(matches!(views[0], ActionView::Delegate(_)));
+    assert!(matches!(views[1], ActionView::Undelegate(_)));
+}
+
+#[wasm_bindgen_test]
+async fn batches_return_perspectives_in_order() {
+    let mut fixture = Fixture::new().await;
+    let record = fixture.add_note(staking_value(1_000)).await;
+
+    let spend_tx = fixture.build(
+        vec![SpendPlan::new(&mut OsRng, record.note.clone(), record.position).into()],
+        None,
+    );
+    let output_tx = fixture.build(
+        vec![OutputPlan::new(&mut OsRng, staking_value(500), fixture.address()).into()],
+        Some(memo_plan(fixture.address())),
+    );
+    let txs = vec![spend_tx.clone(), output_tx.clone(), spend_tx.clone()];
+
+    let storage = Storage::new(fixture.mock_db.clone(), fixture.tables.clone()).unwrap();
+    let infos = transaction_infos_inner(&storage, &fixture.fvk, &txs)
+        .await
+        .unwrap();
+
+    assert_eq!(infos.len(), 3);
+    for (info, tx) in infos.iter().zip(&txs) {
+        assert_eq!(info.txp.transaction_id, tx.id());
+    }
+
+    // Cached lookups give the same perspective as an uncached one.
+    let (single, _) = fixture.perspective(&spend_tx).await;
+    assert_eq!(infos[2].txp.spend_nullifiers, single.spend_nullifiers);
+    assert!(has_denom(&infos[2].txp, &STAKING_TOKEN_ASSET_ID));
+}
+
+#[wasm_bindgen_test]
+async fn partially_known_swap_claims_warn_about_missing_outputs() {
+    let mut fixture = Fixture::new().await;
+    let (tx, swap_record, output_1, output_2) = swap_claim(&mut fixture).await;
+    fixture.put_swap_record(&swap_record).await;
+    put_claim_output(&fixture, &output_1).await;
+
+    let info = fixture.perspective_and_view(&tx).await;
+
+    assert_eq!(
+        info.txp.advice_notes.get(&output_1.commit()),
+        Some(&output_1)
+    );
+    assert!(!info.txp.advice_notes.contains_key(&output_2.commit()));
+    assert_eq!(
+        info.warnings,
+        vec![PerspectiveWarning::UnknownSwapClaimOutput {
+            commitment: output_2.commit()
+        }]
+    );
+    assert!(matches!(
+        info.txv.action_views().next(),
+        Some(ActionView::SwapClaim(_))
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn batched_swap_claims_share_their_lookups() {
+    let mut fixture = Fixture::new().await;
+    let (tx, swap_record, output_1, output_2) = swap_claim(&mut fixture).await;
+    fixture.put_swap_record(&swap_record).await;
+    put_claim_output(&fixture, &output_1).await;
+    put_claim_output(&fixture, &output_2).await;
+
+    let storage = Storage::new(fixture.mock_db.clone(), fixture.tables.clone()).unwrap();
+    let infos = transaction_infos_inner(&storage, &fixture.fvk, &[tx.clone(), tx.clone()])
+        .await
+        .unwrap();
+
+    // The second claim is answered from the cache, and matches an uncached lookup.
+    let single = fixture.perspective_and_view(&tx).await;
+    for info in &infos {
+        assert_eq!(
+            info.txp.creation_transaction_ids_by_nullifier,
+            single.txp.creation_transaction_ids_by_nullifier
+        );
+        assert_eq!(info.txp.advice_notes, single.txp.advice_notes);
+        assert!(info.warnings.is_empty());
+    }
+}
+
+#[wasm_bindgen_test]
+async fn foreign_swap_claims_are_opaque_without_warnings() {
+    let mut fixture = Fixture::new().await;
+    let (tx, _, _, _) = swap_claim(&mut fixture).await;
+
+    // A different wallet, which knows neither the swap nor its outputs.
+    let other = Fixture::new().await;
+    let info = other.perspective_and_view(&tx).await;
+
+    assert!(info.txp.advice_notes.is_empty());
+    assert!(info.txp.creation_transaction_ids_by_nullifier.is_empty());
+    assert!(info.warnings.is_empty());
+    assert!(matches!(
+        info.txv.action_views().next(),
+        Some(ActionView::SwapClaim(SwapClaimView::Opaque { .. }))
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn outputs_without_memos_do_not_warn() {
+    let fixture = Fixture::new().await;
+
+    let tx = fixture.build(
+        vec![
+            OutputPlan::new(&mut OsRng, staking_value(500), fixture.address()).into(),
+            OutputPlan::new(&mut OsRng, staking_value(200), fixture.address()).into(),
+        ],
+        None,
+    );
+    let info = fixture.perspective_and_view(&tx).await;
+
+    assert!(info.warnings.is_empty(), "{:?}", info.warnings);
+}
+
+#[wasm_bindgen_test]
+async fn unreadable_memos_warn_once() {
+    let fixture = Fixture::new().await;
+
+    let mut tx = fixture.build(
+        vec![
+            OutputPlan::new(&mut OsRng, staking_value(500), fixture.address()).into(),
+            OutputPlan::new(&mut OsRng, staking_value(200), fixture.address()).into(),
+        ],
+        Some(memo_plan(fixture.address())),
+    );
+    // Replace the memo with one encrypted to a key the outputs don't carry.
+    let plaintext = MemoPlaintext::new(fixture.address(), "unreadable".to_string()).unwrap();
+    tx.transaction_body.memo =
+        Some(MemoCiphertext::encrypt(PayloadKey::random_key(&mut OsRng), &plaintext).unwrap());
+
+    let info = fixture.perspective_and_view(&tx).await;
+
+    assert_eq!(info.warnings.len(), 1, "{:?}", info.warnings);
+    assert!(matches!(
+        info.warnings[0],
+        PerspectiveWarning::UnreadableMemo { .. }
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn memos_decrypt_with_their_return_address() {
+    let fixture = Fixture::new().await;
+    let other = Fixture::other().await;
+    let return_address = fixture.fvk.payment_address(AddressIndex::new(2)).0;
+
+    let tx = fixture.build(
+        vec![OutputPlan::new(&mut OsRng, staking_value(0), other.address()).into()],
+        Some(memo_plan(return_address.clone())),
+    );
+
+    // The recipient sees the memo and the sender's return address, which is not one of theirs.
+    match memo_view(&other.fvk, &tx) {
+        Some(MemoView::Visible { plaintext, .. }) => {
+            assert_eq!(plaintext.text, "perspective test");
+            assert_eq!(plaintext.return_address.address(), return_address);
+            assert!(matches!(
+                plaintext.return_address,
+                AddressView::Opaque { .. }
+            ));
+        }
+        other => panic!("expected a visible memo, got {:?}", other),
+    }
+
+    // Anyone else only sees the ciphertext.
+    let outsider = Fixture::other().await;
+    assert!(matches!(
+        memo_view(&outsider.fvk, &tx),
+        Some(MemoView::Opaque { .. })
+    ));
+}
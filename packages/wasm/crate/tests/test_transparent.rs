@@ -0,0 +1,84 @@
+use penumbra_keys::keys::AddressIndex;
+use penumbra_keys::{Address, AddressView, FullViewingKey};
+use penumbra_proto::DomainType;
+use std::str::FromStr;
+
+use penumbra_wasm::keys::{get_transparent_address, is_controlled_address, is_controlled_inner};
+use penumbra_wasm::transparent::{
+    address_index, is_transparent_address, note_address_index, transparent_address, view_address,
+};
+
+use crate::utils::fixtures::{fvk, OTHER_FVK};
+
+mod utils;
+
+#[test]
+fn transparent_address_matches_its_encoding() {
+    let fvk = fvk();
+    let response = get_transparent_address(&fvk.encode_to_vec()).unwrap();
+    let address = transparent_address(&fvk).unwrap();
+
+    assert_eq!(response.address, address.encode_to_vec());
+    assert_eq!(Address::from_str(&response.encoding).unwrap(), address);
+    assert!(is_transparent_address(&fvk, &address));
+}
+
+#[test]
+fn transparent_address_belongs_to_account_zero() {
+    let fvk = fvk();
+    let address = transparent_address(&fvk).unwrap();
+
+    assert_eq!(note_address_index(&fvk, &address), AddressIndex::new(0));
+    assert_eq!(address_index(&fvk, &address), Some(AddressIndex::new(0)));
+    let AddressView::Decoded {
+        address: viewed,
+        index,
+        wallet_id,
+    } = view_address(&fvk, address.clone())
+    else {
+        panic!("the transparent address is decoded");
+    };
+    assert_eq!(viewed, address);
+    assert_eq!(index, AddressIndex::new(0));
+    assert_eq!(wallet_id, fvk.wallet_id());
+}
+
+#[test]
+fn other_addresses_keep_their_index() {
+    let fvk = fvk();
+    let index = AddressIndex::new(3);
+    let address = fvk.payment_address(index).0;
+
+    assert!(!is_transparent_address(&fvk, &address));
+    assert_eq!(note_address_index(&fvk, &address), index);
+    assert_eq!(address_index(&fvk, &address), Some(index));
+    assert_eq!(
+        view_address(&fvk, address.clone()).encode_to_vec(),
+        fvk.view_address(address).encode_to_vec()
+    );
+}
+
+#[test]
+fn another_wallets_transparent_address_is_opaque() {
+    let fvk = fvk();
+    let other = FullViewingKey::from_str(OTHER_FVK).unwrap();
+    let address = transparent_address(&other).unwrap();
+
+    assert!(!is_transparent_address(&fvk, &address));
+    assert_eq!(address_index(&fvk, &address), None);
+    assert!(matches!(
+        view_address(&fvk, address),
+        AddressView::Opaque { .. }
+    ));
+}
+
+#[test]
+fn transparent_addresses_are_controlled_by_their_wallet() {
+    let fvk = fvk();
+    let other = FullViewingKey::from_str(OTHER_FVK).unwrap();
+    let address = transparent_address(&fvk).unwrap();
+
+    assert!(is_controlled_inner(&fvk, &address));
+    assert!(is_controlled_address(&fvk.encode_to_vec(), &address.encode_to_vec()).unwrap());
+    assert!(!is_controlled_inner(&other, &address));
+}
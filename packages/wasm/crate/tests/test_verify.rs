@@ -0,0 +1,199 @@
+extern crate penumbra_wasm;
+use penumbra_asset::{Value, STAKING_TOKEN_ASSET_ID};
+use penumbra_fee::GasPrices;
+use penumbra_governance::{DelegatorVotePlan, Vote};
+use penumbra_keys::keys::{Bip44Path, SeedPhrase, SpendKey};
+use penumbra_shielded_pool::{Note, OutputPlan, SpendPlan};
+use penumbra_stake::{DelegationToken, IdentityKey};
+use penumbra_tct::{Tree, Witness};
+use penumbra_transaction::{ActionPlan, Transaction, TransactionPlan, WitnessData};
+use penumbra_wasm::build::build_serial_inner;
+use penumbra_wasm::keys::load_proving_key;
+use penumbra_wasm::verify::{verify_transaction_inner, Check};
+use rand_core::OsRng;
+use std::str::FromStr;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::utils::fixtures::{address, spend_key, staking_value, VALIDATOR};
+
+mod utils;
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+fn load_proving_keys() {
+    let spend_key: &[u8] = include_bytes!("../../../keys/keys/spend_pk.bin");
+    let output_key: &[u8] = include_bytes!("../../../keys/keys/output_pk.bin");
+
+    load_proving_key(spend_key, "spend").expect("can load spend key");
+    load_proving_key(output_key, "output").expect("can load output key");
+}
+
+/// Spends a 1000 staking token note into an output of `output_amount`, with the plan authorized
+/// by `signer`.
+fn build(signer: &SpendKey, output_amount: u64) -> (Transaction, Tree) {
+    load_proving_keys();
+
+    let fvk = spend_key().full_viewing_key().clone();
+    let address = address();
+
+    let note = Note::generate(&mut OsRng, &address, staking_value(1_000));
+    let mut sct = Tree::new();
+    let position = sct.insert(Witness::Keep, note.commit()).unwrap();
+
+    let plan = TransactionPlan {
+        actions: vec![
+            ActionPlan::Spend(SpendPlan::new(&mut OsRng, note.clone(), position)),
+            ActionPlan::Output(OutputPlan::new(
+                &mut OsRng,
+                staking_value(output_amount),
+                address,
+            )),
+        ],
+        ..Default::default()
+    };
+    let auth_data = plan.authorize(OsRng, signer).unwrap();
+    let witness_data = WitnessData {
+        anchor: sct.root(),
+        state_commitment_proofs: [(note.commit(), sct.witness(note.commit()).unwrap())]
+            .into_iter()
+            .collect(),
+    };
+    let tx = build_serial_inner(fvk, plan, witness_data, auth_data).unwrap();
+
+    (tx, sct)
+}
+
+#[wasm_bindgen_test]
+async fn well_formed_transactions_verify() {
+    let (tx, sct) = build(&spend_key(), 1_000);
+    let report = verify_transaction_inner(&tx, sct.root(), None, None);
+
+    assert!(report.valid, "{:?}", report);
+    assert_eq!(report.binding_signature, Check::Passed);
+    assert_eq!(report.anchor, Check::Passed);
+    assert!(matches!(report.fee, Check::Skipped { .. }));
+
+    assert_eq!(report.actions.len(), 2);
+    assert_eq!(report.actions[0].action, "spend");
+    assert_eq!(report.actions[0].signature, Some(Check::Passed));
+    assert_eq!(report.actions[0].proof, Check::Passed);
+    assert_eq!(report.actions[1].action, "output");
+    assert_eq!(report.actions[1].signature, None);
+    assert_eq!(report.actions[1].proof, Check::Passed);
+}
+
+#[wasm_bindgen_test]
+async fn stale_anchors_are_reported() {
+    let (tx, mut sct) = build(&spend_key(), 1_000);
+    sct.insert(
+        Witness::Forget,
+        Note::generate(&mut OsRng, &address(), staking_value(1)).commit(),
+    )
+    .unwrap();
+    let report = verify_transaction_inner(&tx, sct.root(), None, None);
+
+    assert!(!report.valid);
+    assert!(report.anchor.is_failed());
+    // The proofs themselves are still valid against the anchor they were made for.
+    assert_eq!(report.actions[0].proof, Check::Passed);
+}
+
+#[wasm_bindgen_test]
+async fn unbalanced_transactions_fail_the_binding_signature() {
+    let (tx, sct) = build(&spend_key(), 600);
+    let report = verify_transaction_inner(&tx, sct.root(), None, None);
+
+    assert!(!report.valid);
+    assert!(report.binding_signature.is_failed());
+    assert_eq!(report.actions[0].signature, Some(Check::Passed));
+}
+
+#[wasm_bindgen_test]
+async fn mismatched_authorization_fails_the_spend_signature() {
+    let other_signer =
+        SpendKey::from_seed_phrase_bip44(SeedPhrase::generate(OsRng), &Bip44Path::new(0));
+    let (tx, sct) = build(&other_signer, 1_000);
+    let report = verify_transaction_inner(&tx, sct.root(), None, None);
+
+    assert!(!report.valid);
+    assert!(report.actions[0]
+        .signature
+        .as_ref()
+        .is_some_and(Check::is_failed));
+}
+
+#[wasm_bindgen_test]
+async fn fees_below_gas_prices_are_reported() {
+    let (tx, sct) = build(&spend_key(), 1_000);
+    let gas_prices = GasPrices {
+        asset_id: *STAKING_TOKEN_ASSET_ID,
+        block_space_price: 1,
+        compact_block_space_price: 1,
+        verification_price: 1,
+        execution_price: 1,
+    };
+    let report = verify_transaction_inner(&tx, sct.root(), Some(&gas_prices), None);
+
+    assert!(!report.valid);
+    assert!(report.fee.is_failed());
+}
+
+#[wasm_bindgen_test]
+async fn vote_proofs_are_checked_against_the_vote_anchor() {
+    let delegator_vote_key: &[u8] = include_bytes!("../../../keys/keys/delegator_vote_pk.bin");
+    load_proving_key(delegator_vote_key, "delegatorVote").expect("can load delegator vote key");
+
+    let spend_key = spend_key();
+    let fvk = spend_key.full_viewing_key().clone();
+    let delegation_token = DelegationToken::from(IdentityKey::from_str(VALIDATOR).unwrap());
+    let value = Value {
+        amount: 1_000u64.into(),
+        asset_id: delegation_token.id(),
+    };
+
+    let mut sct = Tree::new();
+    let note = Note::generate(&mut OsRng, &address(), value);
+    let position = sct.insert(Witness::Keep, note.commit()).unwrap();
+    sct.end_epoch().unwrap();
+    let start_position = sct.position().unwrap();
+    let vote_anchor = sct.root();
+
+    let plan = TransactionPlan {
+        actions: vec![ActionPlan::DelegatorVote(DelegatorVotePlan::new(
+            &mut OsRng,
+            1,
+            start_position,
+            Vote::Yes,
+            note.clone(),
+            position,
+            1_000u64.into(),
+        ))],
+        ..Default::default()
+    };
+    let auth_data = plan.authorize(OsRng, &spend_key).unwrap();
+    let witness_data = WitnessData {
+        anchor: vote_anchor,
+        state_commitment_proofs: [(note.commit(), sct.witness(note.commit()).unwrap())]
+            .into_iter()
+            .collect(),
+    };
+    let tx = build_serial_inner(fvk, plan, witness_data, auth_data).unwrap();
+
+    let report = verify_transaction_inner(&tx, tx.anchor, None, None);
+    assert_eq!(report.actions[0].action, "delegatorVote");
+    assert!(matches!(report.actions[0].proof, Check::Skipped { .. }));
+
+    let report = verify_transaction_inner(&tx, tx.anchor, None, Some(vote_anchor));
+    assert!(report.valid, "{:?}", report);
+    assert_eq!(report.actions[0].signature, Some(Check::Passed));
+    assert_eq!(report.actions[0].proof, Check::Passed);
+
+    sct.insert(
+        Witness::Forget,
+        Note::generate(&mut OsRng, &address(), staking_value(1)).commit(),
+    )
+    .unwrap();
+    let report = verify_transaction_inner(&tx, tx.anchor, None, Some(sct.root()));
+    assert!(!report.valid);
+    assert!(report.actions[0].proof.is_failed());
+}
@@ -0,0 +1,806 @@
+use decaf377_fmd::Precision;
+use indexed_db_futures::prelude::*;
+use penumbra_compact_block::{CompactBlock as DomainCompactBlock, StatePayload};
+use penumbra_keys::keys::AddressIndex;
+use penumbra_keys::{Address, AddressView, FullViewingKey, PayloadKey};
+use penumbra_proof_params::GROTH16_PROOF_LENGTH_BYTES;
+use penumbra_proto::core::component::compact_block::v1::CompactBlock;
+use penumbra_proto::core::component::shielded_pool::v1::ZkOutputProof;
+use penumbra_proto::crypto::decaf377_fmd::v1 as fmd_pb;
+use penumbra_proto::{DomainType, Message};
+use penumbra_sct::CommitmentSource;
+use penumbra_shielded_pool::{fmd, output, Note, Output, OutputPlan, OutputProof};
+use penumbra_tct::{Tree, Witness};
+use penumbra_transaction::memo::{MemoCiphertext, MemoPlaintext};
+use penumbra_transaction::{Action, Transaction, TransactionBody};
+use rand_core::{OsRng, RngCore};
+use std::str::FromStr;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use penumbra_wasm::database::indexed_db::open_idb_database;
+use penumbra_wasm::database::interface::Database;
+use penumbra_wasm::database::mock::get_mock_tables;
+use penumbra_wasm::error::WasmError;
+use penumbra_wasm::fmd::{FmdStats, NoteClue};
+use penumbra_wasm::invoice::{create_invoice, get_invoice_status, InvoiceState, InvoiceStatus};
+use penumbra_wasm::note_record::SpendableNoteRecord;
+use penumbra_wasm::storage::{init_idb_storage, invoice_key, DbConstants};
+use penumbra_wasm::transparent::transparent_address;
+use penumbra_wasm::view_server::{ScanBlockResult, StoredTree, ViewServer};
+
+use crate::utils::fixtures::{fvk, staking_value, OTHER_FVK};
+
+mod utils;
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+fn idb_constants() -> JsValue {
+    let constants = DbConstants {
+        name: "penumbra-view-server-test".to_string(),
+        version: 1,
+        tables: get_mock_tables(),
+    };
+    serde_wasm_bindgen::to_value(&constants).unwrap()
+}
+
+async fn new_view_server() -> ViewServer {
+    let fvk = fvk();
+    let stored_tree = StoredTree {
+        last_position: None,
+        last_forgotten: None,
+        hashes: vec![],
+        commitments: vec![],
+    };
+
+    ViewServer::new(
+        &fvk.encode_to_vec(),
+        serde_wasm_bindgen::to_value(&stored_tree).unwrap(),
+        idb_constants(),
+        idb_constants(),
+    )
+    .await
+    .expect("view server can be created")
+}
+
+/// An encoded compact block at `height` containing no state payloads.
+fn empty_compact_block(height: u64) -> Vec<u8> {
+    let block_root = Tree::new().end_block().unwrap();
+    CompactBlock {
+        height,
+        block_root: Some(block_root.to_proto()),
+        ..Default::default()
+    }
+    .encode_to_vec()
+}
+
+/// Constants for a database whose tables exist, for tests that scan notes and so read storage.
+fn scanning_idb_constants() -> DbConstants {
+    DbConstants {
+        name: "penumbra-view-server-scan-test".to_string(),
+        version: 1,
+        tables: get_mock_tables(),
+    }
+}
+
+/// A view server for `fvk` backed by a database with every table created and FMD parameters
+/// stored.
+async fn new_scanning_view_server(fvk: &FullViewingKey) -> ViewServer {
+    let constants = scanning_idb_constants();
+
+    let tables = serde_json::to_value(&constants.tables).unwrap();
+    let names: Vec<String> = tables
+        .as_object()
+        .unwrap()
+        .values()
+        .map(|name| name.as_str().unwrap().to_string())
+        .collect();
+    let mut request = IdbDatabase::open_u32(&constants.name, constants.version).unwrap();
+    request.set_on_upgrade_needed(Some(
+        move |event: &IdbVersionChangeEvent| -> Result<(), JsValue> {
+            for name in &names {
+                event.db().create_object_store(name)?;
+            }
+            Ok(())
+        },
+    ));
+    request.await.unwrap().close();
+
+    let params = fmd::Parameters {
+        precision: Default::default(),
+        as_of_block_height: 0,
+    };
+    open_idb_database(&constants)
+        .await
+        .unwrap()
+        .put_with_key(&constants.tables.fmd_parameters, "params", &params)
+        .await
+        .unwrap();
+
+    let stored_tree = StoredTree {
+        last_position: None,
+        last_forgotten: None,
+        hashes: vec![],
+        commitments: vec![],
+    };
+    // The test database holds every table, so it stands in for the wallet data database too.
+    ViewServer::new(
+        &fvk.encode_to_vec(),
+        serde_wasm_bindgen::to_value(&stored_tree).unwrap(),
+        serde_wasm_bindgen::to_value(&constants).unwrap(),
+        serde_wasm_bindgen::to_value(&constants).unwrap(),
+    )
+    .await
+    .expect("view server can be created")
+}
+
+/// An output note of the staking token to `address`.
+fn output_plan(address: &Address) -> OutputPlan {
+    OutputPlan::new(&mut OsRng, staking_value(1_000), address.clone())
+}
+
+/// An encoded compact block at `height` containing the note payload of each output.
+fn note_block(height: u64, outputs: &[OutputPlan]) -> Vec<u8> {
+    let sender = fvk();
+    let bodies: Vec<output::Body> = outputs
+        .iter()
+        .map(|output| output.output_body(sender.outgoing(), &PayloadKey::random_key(&mut OsRng)))
+        .collect();
+    output_block(height, &bodies)
+}
+
+/// An encoded compact block at `height` containing the note payload of each output body.
+fn output_block(height: u64, bodies: &[output::Body]) -> Vec<u8> {
+    let mut sct = Tree::new();
+    let mut state_payloads = Vec::new();
+
+    for body in bodies {
+        sct.insert(Witness::Forget, body.note_payload.note_commitment)
+            .unwrap();
+        state_payloads.push(StatePayload::Note {
+            source: CommitmentSource::Genesis,
+            note: Box::new(body.note_payload.clone()),
+        });
+    }
+
+    DomainCompactBlock {
+        height,
+        state_payloads,
+        block_root: sct.end_block().unwrap(),
+        ..Default::default()
+    }
+    .encode_to_vec()
+}
+
+/// An unproven transaction with one output per body and a memo carrying `return_address`.
+/// The view server never checks proofs or signatures, so this is enough to enrich notes.
+fn memo_transaction(
+    bodies: &[output::Body],
+    memo_key: &PayloadKey,
+    return_address: &Address,
+) -> Transaction {
+    let plaintext = MemoPlaintext::new(return_address.clone(), "thanks".to_string()).unwrap();
+    let actions = bodies
+        .iter()
+        .map(|body| {
+            Action::Output(Output {
+                body: body.clone(),
+                proof: OutputProof::try_from(ZkOutputProof {
+                    inner: vec![0; GROTH16_PROOF_LENGTH_BYTES],
+                })
+                .unwrap(),
+            })
+        })
+        .collect();
+
+    Transaction {
+        transaction_body: TransactionBody {
+            actions,
+            memo: Some(MemoCiphertext::encrypt(*memo_key, &plaintext).unwrap()),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// An FMD clue for `note` at the highest precision, created from its address's own clue key.
+fn note_clue(note: &Note) -> NoteClue {
+    let clue = note
+        .address()
+        .clue_key()
+        .expand()
+        .unwrap()
+        .create_clue(Precision::new(24).unwrap(), OsRng)
+        .unwrap();
+    NoteClue {
+        note_commitment: note.commit(),
+        clue: fmd_pb::Clue { inner: clue.into() },
+    }
+}
+
+/// Deterministic xorshift generator so failures are reproducible.
+fn next_byte(state: &mut u64) -> u8 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    (*state >> 24) as u8
+}
+
+#[wasm_bindgen_test]
+async fn rejects_invalid_frontier() {
+    let fvk = fvk();
+
+    let err = ViewServer::new_snapshot(
+        &fvk.encode_to_vec(),
+        idb_constants(),
+        idb_constants(),
+        &[0xde, 0xad],
+    )
+    .await
+    .err()
+    .expect("corrupt frontier is rejected");
+
+    assert!(matches!(err, WasmError::InvalidFrontier(_)));
+}
+
+#[wasm_bindgen_test]
+async fn rejects_genesis_chunk_out_of_order() {
+    let mut view_server = new_view_server().await;
+
+    let err = view_server
+        .scan_genesis_chunk(5, &empty_compact_block(0), false)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        WasmError::GenesisChunkOutOfOrder {
+            expected: 0,
+            actual: 5
+        }
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn rejects_replayed_block() {
+    let mut view_server = new_view_server().await;
+
+    view_server
+        .scan_block(&empty_compact_block(10), false)
+        .await
+        .unwrap();
+    let err = view_server
+        .scan_block(&empty_compact_block(10), false)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        WasmError::BlockOutOfSequence {
+            expected: 11,
+            actual: 10
+        }
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn rejects_skipped_height() {
+    let mut view_server = new_view_server().await;
+
+    view_server
+        .scan_block(&empty_compact_block(10), false)
+        .await
+        .unwrap();
+    let err = view_server
+        .scan_block(&empty_compact_block(12), false)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        WasmError::BlockOutOfSequence {
+            expected: 11,
+            actual: 12
+        }
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn reports_first_divergent_height() {
+    let mut view_server = new_view_server().await;
+
+    view_server
+        .scan_block(&empty_compact_block(10), false)
+        .await
+        .unwrap();
+
+    // Claim the epoch ends here, reusing the empty block root as a bogus epoch root.
+    let empty_block_root = Tree::new().end_block().unwrap();
+    let diverging_block = CompactBlock {
+        height: 11,
+        block_root: Some(empty_block_root.to_proto()),
+        epoch_root: Some(empty_block_root.to_proto()),
+        ..Default::default()
+    }
+    .encode_to_vec();
+
+    let err = view_server
+        .scan_block(&diverging_block, false)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        WasmError::SctDivergence {
+            height: 11,
+            root: "epoch"
+        }
+    ));
+
+    // Later blocks keep reporting the first divergent height.
+    let err = view_server
+        .scan_block(&empty_compact_block(12), false)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        WasmError::SctDivergence {
+            height: 11,
+            root: "epoch"
+        }
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn random_bytes_do_not_panic() {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+
+    for _ in 0..256 {
+        let len = next_byte(&mut state) as usize;
+        let bytes: Vec<u8> = (0..len).map(|_| next_byte(&mut state)).collect();
+
+        // A fresh server for each input, so that an input that happens to decode does not put
+        // every later one out of sequence.
+        let mut view_server = new_view_server().await;
+        let scanned = view_server.scan_block(&bytes, false).await;
+        let chunk = view_server.scan_genesis_chunk(0, &bytes, false).await;
+
+        if DomainCompactBlock::decode(bytes.as_slice()).is_err() {
+            assert!(scanned.is_err());
+            assert!(chunk.is_err());
+        }
+    }
+}
+
+/// A block at height 0 with two notes for the wallet, so that scanning it trial decrypts its
+/// payloads, rebuilds the block in the SCT and checks the block root.
+fn two_note_block(fvk: &FullViewingKey) -> (Vec<u8>, Vec<Note>) {
+    let (address, _) = fvk.payment_address(AddressIndex::new(0));
+    let outputs = [output_plan(&address), output_plan(&address)];
+    let notes = outputs.iter().map(OutputPlan::output_note).collect();
+    (note_block(0, &outputs), notes)
+}
+
+/// Asserts that a view server that rejected a corrupted block can still scan the valid one.
+/// A divergent root is reported until the server is reset from storage, so a fresh server
+/// stands in for the reset.
+async fn assert_recovers(
+    fvk: &FullViewingKey,
+    mut view_server: ViewServer,
+    err: WasmError,
+    valid: &[u8],
+) {
+    if let WasmError::SctDivergence { height, root } = err {
+        assert!(matches!(
+            view_server.scan_block(valid, false).await,
+            Err(WasmError::SctDivergence { height: h, root: r }) if h == height && r == root
+        ));
+        view_server = new_scanning_view_server(fvk).await;
+    }
+
+    assert!(view_server.scan_block(valid, false).await.unwrap());
+    let result: ScanBlockResult =
+        serde_wasm_bindgen::from_value(view_server.flush_updates().unwrap()).unwrap();
+    assert_eq!(result.new_notes.len(), 2);
+}
+
+#[wasm_bindgen_test]
+async fn truncated_compact_blocks_are_rejected() {
+    let fvk = fvk();
+    let (valid, _) = two_note_block(&fvk);
+
+    for len in 0..valid.len() {
+        let truncated = &valid[..len];
+        let mut view_server = new_scanning_view_server(&fvk).await;
+        let result = view_server.scan_block(truncated, false).await;
+
+        // Cut down to its height, the block is a valid empty block. Any other truncation cuts a
+        // field short, or drops the block root so that it no longer matches the rebuilt block.
+        let empty = DomainCompactBlock::decode(truncated)
+            .is_ok_and(|block| block.state_payloads.is_empty());
+        if empty {
+            assert!(!result.unwrap());
+            continue;
+        }
+
+        let err = result.expect_err("a truncated block is rejected");
+        assert_recovers(&fvk, view_server, err, &valid).await;
+    }
+}
+
+#[wasm_bindgen_test]
+async fn corrupted_commitments_and_roots_are_rejected() {
+    let fvk = fvk();
+    let (valid, notes) = two_note_block(&fvk);
+    let block = DomainCompactBlock::decode(valid.as_slice()).unwrap();
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+
+    // Corrupting a commitment or the block root either fails to decode, or changes the root of
+    // the rebuilt block; the other note keeps the block on the trial-decrypted path.
+    let targets = notes
+        .iter()
+        .map(|note| note.commit().0.to_bytes())
+        .chain([block.block_root.to_proto().inner.try_into().unwrap()]);
+    for target in targets {
+        let start = valid
+            .windows(target.len())
+            .position(|window| window == target)
+            .unwrap();
+
+        for index in start..start + target.len() {
+            let mut corrupted = valid.clone();
+            corrupted[index] ^= next_byte(&mut state) | 1;
+
+            let mut view_server = new_scanning_view_server(&fvk).await;
+            let err = view_server
+                .scan_block(&corrupted, false)
+                .await
+                .expect_err("a corrupted block is rejected");
+            assert_recovers(&fvk, view_server, err, &valid).await;
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+async fn scans_for_multiple_wallets() {
+    let mut view_server = new_view_server().await;
+    let other_fvk = FullViewingKey::from_str(OTHER_FVK).unwrap();
+
+    view_server.add_wallet(&other_fvk.encode_to_vec()).unwrap();
+
+    let (other_address, _) = other_fvk.payment_address(AddressIndex::new(0));
+    assert!(view_server
+        .is_controlled_address(&other_address.encode_to_vec())
+        .unwrap());
+
+    view_server
+        .scan_block(&empty_compact_block(1), false)
+        .await
+        .unwrap();
+
+    let updates: Vec<ScanBlockResult> =
+        serde_wasm_bindgen::from_value(view_server.flush_wallet_updates().unwrap()).unwrap();
+    assert_eq!(updates.len(), 2);
+}
+
+#[wasm_bindgen_test]
+async fn rejects_duplicate_or_late_wallets() {
+    let mut view_server = new_view_server().await;
+    let fvk = fvk();
+    let other_fvk = FullViewingKey::from_str(OTHER_FVK).unwrap();
+
+    assert!(view_server.add_wallet(&fvk.encode_to_vec()).is_err());
+
+    view_server
+        .scan_block(&empty_compact_block(1), false)
+        .await
+        .unwrap();
+    assert!(view_server.add_wallet(&other_fvk.encode_to_vec()).is_err());
+}
+
+#[wasm_bindgen_test]
+async fn transactions_without_memos_enrich_nothing() {
+    let mut view_server = new_view_server().await;
+
+    let enriched: Vec<SpendableNoteRecord> = serde_wasm_bindgen::from_value(
+        view_server
+            .enrich_transaction(&Transaction::default().encode_to_vec())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert!(enriched.is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn fmd_does_not_lose_notes_to_one_time_addresses() {
+    let fvk = fvk();
+    let mut view_server = new_scanning_view_server(&fvk).await;
+    view_server.enable_fmd(1, false).await.unwrap();
+
+    // One-time addresses have no detection key, so their clues are never detected.
+    let (address, _) = fvk.ephemeral_address(OsRng, AddressIndex::new(0));
+    let output = output_plan(&address);
+    let clues = serde_wasm_bindgen::to_value(&vec![note_clue(&output.output_note())]).unwrap();
+
+    let found = view_server
+        .scan_block_with_clues(&note_block(0, &[output.clone()]), clues, false)
+        .await
+        .unwrap();
+    assert!(found);
+
+    let result: ScanBlockResult =
+        serde_wasm_bindgen::from_value(view_server.flush_updates().unwrap()).unwrap();
+    assert_eq!(result.new_notes.len(), 1);
+    assert_eq!(result.new_notes[0].note, output.output_note());
+
+    let stats: FmdStats = serde_wasm_bindgen::from_value(view_server.fmd_stats().unwrap()).unwrap();
+    assert_eq!(stats.examined, 1);
+    assert_eq!(stats.detected, 0);
+    assert_eq!(stats.missed, 1);
+}
+
+#[wasm_bindgen_test]
+async fn fmd_skips_undetected_notes_when_asked_to() {
+    let fvk = fvk();
+    let mut view_server = new_scanning_view_server(&fvk).await;
+    view_server.enable_fmd(1, true).await.unwrap();
+
+    let (address, _) = fvk.ephemeral_address(OsRng, AddressIndex::new(0));
+    let output = output_plan(&address);
+    let clues = serde_wasm_bindgen::to_value(&vec![note_clue(&output.output_note())]).unwrap();
+
+    let found = view_server
+        .scan_block_with_clues(&note_block(0, &[output]), clues, false)
+        .await
+        .unwrap();
+    assert!(!found);
+}
+
+#[wasm_bindgen_test]
+async fn fmd_stats_ignore_blocks_scanned_without_trial_decryption() {
+    let fvk = fvk();
+    let mut view_server = new_scanning_view_server(&fvk).await;
+    view_server.enable_fmd(1, false).await.unwrap();
+
+    let (address, _) = fvk.payment_address(AddressIndex::new(0));
+    let output = output_plan(&address);
+    let clues = serde_wasm_bindgen::to_value(&vec![note_clue(&output.output_note())]).unwrap();
+
+    let found = view_server
+        .scan_block_with_clues(&note_block(0, &[output]), clues, true)
+        .await
+        .unwrap();
+    assert!(!found);
+
+    let stats: FmdStats = serde_wasm_bindgen::from_value(view_server.fmd_stats().unwrap()).unwrap();
+    assert_eq!(stats.examined, 0);
+    assert_eq!(stats.detected, 0);
+}
+
+#[wasm_bindgen_test]
+async fn attributes_notes_to_the_wallet_that_decrypts_them() {
+    let fvk = fvk();
+    let other_fvk = FullViewingKey::from_str(OTHER_FVK).unwrap();
+    let mut view_server = new_scanning_view_server(&fvk).await;
+    view_server.add_wallet(&other_fvk.encode_to_vec()).unwrap();
+
+    let (other_address, _) = other_fvk.payment_address(AddressIndex::new(1));
+    let output = output_plan(&other_address);
+
+    let found = view_server
+        .scan_block(&note_block(0, &[output.clone()]), false)
+        .await
+        .unwrap();
+    assert!(found);
+
+    let updates: Vec<ScanBlockResult> =
+        serde_wasm_bindgen::from_value(view_server.flush_wallet_updates().unwrap()).unwrap();
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[0].wallet_id, fvk.wallet_id());
+    assert!(updates[0].new_notes.is_empty());
+    assert_eq!(updates[1].wallet_id, other_fvk.wallet_id());
+    assert_eq!(updates[1].new_notes.len(), 1);
+    assert_eq!(updates[1].new_notes[0].note, output.output_note());
+    assert_eq!(updates[1].new_notes[0].address_index, AddressIndex::new(1));
+}
+
+#[wasm_bindgen_test]
+async fn ignores_notes_that_belong_to_no_wallet() {
+    let fvk = fvk();
+    let other_fvk = FullViewingKey::from_str(OTHER_FVK).unwrap();
+    let mut view_server = new_scanning_view_server(&fvk).await;
+
+    // Only the primary wallet is scanned, so a note to the other wallet is nobody's.
+    let (other_address, _) = other_fvk.payment_address(AddressIndex::new(0));
+
+    let found = view_server
+        .scan_block(&note_block(0, &[output_plan(&other_address)]), false)
+        .await
+        .unwrap();
+    assert!(!found);
+
+    let updates: Vec<ScanBlockResult> =
+        serde_wasm_bindgen::from_value(view_server.flush_wallet_updates().unwrap()).unwrap();
+    assert_eq!(updates.len(), 1);
+    assert!(updates[0].new_notes.is_empty());
+    assert!(updates[0].new_swaps.is_empty());
+    assert_eq!(updates[0].height, 0);
+}
+
+#[wasm_bindgen_test]
+async fn enriches_notes_with_the_memo_return_address() {
+    let fvk = fvk();
+    let mut view_server = new_scanning_view_server(&fvk).await;
+
+    let (address, _) = fvk.payment_address(AddressIndex::new(0));
+    let (return_address, _) = fvk.payment_address(AddressIndex::new(2));
+    let memo_key = PayloadKey::random_key(&mut OsRng);
+    let output = output_plan(&address);
+    let body = output.output_body(fvk.outgoing(), &memo_key);
+
+    view_server
+        .scan_block(&output_block(0, &[body.clone()]), false)
+        .await
+        .unwrap();
+
+    let tx = memo_transaction(&[body], &memo_key, &return_address);
+    let enriched: Vec<SpendableNoteRecord> = serde_wasm_bindgen::from_value(
+        view_server
+            .enrich_transaction(&tx.encode_to_vec())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+
+    // The return address belongs to the wallet, so its view is decoded to its account.
+    let expected = AddressView::Decoded {
+        address: return_address,
+        index: AddressIndex::new(2),
+        wallet_id: fvk.wallet_id(),
+    };
+    assert_eq!(enriched.len(), 1);
+    assert_eq!(enriched[0].note, output.output_note());
+    assert_eq!(enriched[0].return_address, Some(expected.clone()));
+
+    // The unflushed record was updated in place.
+    let result: ScanBlockResult =
+        serde_wasm_bindgen::from_value(view_server.flush_updates().unwrap()).unwrap();
+    assert_eq!(result.new_notes.len(), 1);
+    assert_eq!(result.new_notes[0].return_address, Some(expected));
+}
+
+#[wasm_bindgen_test]
+async fn foreign_return_addresses_are_enriched_as_opaque() {
+    let fvk = fvk();
+    let other_fvk = FullViewingKey::from_str(OTHER_FVK).unwrap();
+    let mut view_server = new_scanning_view_server(&fvk).await;
+
+    let (address, _) = fvk.payment_address(AddressIndex::new(0));
+    let (return_address, _) = other_fvk.payment_address(AddressIndex::new(0));
+    let memo_key = PayloadKey::random_key(&mut OsRng);
+    let body = output_plan(&address).output_body(other_fvk.outgoing(), &memo_key);
+
+    view_server
+        .scan_block(&output_block(0, &[body.clone()]), false)
+        .await
+        .unwrap();
+
+    let tx = memo_transaction(&[body], &memo_key, &return_address);
+    let enriched: Vec<SpendableNoteRecord> = serde_wasm_bindgen::from_value(
+        view_server
+            .enrich_transaction(&tx.encode_to_vec())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(enriched.len(), 1);
+    assert_eq!(
+        enriched[0].return_address,
+        Some(AddressView::Opaque {
+            address: return_address
+        })
+    );
+}
+
+#[wasm_bindgen_test]
+async fn enriches_stored_notes_sent_to_the_transparent_address() {
+    let fvk = fvk();
+    let mut view_server = new_scanning_view_server(&fvk).await;
+
+    let address = transparent_address(&fvk).unwrap();
+    let (return_address, _) = fvk.payment_address(AddressIndex::new(1));
+    let memo_key = PayloadKey::random_key(&mut OsRng);
+    let output = output_plan(&address);
+    let body = output.output_body(fvk.outgoing(), &memo_key);
+
+    view_server
+        .scan_block(&output_block(0, &[body.clone()]), false)
+        .await
+        .unwrap();
+
+    // Save the flushed record, as the extension does, so that enriching has to find it in storage.
+    let result: ScanBlockResult =
+        serde_wasm_bindgen::from_value(view_server.flush_updates().unwrap()).unwrap();
+    assert_eq!(result.new_notes.len(), 1);
+    let storage = init_idb_storage(scanning_idb_constants()).await.unwrap();
+    storage.save_note(&result.new_notes[0]).await.unwrap();
+
+    let tx = memo_transaction(&[body], &memo_key, &return_address);
+    let enriched: Vec<SpendableNoteRecord> = serde_wasm_bindgen::from_value(
+        view_server
+            .enrich_transaction(&tx.encode_to_vec())
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+
+    let expected = AddressView::Decoded {
+        address: return_address,
+        index: AddressIndex::new(1),
+        wallet_id: fvk.wallet_id(),
+    };
+    assert_eq!(enriched.len(), 1);
+    assert_eq!(enriched[0].note, output.output_note());
+    assert_eq!(enriched[0].return_address, Some(expected.clone()));
+
+    let stored = storage
+        .get_note(&output.output_note().commit())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.return_address, Some(expected));
+}
+
+#[wasm_bindgen_test]
+async fn invoice_payments_are_saved_with_the_flush() {
+    let fvk = fvk();
+    let mut view_server = new_scanning_view_server(&fvk).await;
+    let constants = || serde_wasm_bindgen::to_value(&scanning_idb_constants()).unwrap();
+
+    // The database outlives the test, so each run pays a fresh invoice.
+    let invoice_id = format!("scan-{}", OsRng.next_u64());
+    let requested = staking_value(1_500);
+    let address = create_invoice(
+        &fvk.encode_to_vec(),
+        0,
+        &invoice_id,
+        &requested.encode_to_vec(),
+        constants(),
+    )
+    .await
+    .unwrap();
+    let address = Address::decode(address.as_slice()).unwrap();
+
+    // Both payments land in one block, and the second is credited on top of the first.
+    let found = view_server
+        .scan_block(
+            &note_block(0, &[output_plan(&address), output_plan(&address)]),
+            false,
+        )
+        .await
+        .unwrap();
+    assert!(found);
+
+    // Nothing is saved mid-scan: the invoice is only updated with the flushed notes.
+    let stored = get_invoice_status(&fvk.encode_to_vec(), 0, &invoice_id, constants())
+        .await
+        .unwrap();
+    let stored: InvoiceStatus = serde_wasm_bindgen::from_value(stored).unwrap();
+    assert_eq!(stored.state, InvoiceState::Unpaid);
+
+    let result: ScanBlockResult =
+        serde_wasm_bindgen::from_value(view_server.flush_updates().unwrap()).unwrap();
+    assert_eq!(result.new_notes.len(), 2);
+    assert_eq!(result.invoice_updates.len(), 1);
+    let update = &result.invoice_updates[0];
+    assert_eq!(update.invoice.id, invoice_id);
+    assert_eq!(update.state, InvoiceState::Paid);
+    assert_eq!(update.invoice.received, 2_000u64.into());
+    assert_eq!(update.invoice.paid_by.len(), 2);
+    assert_eq!(
+        update.key,
+        invoice_key(&fvk.wallet_id(), &update.invoice.address_index)
+    );
+}
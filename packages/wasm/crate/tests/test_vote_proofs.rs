@@ -0,0 +1,123 @@
+extern crate penumbra_wasm;
+use decaf377::Fr;
+use penumbra_asset::Value;
+use penumbra_funding::liquidity_tournament::proof::LiquidityTournamentVoteProofPublic;
+use penumbra_funding::liquidity_tournament::ActionLiquidityTournamentVotePlan;
+use penumbra_governance::{DelegatorVotePlan, DelegatorVoteProofPublic, Vote};
+use penumbra_keys::keys::AddressIndex;
+use penumbra_proof_params::DELEGATOR_VOTE_PROOF_VERIFICATION_KEY;
+use penumbra_shielded_pool::Note;
+use penumbra_stake::{DelegationToken, IdentityKey};
+use penumbra_tct::{Tree, Witness};
+use penumbra_transaction::{Action, ActionPlan, TransactionPlan, WitnessData};
+use penumbra_wasm::build::build_serial_inner;
+use penumbra_wasm::keys::{load_proving_key, proving_key_status_inner, ProvingKeyType};
+use rand_core::OsRng;
+use std::str::FromStr;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::utils::fixtures::{spend_key, VALIDATOR};
+
+mod utils;
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn delegator_and_liquidity_tournament_votes_prove_in_one_session() {
+    let delegator_vote_key: &[u8] = include_bytes!("../../../keys/keys/delegator_vote_pk.bin");
+    load_proving_key(delegator_vote_key, "delegatorVote").expect("can load delegator vote key");
+    load_proving_key(delegator_vote_key, "actionLiquidityTournamentVote")
+        .expect("can load liquidity tournament vote key");
+
+    let status = proving_key_status_inner();
+    assert!(status.loaded.contains(&ProvingKeyType::DelegatorVote));
+    assert!(status
+        .loaded
+        .contains(&ProvingKeyType::ActionLiquidityTournamentVote));
+
+    let spend_key = spend_key();
+    let fvk = spend_key.full_viewing_key();
+    let address = fvk.payment_address(AddressIndex::new(0)).0;
+    let delegation_token = DelegationToken::from(IdentityKey::from_str(VALIDATOR).unwrap());
+    let value = Value {
+        amount: 1_000u64.into(),
+        asset_id: delegation_token.id(),
+    };
+
+    // Both notes must exist before the vote starts, at the beginning of the next epoch.
+    let mut sct = Tree::new();
+    let delegator_note = Note::generate(&mut OsRng, &address, value);
+    let lqt_note = Note::generate(&mut OsRng, &address, value);
+    let delegator_position = sct.insert(Witness::Keep, delegator_note.commit()).unwrap();
+    let lqt_position = sct.insert(Witness::Keep, lqt_note.commit()).unwrap();
+    sct.end_epoch().unwrap();
+    let start_position = sct.position().unwrap();
+
+    let plan = TransactionPlan {
+        actions: vec![
+            ActionPlan::DelegatorVote(DelegatorVotePlan::new(
+                &mut OsRng,
+                1,
+                start_position,
+                Vote::Yes,
+                delegator_note.clone(),
+                delegator_position,
+                1_000u64.into(),
+            )),
+            ActionLiquidityTournamentVotePlan::new(
+                &mut OsRng,
+                delegation_token.denom().base_denom(),
+                address,
+                lqt_note.clone(),
+                lqt_position,
+                start_position,
+            )
+            .into(),
+        ],
+        ..Default::default()
+    };
+    let auth_data = plan.authorize(OsRng, &spend_key).unwrap();
+    let witness_data = WitnessData {
+        anchor: sct.root(),
+        state_commitment_proofs: [delegator_note.commit(), lqt_note.commit()]
+            .into_iter()
+            .map(|commitment| (commitment, sct.witness(commitment).unwrap()))
+            .collect(),
+    };
+    let tx = build_serial_inner(fvk.clone(), plan, witness_data, auth_data).unwrap();
+
+    let actions: Vec<&Action> = tx.actions().collect();
+    let [Action::DelegatorVote(delegator_vote), Action::ActionLiquidityTournamentVote(lqt_vote)] =
+        actions.as_slice()
+    else {
+        panic!("unexpected actions: {actions:?}");
+    };
+
+    delegator_vote
+        .proof
+        .verify(
+            &DELEGATOR_VOTE_PROOF_VERIFICATION_KEY,
+            DelegatorVoteProofPublic {
+                anchor: sct.root(),
+                balance_commitment: delegator_vote.body.value.commit(Fr::from(0u64)),
+                nullifier: delegator_vote.body.nullifier,
+                rk: delegator_vote.body.rk,
+                start_position,
+            },
+        )
+        .expect("delegator vote proof verifies");
+
+    lqt_vote
+        .proof
+        .verify(
+            &DELEGATOR_VOTE_PROOF_VERIFICATION_KEY,
+            LiquidityTournamentVoteProofPublic {
+                anchor: sct.root(),
+                value: lqt_vote.body.value,
+                nullifier: lqt_vote.body.nullifier,
+                rk: lqt_vote.body.rk,
+                start_position,
+            },
+        )
+        .expect("liquidity tournament vote proof verifies");
+}
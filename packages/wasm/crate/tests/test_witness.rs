@@ -14,16 +14,19 @@ use penumbra_proto::DomainType;
 use penumbra_sct::params::SctParameters;
 use penumbra_sct::{CommitmentSource, Nullifier};
 use penumbra_shielded_pool::fmd::Parameters;
-use penumbra_shielded_pool::{Note, Rseed};
-use penumbra_tct::storage::StoredPosition;
-use penumbra_tct::{Forgotten, Position, StateCommitment};
+use penumbra_shielded_pool::{Note, Rseed, SpendPlan};
+use penumbra_tct::storage::{StoredPosition, Updates};
+use penumbra_tct::{Forgotten, Position, StateCommitment, Tree, Witness};
+use penumbra_transaction::{ActionPlan, TransactionPlan};
 use penumbra_wasm::database::interface::Database;
 use penumbra_wasm::database::mock::{get_mock_tables, MockDb};
+use penumbra_wasm::error::WasmError;
 use penumbra_wasm::note_record::SpendableNoteRecord;
 use penumbra_wasm::planner::plan_transaction_inner;
-use penumbra_wasm::storage::{byte_array_to_base64, Storage};
-use penumbra_wasm::tx::witness_inner;
+use penumbra_wasm::storage::{byte_array_to_base64, Storage, Tables};
+use penumbra_wasm::tx::{clear_witness_cache, witness_from_storage_inner, witness_inner};
 use penumbra_wasm::view_server::StoredTree;
+use rand_core::OsRng;
 use std::str::FromStr;
 pub use utils::planner_setup::*;
 use utils::sct::{
@@ -299,3 +302,167 @@ async fn test_witness() {
     let s = StoredPosition::Position(Position::default());
     sct_tree.last_position = Some(s);
 }
+
+/// Writes the SCT rows produced since `last_position` the way the extension persists them.
+async fn store_sct_updates(mock_db: &MockDb, tables: &Tables, updates: Updates) {
+    if let Some(position) = updates.set_position {
+        mock_db
+            .put_with_key(&tables.tree_last_position, "last_position", &position)
+            .await
+            .unwrap();
+    }
+    if let Some(forgotten) = updates.set_forgotten {
+        mock_db
+            .put_with_key(&tables.tree_last_forgotten, "last_forgotten", &forgotten)
+            .await
+            .unwrap();
+    }
+    for commitment in &updates.store_commitments {
+        mock_db
+            .put_with_key(
+                &tables.tree_commitments,
+                byte_array_to_base64(&commitment.commitment.to_proto().inner),
+                commitment,
+            )
+            .await
+            .unwrap();
+    }
+    for hash in &updates.store_hashes {
+        mock_db
+            .put_with_key(
+                &tables.tree_hashes,
+                format!("{:?}-{}", hash.position, hash.height),
+                hash,
+            )
+            .await
+            .unwrap();
+    }
+}
+
+fn spend_of(note: &Note, position: Position) -> TransactionPlan {
+    TransactionPlan {
+        actions: vec![ActionPlan::Spend(SpendPlan::new(
+            &mut OsRng,
+            note.clone(),
+            position,
+        ))],
+        ..Default::default()
+    }
+}
+
+#[wasm_bindgen_test]
+async fn witness_from_storage_reads_the_stored_tree() {
+    // Tests share the thread's cache, so start from an empty one.
+    clear_witness_cache();
+    let mock_db = MockDb::new();
+    let tables = get_mock_tables();
+    let storage = Storage::new(mock_db.clone(), tables.clone()).unwrap();
+
+    let fvk = FullViewingKey::from_str("penumbrafullviewingkey1mnm04x7yx5tyznswlp0sxs8nsxtgxr9p98dp0msuek8fzxuknuzawjpct8zdevcvm3tsph0wvsuw33x2q42e7sf29q904hwerma8xzgrxsgq2").unwrap();
+    let address = fvk.payment_address(AddressIndex::new(0)).0;
+    let value = Value {
+        amount: 1_000u64.into(),
+        asset_id: *STAKING_TOKEN_ASSET_ID,
+    };
+
+    let mut sct = Tree::new();
+    let note = Note::generate(&mut OsRng, &address, value);
+    let position = sct.insert(Witness::Keep, note.commit()).unwrap();
+    let updates: Updates = sct
+        .updates(StoredPosition::default(), Forgotten::default())
+        .collect();
+    let (last_position, last_forgotten) = (updates.set_position, updates.set_forgotten);
+    store_sct_updates(&mock_db, &tables, updates).await;
+
+    let witness_data = witness_from_storage_inner(&storage, "wallet", &spend_of(&note, position))
+        .await
+        .unwrap();
+    assert_eq!(witness_data.anchor, sct.root());
+    assert!(witness_data
+        .state_commitment_proofs
+        .contains_key(&note.commit()));
+
+    // Commitments that were never synced are named in the error.
+    let unknown = Note::generate(&mut OsRng, &address, value);
+    let result =
+        witness_from_storage_inner(&storage, "wallet", &spend_of(&unknown, position)).await;
+    assert!(matches!(
+        result,
+        Err(WasmError::CommitmentNotInSct(commitment)) if commitment == unknown.commit()
+    ));
+
+    // Once more rows are synced, the cached tree is replaced.
+    let later = Note::generate(&mut OsRng, &address, value);
+    let later_position = sct.insert(Witness::Keep, later.commit()).unwrap();
+    store_sct_updates(
+        &mock_db,
+        &tables,
+        sct.updates(
+            last_position.unwrap_or_default(),
+            last_forgotten.unwrap_or_default(),
+        )
+        .collect(),
+    )
+    .await;
+
+    let witness_data =
+        witness_from_storage_inner(&storage, "wallet", &spend_of(&later, later_position))
+            .await
+            .unwrap();
+    assert_eq!(witness_data.anchor, sct.root());
+}
+
+/// A database holding an SCT with one kept note, and that note and tree.
+async fn stored_note(fvk: &FullViewingKey) -> (Storage<MockDb>, Tree, Note, Position) {
+    let mock_db = MockDb::new();
+    let tables = get_mock_tables();
+    let address = fvk.payment_address(AddressIndex::new(0)).0;
+    let value = Value {
+        amount: 1_000u64.into(),
+        asset_id: *STAKING_TOKEN_ASSET_ID,
+    };
+
+    let mut sct = Tree::new();
+    let note = Note::generate(&mut OsRng, &address, value);
+    let position = sct.insert(Witness::Keep, note.commit()).unwrap();
+    store_sct_updates(
+        &mock_db,
+        &tables,
+        sct.updates(StoredPosition::default(), Forgotten::default())
+            .collect(),
+    )
+    .await;
+
+    (Storage::new(mock_db, tables).unwrap(), sct, note, position)
+}
+
+#[wasm_bindgen_test]
+async fn witness_cache_is_replaced_per_database_and_can_be_cleared() {
+    clear_witness_cache();
+    let fvk = FullViewingKey::from_str("penumbrafullviewingkey1mnm04x7yx5tyznswlp0sxs8nsxtgxr9p98dp0msuek8fzxuknuzawjpct8zdevcvm3tsph0wvsuw33x2q42e7sf29q904hwerma8xzgrxsgq2").unwrap();
+    let (storage, sct, note, position) = stored_note(&fvk).await;
+    // Another wallet's tree, stored at the same position.
+    let (other_storage, other_sct, other_note, other_position) = stored_note(&fvk).await;
+
+    let witness_data = witness_from_storage_inner(&storage, "wallet", &spend_of(&note, position))
+        .await
+        .unwrap();
+    assert_eq!(witness_data.anchor, sct.root());
+
+    let witness_data = witness_from_storage_inner(
+        &other_storage,
+        "other-wallet",
+        &spend_of(&other_note, other_position),
+    )
+    .await
+    .unwrap();
+    assert_eq!(witness_data.anchor, other_sct.root());
+
+    // Without clearing, a database reusing the name would be served the cached tree.
+    clear_witness_cache();
+    let witness_data =
+        witness_from_storage_inner(&storage, "other-wallet", &spend_of(&note, position))
+            .await
+            .unwrap();
+    assert_eq!(witness_data.anchor, sct.root());
+}
@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use penumbra_asset::{Value, STAKING_TOKEN_ASSET_ID, STAKING_TOKEN_DENOM};
+use penumbra_keys::keys::{AddressIndex, SpendKey};
+use penumbra_keys::{Address, FullViewingKey};
+use penumbra_num::Amount;
+use penumbra_proto::core::asset::v1 as asset_pb;
+use penumbra_proto::core::component::ibc::v1 as ibc_pb;
+
+use penumbra_wasm::database::mock::{get_mock_tables, MockDb};
+use penumbra_wasm::storage::Storage;
+
+pub const SPEND_KEY: &str =
+    "penumbraspendkey1qul0huewkcmemljd5m3vz3awqt7442tjg2dudahvzu6eyj9qf0eszrnguh";
+
+/// The full viewing key of the test seed phrase.
+pub const TEST_FVK: &str = "penumbrafullviewingkey1vzfytwlvq067g2kz095vn7sgcft47hga40atrg5zu2crskm6tyyjysm28qg5nth2fqmdf5n0q530jreumjlsrcxjwtfv6zdmfpe5kqsa5lg09";
+
+/// The full viewing key of a wallet unrelated to `TEST_FVK`.
+pub const OTHER_FVK: &str = "penumbrafullviewingkey1sjeaceqzgaeye2ksnz8q73mp6rpx2ykdtzs8wurrnhwdn8vqwuxhxtjdndrjc74udjh0uch0tatnrd93q50wp9pfk86h3lgpew8lsqsz2a6la";
+
+pub const VALIDATOR: &str =
+    "penumbravalid19caff39080amxlupcjutnhcm7vh8rjfevza0hpx33pn7lntf6vyqvuekzh";
+
+pub fn spend_key() -> SpendKey {
+    SpendKey::from_str(SPEND_KEY).unwrap()
+}
+
+/// The address of account 0 of `SPEND_KEY`.
+pub fn address() -> Address {
+    spend_key()
+        .full_viewing_key()
+        .payment_address(AddressIndex::new(0))
+        .0
+}
+
+pub fn fvk() -> FullViewingKey {
+    FullViewingKey::from_str(TEST_FVK).unwrap()
+}
+
+pub fn staking_value(amount: u64) -> Value {
+    Value {
+        amount: amount.into(),
+        asset_id: *STAKING_TOKEN_ASSET_ID,
+    }
+}
+
+pub fn storage() -> Storage<MockDb> {
+    Storage::new(MockDb::new(), get_mock_tables()).unwrap()
+}
+
+/// A withdrawal of `amount` staking tokens over channel-0.
+pub fn ics20_withdrawal(amount: u64, return_address: Option<&Address>) -> ibc_pb::Ics20Withdrawal {
+    let mut withdrawal = ibc_pb::Ics20Withdrawal {
+        amount: Some(Amount::from(amount).into()),
+        denom: Some(asset_pb::Denom {
+            denom: STAKING_TOKEN_DENOM.base_denom().denom,
+        }),
+        destination_chain_address: "osmo1destination".to_string(),
+        return_address: return_address.cloned().map(Into::into),
+        timeout_height: Some(Default::default()),
+        timeout_time: 1,
+        source_channel: "channel-0".to_string(),
+        ..Default::default()
+    };
+    // A zero revision height is not a valid timeout.
+    withdrawal.timeout_height.as_mut().unwrap().revision_height = 100;
+    withdrawal
+}
@@ -1,2 +1,6 @@
+// Each test crate only uses some of the utilities.
+#![allow(dead_code)]
+
+pub mod fixtures;
 pub mod planner_setup;
 pub mod sct;
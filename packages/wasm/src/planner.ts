@@ -1,10 +1,11 @@
 import { TransactionPlan } from '@penumbra-zone/protobuf/penumbra/core/transaction/v1/transaction_pb';
 import { TransactionPlannerRequest } from '@penumbra-zone/protobuf/penumbra/view/v1/view_pb';
 import { JsonValue } from '@bufbuild/protobuf';
-import { plan_transaction } from '../wasm/index.js';
+import { plan_transaction, plan_transaction_with_options } from '../wasm/index.js';
 import type { IdbConstants } from '@penumbra-zone/types/indexed-db';
 import { FullViewingKey } from '@penumbra-zone/protobuf/penumbra/core/keys/v1/keys_pb';
 import { AssetId } from '@penumbra-zone/protobuf/penumbra/core/asset/v1/asset_pb';
+import { StateCommitment } from '@penumbra-zone/protobuf/penumbra/crypto/tct/v1/tct_pb';
 
 export const planTransaction = async (
   idbConstants: IdbConstants,
@@ -20,3 +21,29 @@ export const planTransaction = async (
   )) as JsonValue;
   return TransactionPlan.fromJson(plan);
 };
+
+export interface PlannerOptions {
+  /** Return change to the transparent address of account 0. */
+  transparentChange?: boolean;
+}
+
+export const planTransactionWithOptions = async (
+  idbConstants: IdbConstants,
+  request: TransactionPlannerRequest,
+  fullViewingKey: FullViewingKey,
+  gasFeeToken: AssetId,
+  options: PlannerOptions,
+) => {
+  const planned = (await plan_transaction_with_options(
+    idbConstants,
+    request.toBinary(),
+    fullViewingKey.toBinary(),
+    gasFeeToken.toBinary(),
+    options,
+  )) as { plan: JsonValue; changeCommitments: JsonValue[] };
+  return {
+    plan: TransactionPlan.fromJson(planned.plan),
+    /** Commitments of the outputs the planner added as change. */
+    changeCommitments: planned.changeCommitments.map(c => StateCommitment.fromJson(c)),
+  };
+};
@@ -1,4 +1,8 @@
-import { transaction_perspective_and_view, transaction_summary } from '../wasm/index.js';
+import {
+  transaction_perspective_and_view,
+  transaction_perspectives_and_views,
+  transaction_summary,
+} from '../wasm/index.js';
 import {
   Transaction,
   TransactionPerspective,
@@ -6,14 +10,23 @@ import {
   TransactionView,
 } from '@penumbra-zone/protobuf/penumbra/core/transaction/v1/transaction_pb';
 import type { IdbConstants } from '@penumbra-zone/types/indexed-db';
+import type { Jsonified } from '@penumbra-zone/types/jsonified';
 import { FullViewingKey } from '@penumbra-zone/protobuf/penumbra/core/keys/v1/keys_pb';
+import { Nullifier } from '@penumbra-zone/protobuf/penumbra/core/component/sct/v1/sct_pb';
+import { StateCommitment } from '@penumbra-zone/protobuf/penumbra/crypto/tct/v1/tct_pb';
+
+// Mirrors `PerspectiveWarning` in the wasm crate's tx.rs
+export type PerspectiveWarning =
+  | { kind: 'invalidSwapSource'; nullifier: Jsonified<Nullifier>; reason: string }
+  | { kind: 'unknownSwapClaimOutput'; commitment: Jsonified<StateCommitment> }
+  | { kind: 'unreadableMemo'; reason: string };
 
 export const generateTransactionInfo = async (
   fullViewingKey: FullViewingKey,
   tx: Transaction,
   idbConstants: IdbConstants,
 ) => {
-  const { txp, txv } = await transaction_perspective_and_view(
+  const { txp, txv, warnings } = await transaction_perspective_and_view(
     fullViewingKey.toBinary(),
     tx.toBinary(),
     idbConstants,
@@ -22,9 +35,28 @@ export const generateTransactionInfo = async (
   return {
     txp: TransactionPerspective.fromBinary(txp),
     txv: TransactionView.fromBinary(txv),
+    warnings: warnings as PerspectiveWarning[],
   };
 };
 
+export const generateTransactionInfos = async (
+  fullViewingKey: FullViewingKey,
+  txs: Transaction[],
+  idbConstants: IdbConstants,
+) => {
+  const infos = await transaction_perspectives_and_views(
+    fullViewingKey.toBinary(),
+    txs.map(tx => tx.toBinary()),
+    idbConstants,
+  );
+
+  return infos.map(({ txp, txv, warnings }) => ({
+    txp: TransactionPerspective.fromBinary(txp),
+    txv: TransactionView.fromBinary(txv),
+    warnings: warnings as PerspectiveWarning[],
+  }));
+};
+
 export const generateTransactionSummary = async (txv: TransactionView) => {
   const tx_summary = await transaction_summary(txv.toBinary());
 
@@ -16,6 +16,11 @@ describe('wasmViewServer', () => {
       version: 123,
       tables: IDB_TABLES,
     } satisfies IdbConstants;
+    const walletDataConstants = {
+      name: 'walletDataDbName',
+      version: 123,
+      tables: IDB_TABLES,
+    } satisfies IdbConstants;
 
     const storedTree = {
       hashes: [],
@@ -30,7 +35,12 @@ describe('wasmViewServer', () => {
       },
     };
 
-    const vsServer = ViewServer.new(fullViewingKey.toBinary(), storedTree, idbConstants);
+    const vsServer = ViewServer.new(
+      fullViewingKey.toBinary(),
+      storedTree,
+      idbConstants,
+      walletDataConstants,
+    );
     await expect(vsServer).resolves.not.toThrow();
   });
 });
@@ -8,11 +8,12 @@ import {
   SctUpdatesSchema,
   StateCommitmentTree,
 } from '@penumbra-zone/types/state-commitment-tree';
-import type { IdbConstants } from '@penumbra-zone/types/indexed-db';
+import type { IdbConstants, InvoiceUpdate } from '@penumbra-zone/types/indexed-db';
 import type { ViewServerInterface } from '@penumbra-zone/types/servers';
 import { Address, FullViewingKey } from '@penumbra-zone/protobuf/penumbra/core/keys/v1/keys_pb';
 import { isControlledAddress } from './address.js';
 import { SctFrontierResponse } from '@penumbra-zone/protobuf/penumbra/core/component/sct/v1/sct_pb';
+import { Transaction } from '@penumbra-zone/protobuf/penumbra/core/transaction/v1/transaction_pb';
 
 declare global {
   // eslint-disable-next-line no-var -- TODO: explain
@@ -23,6 +24,7 @@ interface BaseViewServerProps {
   fullViewingKey: FullViewingKey;
   getStoredTree: () => Promise<StateCommitmentTree>;
   idbConstants: IdbConstants;
+  walletDataConstants: IdbConstants;
 }
 
 interface SnapshotViewServerProps extends BaseViewServerProps {
@@ -34,6 +36,7 @@ interface FlushResult {
   sct_updates?: JsonObject;
   new_notes?: JsonValue[];
   new_swaps?: JsonValue[];
+  invoice_updates?: InvoiceUpdate[];
 }
 
 export class ViewServer implements ViewServerInterface {
@@ -42,34 +45,39 @@ export class ViewServer implements ViewServerInterface {
     public readonly fullViewingKey: FullViewingKey,
     private readonly getStoredTree: () => Promise<StateCommitmentTree>,
     private readonly idbConstants: IdbConstants,
+    private readonly walletDataConstants: IdbConstants,
   ) {}
 
   static async initialize({
     fullViewingKey,
     getStoredTree,
     idbConstants,
+    walletDataConstants,
   }: BaseViewServerProps): Promise<ViewServer> {
     const wvs = await WasmViewServer.new(
       fullViewingKey.toBinary(),
       await getStoredTree(),
       idbConstants,
+      walletDataConstants,
     );
-    return new this(wvs, fullViewingKey, getStoredTree, idbConstants);
+    return new this(wvs, fullViewingKey, getStoredTree, idbConstants, walletDataConstants);
   }
 
   static async initialize_from_snapshot({
     fullViewingKey,
     getStoredTree,
     idbConstants,
+    walletDataConstants,
     compact_frontier,
   }: SnapshotViewServerProps): Promise<ViewServer> {
     const wvs = await WasmViewServer.new_snapshot(
       fullViewingKey.toBinary(),
       idbConstants,
+      walletDataConstants,
       compact_frontier.compactFrontier,
     );
 
-    return new this(wvs, fullViewingKey, getStoredTree, idbConstants);
+    return new this(wvs, fullViewingKey, getStoredTree, idbConstants, walletDataConstants);
   }
 
   // Trial decrypts a chunk of state payloads in the genesis block.
@@ -102,6 +110,7 @@ export class ViewServer implements ViewServerInterface {
       this.fullViewingKey.toBinary(),
       await this.getStoredTree(),
       this.idbConstants,
+      this.walletDataConstants,
     );
   }
 
@@ -114,7 +123,7 @@ export class ViewServer implements ViewServerInterface {
   // Flush updates clears the state and returns all the updates since the last checkpoint.
   flushUpdates(): ScanBlockResult {
     const result = this.wasmViewServer.flush_updates() as FlushResult;
-    const { height, sct_updates, new_notes, new_swaps } = result;
+    const { height, sct_updates, new_notes, new_swaps, invoice_updates } = result;
     return {
       height: BigInt(height ?? 0),
       sctUpdates: globalThis.__DEV__
@@ -122,9 +131,20 @@ export class ViewServer implements ViewServerInterface {
         : (sct_updates as unknown as ScanBlockResult['sctUpdates']),
       newNotes: (new_notes ?? []).map(n => SpendableNoteRecord.fromJson(n)),
       newSwaps: (new_swaps ?? []).map(s => SwapRecord.fromJson(s)),
+      invoiceUpdates: invoice_updates ?? [],
     };
   }
 
+  // Fills in the memo's return address on the notes a transaction created for this wallet.
+  // Should be called with the transactions of blocks that contained new notes. Notes not yet
+  // flushed are updated in place; notes already saved are updated in storage.
+  async enrichTransaction(transaction: Transaction): Promise<SpendableNoteRecord[]> {
+    const records = (await this.wasmViewServer.enrich_transaction(
+      transaction.toBinary(),
+    )) as JsonValue[];
+    return records.map(r => SpendableNoteRecord.fromJson(r));
+  }
+
   isControlledAddress(address: Address): boolean {
     return isControlledAddress(this.fullViewingKey, address);
   }